@@ -578,6 +578,22 @@ fn run() {
 
     something_with_a_dtor(&|| assert_eq!(bb(1), 1));
 
+    #[cfg(all(
+        target_arch = "arm",
+        any(
+            not(target_feature = "thumb-mode"),
+            target_feature = "thumb2",
+            target_feature = "mclass"
+        ),
+    ))]
+    aeabi_flags_cmp::check();
+
+    #[cfg(all(
+        target_arch = "aarch64",
+        any(target_os = "linux", target_os = "android")
+    ))]
+    lse::check();
+
     // FIXME(#802): This should be re-enabled once a workaround is found.
     // extern "C" {
     //     fn rust_begin_unwind(x: usize);
@@ -588,6 +604,132 @@ fn run() {
     // }
 }
 
+/// The `__aeabi_cfcmp*` and `__aeabi_cdcmp*` comparisons return their result in the Z and C flags
+/// and preserve the argument registers, so they can only be called from assembly. Reading the
+/// flags needs `mrs`, which Thumb-1 only has on M-profile cores.
+#[cfg(all(
+    target_arch = "arm",
+    any(
+        not(target_feature = "thumb-mode"),
+        target_feature = "thumb2",
+        target_feature = "mclass"
+    ),
+))]
+mod aeabi_flags_cmp {
+    use core::arch::asm;
+
+    // SAFETY: defined in compiler-builtins, these are never called from Rust
+    unsafe extern "C" {
+        fn __aeabi_cfcmpeq();
+        fn __aeabi_cfcmple();
+        fn __aeabi_cfrcmple();
+        fn __aeabi_cdcmpeq();
+        fn __aeabi_cdcmple();
+        fn __aeabi_cdrcmple();
+    }
+
+    /// Call `$f` with `$args` in `r0-r3` and return the Z and C flags.
+    macro_rules! call_flags {
+        ($f:ident, $args:expr) => {{
+            let args: [u32; 4] = $args;
+            let mut regs = args;
+            let apsr: u32;
+            // SAFETY: the comparison only clobbers `ip`, `lr` and the flags
+            unsafe {
+                asm!(
+                    "bl {f}",
+                    "mrs {apsr}, apsr",
+                    f = sym $f,
+                    apsr = out(reg) apsr,
+                    inout("r0") regs[0],
+                    inout("r1") regs[1],
+                    inout("r2") regs[2],
+                    inout("r3") regs[3],
+                    out("r12") _,
+                    out("lr") _,
+                );
+            }
+            assert_eq!(regs, args, "{} clobbered its arguments", stringify!($f));
+            (apsr & (1 << 30) != 0, apsr & (1 << 29) != 0)
+        }};
+    }
+
+    /// `f64` arguments are passed as if loaded from memory, so the word order follows endianness.
+    fn f64_words(x: f64) -> [u32; 2] {
+        let bits = x.to_bits();
+        let (lo, hi) = (bits as u32, (bits >> 32) as u32);
+        if cfg!(target_endian = "big") {
+            [hi, lo]
+        } else {
+            [lo, hi]
+        }
+    }
+
+    /// The expected Z and C flags for a comparison of `a` with `b`.
+    fn expected(a: f64, b: f64) -> (bool, bool) {
+        if a < b {
+            (false, false)
+        } else {
+            (a == b, true)
+        }
+    }
+
+    pub fn check() {
+        let cases = [
+            (1.0, 2.0),
+            (2.0, 2.0),
+            (3.0, 2.0),
+            (-0.0, 0.0),
+            (f64::NEG_INFINITY, f64::INFINITY),
+            (f64::NAN, 2.0),
+            (2.0, f64::NAN),
+            (f64::NAN, f64::NAN),
+        ];
+
+        for (a, b) in cases {
+            // Junk in `r2` and `r3`, which must be preserved too.
+            let fa = (a as f32).to_bits();
+            let fb = (b as f32).to_bits();
+            let fargs = [fa, fb, 0xdead_beef, 0x0123_4567];
+            let frargs = [fb, fa, 0xdead_beef, 0x0123_4567];
+            assert_eq!(call_flags!(__aeabi_cfcmple, fargs), expected(a, b));
+            assert_eq!(call_flags!(__aeabi_cfrcmple, frargs), expected(a, b));
+            assert_eq!(call_flags!(__aeabi_cfcmpeq, fargs).0, a == b);
+
+            let [a0, a1] = f64_words(a);
+            let [b0, b1] = f64_words(b);
+            let dargs = [a0, a1, b0, b1];
+            let drargs = [b0, b1, a0, a1];
+            assert_eq!(call_flags!(__aeabi_cdcmple, dargs), expected(a, b));
+            assert_eq!(call_flags!(__aeabi_cdrcmple, drargs), expected(a, b));
+            assert_eq!(call_flags!(__aeabi_cdcmpeq, dargs).0, a == b);
+        }
+    }
+}
+
+/// The `lse-init` constructor only runs if the linker keeps it, which is what this checks.
+#[cfg(all(
+    target_arch = "aarch64",
+    any(target_os = "linux", target_os = "android")
+))]
+mod lse {
+    use core::ffi::c_ulong;
+
+    use compiler_builtins::aarch64_outline_atomics::get_have_lse_atomics;
+
+    const AT_HWCAP: c_ulong = 16;
+    const HWCAP_ATOMICS: c_ulong = 1 << 8;
+
+    unsafe extern "C" {
+        fn getauxval(ty: c_ulong) -> c_ulong;
+    }
+
+    pub fn check() {
+        let cpu_has_lse = unsafe { getauxval(AT_HWCAP) } & HWCAP_ATOMICS != 0;
+        assert_eq!(get_have_lse_atomics(), cpu_has_lse);
+    }
+}
+
 fn something_with_a_dtor(f: &dyn Fn()) {
     struct A<'a>(&'a (dyn Fn() + 'a));
 
@@ -1,10 +1,10 @@
-#![feature(test)]
+//! Wall time benchmarks comparing our memory routines against the system ones.
 
-extern crate test;
-use test::{Bencher, black_box};
+use std::hint::black_box;
 
-extern crate compiler_builtins;
 use compiler_builtins::mem::{memcmp, memcpy, memmove, memset};
+use criterion::measurement::WallTime;
+use criterion::{Bencher, BenchmarkGroup, Criterion, Throughput, criterion_main};
 
 const WORD_SIZE: usize = core::mem::size_of::<usize>();
 
@@ -43,7 +43,6 @@ impl core::ops::DerefMut for AlignedVec {
 fn memcpy_builtin(b: &mut Bencher, n: usize, offset1: usize, offset2: usize) {
     let v1 = AlignedVec::new(1, n + offset1);
     let mut v2 = AlignedVec::new(0, n + offset2);
-    b.bytes = n as u64;
     b.iter(|| {
         let src: &[u8] = black_box(&v1[offset1..]);
         let dst: &mut [u8] = black_box(&mut v2[offset2..]);
@@ -54,7 +53,6 @@ fn memcpy_builtin(b: &mut Bencher, n: usize, offset1: usize, offset2: usize) {
 fn memcpy_rust(b: &mut Bencher, n: usize, offset1: usize, offset2: usize) {
     let v1 = AlignedVec::new(1, n + offset1);
     let mut v2 = AlignedVec::new(0, n + offset2);
-    b.bytes = n as u64;
     b.iter(|| {
         let src: &[u8] = black_box(&v1[offset1..]);
         let dst: &mut [u8] = black_box(&mut v2[offset2..]);
@@ -64,7 +62,6 @@ fn memcpy_rust(b: &mut Bencher, n: usize, offset1: usize, offset2: usize) {
 
 fn memset_builtin(b: &mut Bencher, n: usize, offset: usize) {
     let mut v1 = AlignedVec::new(0, n + offset);
-    b.bytes = n as u64;
     b.iter(|| {
         let dst: &mut [u8] = black_box(&mut v1[offset..]);
         let val: u8 = black_box(27);
@@ -76,7 +73,6 @@ fn memset_builtin(b: &mut Bencher, n: usize, offset: usize) {
 
 fn memset_rust(b: &mut Bencher, n: usize, offset: usize) {
     let mut v1 = AlignedVec::new(0, n + offset);
-    b.bytes = n as u64;
     b.iter(|| {
         let dst: &mut [u8] = black_box(&mut v1[offset..]);
         let val = black_box(27);
@@ -88,7 +84,6 @@ fn memcmp_builtin(b: &mut Bencher, n: usize) {
     let v1 = AlignedVec::new(0, n);
     let mut v2 = AlignedVec::new(0, n);
     v2[n - 1] = 1;
-    b.bytes = n as u64;
     b.iter(|| {
         let s1: &[u8] = black_box(&v1);
         let s2: &[u8] = black_box(&v2);
@@ -100,7 +95,6 @@ fn memcmp_builtin_unaligned(b: &mut Bencher, n: usize) {
     let v1 = AlignedVec::new(0, n);
     let mut v2 = AlignedVec::new(0, n);
     v2[n - 1] = 1;
-    b.bytes = n as u64;
     b.iter(|| {
         let s1: &[u8] = black_box(&v1[0..]);
         let s2: &[u8] = black_box(&v2[1..]);
@@ -112,7 +106,6 @@ fn memcmp_rust(b: &mut Bencher, n: usize) {
     let v1 = AlignedVec::new(0, n);
     let mut v2 = AlignedVec::new(0, n);
     v2[n - 1] = 1;
-    b.bytes = n as u64;
     b.iter(|| {
         let s1: &[u8] = black_box(&v1);
         let s2: &[u8] = black_box(&v2);
@@ -124,7 +117,6 @@ fn memcmp_rust_unaligned(b: &mut Bencher, n: usize) {
     let v1 = AlignedVec::new(0, n);
     let mut v2 = AlignedVec::new(0, n);
     v2[n - 1] = 1;
-    b.bytes = n as u64;
     b.iter(|| {
         let s1: &[u8] = black_box(&v1[0..]);
         let s2: &[u8] = black_box(&v2[1..]);
@@ -134,7 +126,6 @@ fn memcmp_rust_unaligned(b: &mut Bencher, n: usize) {
 
 fn memmove_builtin(b: &mut Bencher, n: usize, offset: usize) {
     let mut v = AlignedVec::new(0, n + n / 2 + offset);
-    b.bytes = n as u64;
     b.iter(|| {
         let s: &mut [u8] = black_box(&mut v);
         s.copy_within(0..n, n / 2 + offset);
@@ -143,7 +134,6 @@ fn memmove_builtin(b: &mut Bencher, n: usize, offset: usize) {
 
 fn memmove_rust(b: &mut Bencher, n: usize, offset: usize) {
     let mut v = AlignedVec::new(0, n + n / 2 + offset);
-    b.bytes = n as u64;
     b.iter(|| {
         let dst: *mut u8 = black_box(&mut v[n / 2 + offset..]).as_mut_ptr();
         let src: *const u8 = black_box(&v).as_ptr();
@@ -151,214 +141,169 @@ fn memmove_rust(b: &mut Bencher, n: usize, offset: usize) {
     })
 }
 
-#[bench]
-fn memcpy_builtin_4096(b: &mut Bencher) {
-    memcpy_builtin(b, 4096, 0, 0)
-}
-#[bench]
-fn memcpy_rust_4096(b: &mut Bencher) {
-    memcpy_rust(b, 4096, 0, 0)
-}
-#[bench]
-fn memcpy_builtin_1048576(b: &mut Bencher) {
-    memcpy_builtin(b, 1048576, 0, 0)
-}
-#[bench]
-fn memcpy_rust_1048576(b: &mut Bencher) {
-    memcpy_rust(b, 1048576, 0, 0)
-}
-#[bench]
-fn memcpy_builtin_4096_offset(b: &mut Bencher) {
-    memcpy_builtin(b, 4096, 65, 65)
-}
-#[bench]
-fn memcpy_rust_4096_offset(b: &mut Bencher) {
-    memcpy_rust(b, 4096, 65, 65)
-}
-#[bench]
-fn memcpy_builtin_1048576_offset(b: &mut Bencher) {
-    memcpy_builtin(b, 1048576, 65, 65)
-}
-#[bench]
-fn memcpy_rust_1048576_offset(b: &mut Bencher) {
-    memcpy_rust(b, 1048576, 65, 65)
-}
-#[bench]
-fn memcpy_builtin_4096_misalign(b: &mut Bencher) {
-    memcpy_builtin(b, 4096, 65, 66)
-}
-#[bench]
-fn memcpy_rust_4096_misalign(b: &mut Bencher) {
-    memcpy_rust(b, 4096, 65, 66)
-}
-#[bench]
-fn memcpy_builtin_1048576_misalign(b: &mut Bencher) {
-    memcpy_builtin(b, 1048576, 65, 66)
-}
-#[bench]
-fn memcpy_rust_1048576_misalign(b: &mut Bencher) {
-    memcpy_rust(b, 1048576, 65, 66)
+/// Add a `builtin` (using `core`, which usually calls the system routine) and a `rust` (ours)
+/// benchmark for each length.
+fn compare<T: Copy>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    suffix: &str,
+    lens: &[usize],
+    args: T,
+    builtin: fn(&mut Bencher, usize, T),
+    rust: fn(&mut Bencher, usize, T),
+) {
+    for &n in lens {
+        group.throughput(Throughput::Bytes(n as u64));
+        group.bench_function(format!("builtin_{n}{suffix}"), |b| builtin(b, n, args));
+        group.bench_function(format!("rust_{n}{suffix}"), |b| rust(b, n, args));
+    }
 }
 
-#[bench]
-fn memset_builtin_4096(b: &mut Bencher) {
-    memset_builtin(b, 4096, 0)
-}
-#[bench]
-fn memset_rust_4096(b: &mut Bencher) {
-    memset_rust(b, 4096, 0)
-}
-#[bench]
-fn memset_builtin_1048576(b: &mut Bencher) {
-    memset_builtin(b, 1048576, 0)
-}
-#[bench]
-fn memset_rust_1048576(b: &mut Bencher) {
-    memset_rust(b, 1048576, 0)
-}
-#[bench]
-fn memset_builtin_4096_offset(b: &mut Bencher) {
-    memset_builtin(b, 4096, 65)
-}
-#[bench]
-fn memset_rust_4096_offset(b: &mut Bencher) {
-    memset_rust(b, 4096, 65)
-}
-#[bench]
-fn memset_builtin_1048576_offset(b: &mut Bencher) {
-    memset_builtin(b, 1048576, 65)
-}
-#[bench]
-fn memset_rust_1048576_offset(b: &mut Bencher) {
-    memset_rust(b, 1048576, 65)
+fn bench_memcpy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memcpy");
+    let lens = &[4096, 1048576];
+    let builtin = |b: &mut Bencher, n, (o1, o2)| memcpy_builtin(b, n, o1, o2);
+    let rust = |b: &mut Bencher, n, (o1, o2)| memcpy_rust(b, n, o1, o2);
+    compare(&mut group, "", lens, (0, 0), builtin, rust);
+    compare(&mut group, "_offset", lens, (65, 65), builtin, rust);
+    compare(&mut group, "_misalign", lens, (65, 66), builtin, rust);
 }
 
-#[bench]
-fn memcmp_builtin_8(b: &mut Bencher) {
-    memcmp_builtin(b, 8)
-}
-#[bench]
-fn memcmp_rust_8(b: &mut Bencher) {
-    memcmp_rust(b, 8)
-}
-#[bench]
-fn memcmp_builtin_16(b: &mut Bencher) {
-    memcmp_builtin(b, 16)
-}
-#[bench]
-fn memcmp_rust_16(b: &mut Bencher) {
-    memcmp_rust(b, 16)
-}
-#[bench]
-fn memcmp_builtin_32(b: &mut Bencher) {
-    memcmp_builtin(b, 32)
-}
-#[bench]
-fn memcmp_rust_32(b: &mut Bencher) {
-    memcmp_rust(b, 32)
-}
-#[bench]
-fn memcmp_builtin_64(b: &mut Bencher) {
-    memcmp_builtin(b, 64)
-}
-#[bench]
-fn memcmp_rust_64(b: &mut Bencher) {
-    memcmp_rust(b, 64)
-}
-#[bench]
-fn memcmp_builtin_4096(b: &mut Bencher) {
-    memcmp_builtin(b, 4096)
-}
-#[bench]
-fn memcmp_rust_4096(b: &mut Bencher) {
-    memcmp_rust(b, 4096)
-}
-#[bench]
-fn memcmp_builtin_1048576(b: &mut Bencher) {
-    memcmp_builtin(b, 1048576)
-}
-#[bench]
-fn memcmp_rust_1048576(b: &mut Bencher) {
-    memcmp_rust(b, 1048576)
-}
-#[bench]
-fn memcmp_builtin_unaligned_7(b: &mut Bencher) {
-    memcmp_builtin_unaligned(b, 8)
-}
-#[bench]
-fn memcmp_rust_unaligned_7(b: &mut Bencher) {
-    memcmp_rust_unaligned(b, 8)
-}
-#[bench]
-fn memcmp_builtin_unaligned_15(b: &mut Bencher) {
-    memcmp_builtin_unaligned(b, 16)
-}
-#[bench]
-fn memcmp_rust_unaligned_15(b: &mut Bencher) {
-    memcmp_rust_unaligned(b, 16)
-}
-#[bench]
-fn memcmp_builtin_unaligned_31(b: &mut Bencher) {
-    memcmp_builtin_unaligned(b, 32)
-}
-#[bench]
-fn memcmp_rust_unaligned_31(b: &mut Bencher) {
-    memcmp_rust_unaligned(b, 32)
-}
-#[bench]
-fn memcmp_builtin_unaligned_63(b: &mut Bencher) {
-    memcmp_builtin_unaligned(b, 64)
-}
-#[bench]
-fn memcmp_rust_unaligned_63(b: &mut Bencher) {
-    memcmp_rust_unaligned(b, 64)
-}
-#[bench]
-fn memcmp_builtin_unaligned_4095(b: &mut Bencher) {
-    memcmp_builtin_unaligned(b, 4096)
-}
-#[bench]
-fn memcmp_rust_unaligned_4095(b: &mut Bencher) {
-    memcmp_rust_unaligned(b, 4096)
-}
-#[bench]
-fn memcmp_builtin_unaligned_1048575(b: &mut Bencher) {
-    memcmp_builtin_unaligned(b, 1048576)
-}
-#[bench]
-fn memcmp_rust_unaligned_1048575(b: &mut Bencher) {
-    memcmp_rust_unaligned(b, 1048576)
+fn bench_memset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memset");
+    let lens = &[4096, 1048576];
+    compare(&mut group, "", lens, 0, memset_builtin, memset_rust);
+    compare(&mut group, "_offset", lens, 65, memset_builtin, memset_rust);
 }
 
-#[bench]
-fn memmove_builtin_4096(b: &mut Bencher) {
-    memmove_builtin(b, 4096, 0)
-}
-#[bench]
-fn memmove_rust_4096(b: &mut Bencher) {
-    memmove_rust(b, 4096, 0)
-}
-#[bench]
-fn memmove_builtin_1048576(b: &mut Bencher) {
-    memmove_builtin(b, 1048576, 0)
-}
-#[bench]
-fn memmove_rust_1048576(b: &mut Bencher) {
-    memmove_rust(b, 1048576, 0)
-}
-#[bench]
-fn memmove_builtin_4096_misalign(b: &mut Bencher) {
-    memmove_builtin(b, 4096, 1)
+fn bench_memcmp(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memcmp");
+    let lens = &[8, 16, 32, 64, 4096, 1048576];
+    let builtin = |b: &mut Bencher, n, ()| memcmp_builtin(b, n);
+    let rust = |b: &mut Bencher, n, ()| memcmp_rust(b, n);
+    compare(&mut group, "", lens, (), builtin, rust);
+    let builtin = |b: &mut Bencher, n, ()| memcmp_builtin_unaligned(b, n);
+    let rust = |b: &mut Bencher, n, ()| memcmp_rust_unaligned(b, n);
+    compare(&mut group, "_unaligned", lens, (), builtin, rust);
 }
-#[bench]
-fn memmove_rust_4096_misalign(b: &mut Bencher) {
-    memmove_rust(b, 4096, 1)
+
+fn bench_memmove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memmove");
+    let lens = &[4096, 1048576];
+    compare(&mut group, "", lens, 0, memmove_builtin, memmove_rust);
+    compare(
+        &mut group,
+        "_misalign",
+        lens,
+        1,
+        memmove_builtin,
+        memmove_rust,
+    );
 }
-#[bench]
-fn memmove_builtin_1048576_misalign(b: &mut Bencher) {
-    memmove_builtin(b, 1048576, 1)
+
+/// Compare the implementations that get selected between at runtime on x86-64. Those that need
+/// features this CPU doesn't have are skipped.
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch"))]
+mod x86_64 {
+    use compiler_builtins::mem::x86_64::*;
+
+    use super::*;
+
+    type CopyFn = unsafe fn(*mut u8, *const u8, usize);
+    type SetFn = unsafe fn(*mut u8, u8, usize);
+
+    const SIZES: &[usize] = &[16, 64, 256, 1024, 4096, 65536, 1048576];
+
+    fn avx2() -> bool {
+        std::is_x86_feature_detected!("avx2")
+    }
+
+    fn copy_forward_variants() -> Vec<(&'static str, CopyFn)> {
+        let mut v: Vec<(&str, CopyFn)> = vec![
+            ("rep_movsb", copy_forward_rep_movsb),
+            ("rep_movsq", copy_forward_rep_movsq),
+        ];
+        if avx2() {
+            v.push(("avx2", copy_forward_avx2));
+            v.push(("ermsb_avx2", copy_forward_ermsb_avx2));
+        }
+        v
+    }
+
+    fn copy_backward_variants() -> Vec<(&'static str, CopyFn)> {
+        let mut v: Vec<(&str, CopyFn)> = vec![("rep_movsq", copy_backward_rep_movsq)];
+        if avx2() {
+            v.push(("avx2", copy_backward_avx2));
+        }
+        v
+    }
+
+    fn set_bytes_variants() -> Vec<(&'static str, SetFn)> {
+        let mut v: Vec<(&str, SetFn)> = vec![
+            ("rep_stosb", set_bytes_rep_stosb),
+            ("rep_stosq", set_bytes_rep_stosq),
+        ];
+        if avx2() {
+            v.push(("avx2", set_bytes_avx2));
+            v.push(("ermsb_avx2", set_bytes_ermsb_avx2));
+        }
+        v
+    }
+
+    pub fn bench_variants(c: &mut Criterion) {
+        let mut group = c.benchmark_group("x86_64_copy_forward");
+        for &n in SIZES {
+            let v1 = AlignedVec::new(1, n + 1);
+            let mut v2 = AlignedVec::new(0, n);
+            group.throughput(Throughput::Bytes(n as u64));
+            for (name, f) in copy_forward_variants() {
+                group.bench_function(format!("{name}_{n}"), |b| {
+                    b.iter(|| unsafe {
+                        f(black_box(v2.as_mut_ptr()), black_box(v1[1..].as_ptr()), n)
+                    })
+                });
+            }
+        }
+        group.finish();
+
+        let mut group = c.benchmark_group("x86_64_copy_backward");
+        for &n in SIZES {
+            let mut v = AlignedVec::new(0, n + n / 2 + 1);
+            group.throughput(Throughput::Bytes(n as u64));
+            for (name, f) in copy_backward_variants() {
+                group.bench_function(format!("{name}_{n}"), |b| {
+                    b.iter(|| unsafe {
+                        let dst = black_box(v[n / 2 + 1..].as_mut_ptr());
+                        f(dst, black_box(v.as_ptr()), n)
+                    })
+                });
+            }
+        }
+        group.finish();
+
+        let mut group = c.benchmark_group("x86_64_set_bytes");
+        for &n in SIZES {
+            let mut v = AlignedVec::new(0, n + 1);
+            group.throughput(Throughput::Bytes(n as u64));
+            for (name, f) in set_bytes_variants() {
+                group.bench_function(format!("{name}_{n}"), |b| {
+                    b.iter(|| unsafe { f(black_box(v[1..].as_mut_ptr()), black_box(27), n) })
+                });
+            }
+        }
+        group.finish();
+    }
 }
-#[bench]
-fn memmove_rust_1048576_misalign(b: &mut Bencher) {
-    memmove_rust(b, 1048576, 1)
+
+pub fn mem() {
+    let mut criterion = Criterion::default().configure_from_args();
+
+    bench_memcpy(&mut criterion);
+    bench_memset(&mut criterion);
+    bench_memcmp(&mut criterion);
+    bench_memmove(&mut criterion);
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch"))]
+    x86_64::bench_variants(&mut criterion);
 }
+
+criterion_main!(mem);
@@ -492,9 +492,250 @@ mod mmove {
     library_benchmark_group!(name = memmove, benchmarks = [forward_move, backward_move]);
 }
 
+/// The implementations that get selected between at runtime on x86-64. Those that need AVX2 are
+/// skipped if the CPU doesn't have it, which shows up as a near-empty benchmark.
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch"))]
+mod x86_64 {
+    use compiler_builtins::mem::x86_64::*;
+
+    use super::*;
+
+    type CopyFn = unsafe fn(*mut u8, *const u8, usize);
+    type SetFn = unsafe fn(*mut u8, u8, usize);
+
+    struct Cfg<V> {
+        variant: V,
+        len: usize,
+    }
+
+    fn avx2() -> bool {
+        std::is_x86_feature_detected!("avx2")
+    }
+
+    #[derive(Clone, Copy)]
+    enum Forward {
+        RepMovsb,
+        RepMovsq,
+        Avx2,
+        ErmsbAvx2,
+    }
+
+    impl Forward {
+        fn func(self) -> Option<CopyFn> {
+            match self {
+                Forward::RepMovsb => Some(copy_forward_rep_movsb),
+                Forward::RepMovsq => Some(copy_forward_rep_movsq),
+                Forward::Avx2 => avx2().then_some(copy_forward_avx2 as CopyFn),
+                Forward::ErmsbAvx2 => avx2().then_some(copy_forward_ermsb_avx2 as CopyFn),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum Backward {
+        RepMovsq,
+        Avx2,
+    }
+
+    impl Backward {
+        fn func(self) -> Option<CopyFn> {
+            match self {
+                Backward::RepMovsq => Some(copy_backward_rep_movsq),
+                Backward::Avx2 => avx2().then_some(copy_backward_avx2 as CopyFn),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum Set {
+        RepStosb,
+        RepStosq,
+        Avx2,
+        ErmsbAvx2,
+    }
+
+    impl Set {
+        fn func(self) -> Option<SetFn> {
+            match self {
+                Set::RepStosb => Some(set_bytes_rep_stosb),
+                Set::RepStosq => Some(set_bytes_rep_stosq),
+                Set::Avx2 => avx2().then_some(set_bytes_avx2 as SetFn),
+                Set::ErmsbAvx2 => avx2().then_some(set_bytes_ermsb_avx2 as SetFn),
+            }
+        }
+    }
+
+    fn setup_forward(
+        Cfg { variant, len }: Cfg<Forward>,
+    ) -> (Option<CopyFn>, usize, AlignedSlice, AlignedSlice) {
+        println!("bytes: {len}, src offset: 1");
+        let mut src = AlignedSlice::new_zeroed(len, 1);
+        src.fill(1);
+        (variant.func(), len, src, AlignedSlice::new_zeroed(len, 0))
+    }
+
+    fn setup_backward(
+        Cfg { variant, len }: Cfg<Backward>,
+    ) -> (Option<CopyFn>, usize, usize, AlignedSlice) {
+        let spread = (len / 2) + 1;
+        println!("bytes: {len}, spread: {spread}");
+        let buf = AlignedSlice::new_zeroed(len + spread, 0);
+        (variant.func(), len, spread, buf)
+    }
+
+    fn setup_set(Cfg { variant, len }: Cfg<Set>) -> (Option<SetFn>, usize, AlignedSlice) {
+        println!("bytes: {len}, offset: 1");
+        (variant.func(), len, AlignedSlice::new_zeroed(len, 1))
+    }
+
+    #[library_benchmark]
+    #[benches::rep_movsb(
+        args = [
+            Cfg { variant: Forward::RepMovsb, len: 16 },
+            Cfg { variant: Forward::RepMovsb, len: 64 },
+            Cfg { variant: Forward::RepMovsb, len: 512 },
+            Cfg { variant: Forward::RepMovsb, len: 4096 },
+            Cfg { variant: Forward::RepMovsb, len: MEG1 },
+        ],
+        setup = setup_forward,
+    )]
+    #[benches::rep_movsq(
+        args = [
+            Cfg { variant: Forward::RepMovsq, len: 16 },
+            Cfg { variant: Forward::RepMovsq, len: 64 },
+            Cfg { variant: Forward::RepMovsq, len: 512 },
+            Cfg { variant: Forward::RepMovsq, len: 4096 },
+            Cfg { variant: Forward::RepMovsq, len: MEG1 },
+        ],
+        setup = setup_forward,
+    )]
+    #[benches::avx2(
+        args = [
+            Cfg { variant: Forward::Avx2, len: 16 },
+            Cfg { variant: Forward::Avx2, len: 64 },
+            Cfg { variant: Forward::Avx2, len: 512 },
+            Cfg { variant: Forward::Avx2, len: 4096 },
+            Cfg { variant: Forward::Avx2, len: MEG1 },
+        ],
+        setup = setup_forward,
+    )]
+    #[benches::ermsb_avx2(
+        args = [
+            Cfg { variant: Forward::ErmsbAvx2, len: 16 },
+            Cfg { variant: Forward::ErmsbAvx2, len: 64 },
+            Cfg { variant: Forward::ErmsbAvx2, len: 512 },
+            Cfg { variant: Forward::ErmsbAvx2, len: 4096 },
+            Cfg { variant: Forward::ErmsbAvx2, len: MEG1 },
+        ],
+        setup = setup_forward,
+    )]
+    fn copy_forward((f, len, src, mut dst): (Option<CopyFn>, usize, AlignedSlice, AlignedSlice)) {
+        if let Some(f) = f {
+            unsafe {
+                f(
+                    black_box(dst.as_mut_ptr()),
+                    black_box(src.as_ptr()),
+                    black_box(len),
+                )
+            }
+        }
+    }
+
+    #[library_benchmark]
+    #[benches::rep_movsq(
+        args = [
+            Cfg { variant: Backward::RepMovsq, len: 16 },
+            Cfg { variant: Backward::RepMovsq, len: 64 },
+            Cfg { variant: Backward::RepMovsq, len: 512 },
+            Cfg { variant: Backward::RepMovsq, len: 4096 },
+            Cfg { variant: Backward::RepMovsq, len: MEG1 },
+        ],
+        setup = setup_backward,
+    )]
+    #[benches::avx2(
+        args = [
+            Cfg { variant: Backward::Avx2, len: 16 },
+            Cfg { variant: Backward::Avx2, len: 64 },
+            Cfg { variant: Backward::Avx2, len: 512 },
+            Cfg { variant: Backward::Avx2, len: 4096 },
+            Cfg { variant: Backward::Avx2, len: MEG1 },
+        ],
+        setup = setup_backward,
+    )]
+    fn copy_backward((f, len, spread, mut buf): (Option<CopyFn>, usize, usize, AlignedSlice)) {
+        // Move toward the end of the buffer, which copies backward
+        if let Some(f) = f {
+            unsafe {
+                f(
+                    black_box(buf[spread..].as_mut_ptr()),
+                    black_box(buf.as_ptr()),
+                    black_box(len),
+                )
+            }
+        }
+    }
+
+    #[library_benchmark]
+    #[benches::rep_stosb(
+        args = [
+            Cfg { variant: Set::RepStosb, len: 16 },
+            Cfg { variant: Set::RepStosb, len: 64 },
+            Cfg { variant: Set::RepStosb, len: 512 },
+            Cfg { variant: Set::RepStosb, len: 4096 },
+            Cfg { variant: Set::RepStosb, len: MEG1 },
+        ],
+        setup = setup_set,
+    )]
+    #[benches::rep_stosq(
+        args = [
+            Cfg { variant: Set::RepStosq, len: 16 },
+            Cfg { variant: Set::RepStosq, len: 64 },
+            Cfg { variant: Set::RepStosq, len: 512 },
+            Cfg { variant: Set::RepStosq, len: 4096 },
+            Cfg { variant: Set::RepStosq, len: MEG1 },
+        ],
+        setup = setup_set,
+    )]
+    #[benches::avx2(
+        args = [
+            Cfg { variant: Set::Avx2, len: 16 },
+            Cfg { variant: Set::Avx2, len: 64 },
+            Cfg { variant: Set::Avx2, len: 512 },
+            Cfg { variant: Set::Avx2, len: 4096 },
+            Cfg { variant: Set::Avx2, len: MEG1 },
+        ],
+        setup = setup_set,
+    )]
+    #[benches::ermsb_avx2(
+        args = [
+            Cfg { variant: Set::ErmsbAvx2, len: 16 },
+            Cfg { variant: Set::ErmsbAvx2, len: 64 },
+            Cfg { variant: Set::ErmsbAvx2, len: 512 },
+            Cfg { variant: Set::ErmsbAvx2, len: 4096 },
+            Cfg { variant: Set::ErmsbAvx2, len: MEG1 },
+        ],
+        setup = setup_set,
+    )]
+    fn set_bytes((f, len, mut dst): (Option<SetFn>, usize, AlignedSlice)) {
+        if let Some(f) = f {
+            unsafe { f(black_box(dst.as_mut_ptr()), black_box(27), black_box(len)) }
+        }
+    }
+
+    library_benchmark_group!(
+        name = x86_64_variants,
+        benchmarks = [copy_forward, copy_backward, set_bytes]
+    );
+}
+
 use mcmp::memcmp;
 use mcpy::memcpy;
 use mmove::memmove;
 use mset::memset;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch"))]
+use x86_64::x86_64_variants;
 
+#[cfg(not(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch")))]
 main!(library_benchmark_groups = [memcpy, memset, memcmp, memmove]);
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch"))]
+main!(library_benchmark_groups = [memcpy, memset, memcmp, memmove, x86_64_variants]);
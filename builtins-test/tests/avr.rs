@@ -0,0 +1,365 @@
+//! Tests for the AVR assembly helpers, which can't run on the host. Instead their source is run by
+//! a small interpreter for the instructions they use.
+//!
+//! Besides the results, this checks that nothing outside the registers each function documents
+//! is changed, since avr-gcc relies on that.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use builtins_test::*;
+
+const SOURCES: &[&str] = &[
+    include_str!("../../compiler-builtins/src/int/mul.rs"),
+    include_str!("../../compiler-builtins/src/int/sdiv.rs"),
+    include_str!("../../compiler-builtins/src/int/udiv.rs"),
+];
+
+/// An instruction and up to two operands. Registers are their number, and branch targets are
+/// the index of an instruction.
+struct Insn {
+    op: String,
+    args: [usize; 2],
+}
+
+/// The instructions of the naked function `name`.
+fn asm_of(name: &str) -> Vec<Insn> {
+    let header = format!("fn {name}()");
+    let src = SOURCES
+        .iter()
+        .find_map(|src| Some(&src[src.find(&header)?..]))
+        .unwrap_or_else(|| panic!("`{name}` not found"));
+    let body = &src[src.find("naked_asm!(").unwrap()..];
+    let lines: Vec<&str> = body
+        .lines()
+        .skip(1)
+        .take_while(|line| line.trim() != ");")
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix('"')?;
+            Some(&line[..line.find('"').unwrap()])
+        })
+        .collect();
+
+    // The index of the instruction following line `i`, skipping labels.
+    let index = |i: usize| lines[..i].iter().filter(|l| !l.ends_with(':')).count();
+    let operand = |i: usize, s: &str| -> usize {
+        if let Some(reg) = s.strip_prefix('R') {
+            reg.parse().unwrap()
+        } else if let Some(hex) = s.strip_prefix("0x") {
+            usize::from_str_radix(hex, 16).unwrap()
+        } else if let Some(label) = s.strip_suffix('f') {
+            let def = format!("{label}:");
+            index((i + 1..).find(|&j| lines[j] == def).unwrap())
+        } else if let Some(label) = s.strip_suffix('b') {
+            let def = format!("{label}:");
+            index((0..i).rev().find(|&j| lines[j] == def).unwrap())
+        } else {
+            s.parse().unwrap()
+        }
+    };
+
+    let mut insns = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.ends_with(':') {
+            continue;
+        }
+        let (op, args) = line.split_once(' ').unwrap_or((line, ""));
+        let mut insn = Insn {
+            op: op.to_owned(),
+            args: [0; 2],
+        };
+        for (j, arg) in args
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .enumerate()
+        {
+            insn.args[j] = operand(i, arg);
+        }
+        insns.push(insn);
+    }
+    insns
+}
+
+#[derive(Clone)]
+struct Cpu {
+    r: [u8; 32],
+    c: bool,
+    z: bool,
+    t: bool,
+}
+
+impl Cpu {
+    /// Registers with values that are easy to recognize, except for the zero register R1.
+    fn new() -> Self {
+        let mut r: [u8; 32] = std::array::from_fn(|i| 0xa5 ^ (i as u8 * 7));
+        r[1] = 0;
+        Self {
+            r,
+            c: false,
+            z: false,
+            t: false,
+        }
+    }
+
+    fn set(&mut self, lo: usize, bytes: &[u8]) {
+        self.r[lo..][..bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn get(&self, lo: usize, len: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes[..len].copy_from_slice(&self.r[lo..][..len]);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Run `insns` until they return.
+    fn run(&mut self, insns: &[Insn]) {
+        let mut pc = 0;
+        for _ in 0..100_000 {
+            let Insn { op, args: [d, s] } = &insns[pc];
+            let (d, s, k) = (*d, *s, *s as u8);
+            let r = &mut self.r;
+            pc += 1;
+            match op.as_str() {
+                "ret" => return,
+                "rjmp" => pc = d,
+                "brne" | "breq" | "brlo" | "brcs" | "brcc" | "brsh" | "brtc" | "brts" => {
+                    let taken = match op.as_str() {
+                        "brne" => !self.z,
+                        "breq" => self.z,
+                        "brlo" | "brcs" => self.c,
+                        "brcc" | "brsh" => !self.c,
+                        "brtc" => !self.t,
+                        _ => self.t,
+                    };
+                    if taken {
+                        pc = d;
+                    }
+                }
+                "sbrc" | "sbrs" => {
+                    let set = r[d] & (1 << k) != 0;
+                    if set == (op == "sbrs") {
+                        pc += 1;
+                    }
+                }
+                "bst" => self.t = r[d] & (1 << k) != 0,
+                "ldi" => r[d] = k,
+                "mov" => r[d] = r[s],
+                "cp" | "cpc" | "sub" | "sbc" | "sbci" => {
+                    let rhs = if op == "sbci" { k } else { r[s] };
+                    let with_carry = matches!(op.as_str(), "cpc" | "sbc" | "sbci");
+                    let carry = with_carry && self.c;
+                    let res = r[d].wrapping_sub(rhs).wrapping_sub(carry.into());
+                    self.c = u16::from(r[d]) < u16::from(rhs) + u16::from(carry);
+                    // With carry, Z is only kept if it was already set.
+                    self.z = res == 0 && (!with_carry || self.z);
+                    if !op.starts_with("cp") {
+                        r[d] = res;
+                    }
+                }
+                "add" | "adc" => {
+                    let carry = op == "adc" && self.c;
+                    let res = u16::from(r[d]) + u16::from(r[s]) + u16::from(carry);
+                    r[d] = res as u8;
+                    self.c = res > 0xff;
+                    self.z = r[d] == 0;
+                }
+                _ => {
+                    // Single register operations that set Z.
+                    let old = r[d];
+                    r[d] = match op.as_str() {
+                        "clr" => 0,
+                        "inc" => old.wrapping_add(1),
+                        "dec" => old.wrapping_sub(1),
+                        "eor" => old ^ r[s],
+                        "sbr" => old | k,
+                        "com" => {
+                            self.c = true;
+                            !old
+                        }
+                        "neg" => {
+                            self.c = old != 0;
+                            old.wrapping_neg()
+                        }
+                        "lsl" | "rol" => {
+                            let carry_in = op == "rol" && self.c;
+                            self.c = old & 0x80 != 0;
+                            (old << 1) | u8::from(carry_in)
+                        }
+                        "lsr" | "ror" => {
+                            let carry_in = op == "ror" && self.c;
+                            self.c = old & 1 != 0;
+                            (old >> 1) | (u8::from(carry_in) << 7)
+                        }
+                        _ => panic!("unsupported instruction {op}"),
+                    };
+                    self.z = r[d] == 0;
+                }
+            }
+        }
+        panic!("did not return");
+    }
+}
+
+/// Run `name` with `inputs` as `(first register, little endian bytes)`, and check that only the
+/// registers in `outputs` and `clobbers` changed. Returns the outputs.
+fn call(
+    name: &str,
+    inputs: &[(usize, &[u8])],
+    outputs: &[(usize, usize)],
+    clobbers: &[usize],
+) -> Vec<u32> {
+    thread_local! {
+        static CODE: RefCell<HashMap<String, Rc<[Insn]>>> = Default::default();
+    }
+    let code = CODE.with_borrow_mut(|cache| {
+        cache
+            .entry(name.to_owned())
+            .or_insert_with(|| asm_of(name).into())
+            .clone()
+    });
+
+    let mut cpu = Cpu::new();
+    for &(lo, bytes) in inputs {
+        cpu.set(lo, bytes);
+    }
+    let before = cpu.clone();
+    cpu.run(&code);
+
+    for i in 0..32 {
+        let written = outputs.iter().any(|&(lo, len)| (lo..lo + len).contains(&i));
+        if !written && !clobbers.contains(&i) {
+            assert_eq!(cpu.r[i], before.r[i], "{name} changed R{i}");
+        }
+    }
+    outputs.iter().map(|&(lo, len)| cpu.get(lo, len)).collect()
+}
+
+#[test]
+fn mulqi3() {
+    for a in 0..=u8::MAX {
+        for b in 0..=u8::MAX {
+            let out = call("__mulqi3", &[(24, &[a]), (22, &[b])], &[(24, 1)], &[0, 22]);
+            assert_eq!(out[0], u32::from(a.wrapping_mul(b)), "{a} * {b}");
+        }
+    }
+}
+
+#[test]
+fn mulhi3() {
+    fuzz_2(N, |a: u16, b: u16| {
+        let (a_bytes, b_bytes) = (a.to_le_bytes(), b.to_le_bytes());
+        let out = call(
+            "__mulhi3",
+            &[(24, &a_bytes), (22, &b_bytes)],
+            &[(24, 2)],
+            &[0, 21, 22, 23],
+        );
+        assert_eq!(out[0], u32::from(a.wrapping_mul(b)), "{a} * {b}");
+    });
+}
+
+#[test]
+fn mulpsi3() {
+    fuzz_2(N, |a: u32, b: u32| {
+        let (a, b) = (a & 0xff_ffff, b & 0xff_ffff);
+        let out = call(
+            "__mulpsi3",
+            &[(22, &a.to_le_bytes()[..3]), (18, &b.to_le_bytes()[..3])],
+            &[(22, 3)],
+            &[0, 25, 26, 27],
+        );
+        assert_eq!(out[0], a.wrapping_mul(b) & 0xff_ffff, "{a} * {b}");
+    });
+}
+
+#[test]
+fn mulsi3() {
+    fuzz_2(N, |a: u32, b: u32| {
+        let out = call(
+            "__mulsi3",
+            &[(22, &a.to_le_bytes()), (18, &b.to_le_bytes())],
+            &[(22, 4)],
+            &[0, 26, 27],
+        );
+        assert_eq!(out[0], a.wrapping_mul(b), "{a} * {b}");
+    });
+}
+
+#[test]
+fn umulhisi3() {
+    fuzz_2(N, |a: u16, b: u16| {
+        let out = call(
+            "__umulhisi3",
+            &[(26, &a.to_le_bytes()), (18, &b.to_le_bytes())],
+            &[(22, 4)],
+            &[0],
+        );
+        assert_eq!(out[0], u32::from(a) * u32::from(b), "{a} * {b}");
+    });
+}
+
+#[test]
+fn udivmodqi4() {
+    for n in 0..=u8::MAX {
+        for d in 1..=u8::MAX {
+            let out = call(
+                "__udivmodqi4",
+                &[(24, &[n]), (22, &[d])],
+                &[(24, 1), (25, 1)],
+                &[23],
+            );
+            assert_eq!(out, [u32::from(n / d), u32::from(n % d)], "{n} / {d}");
+        }
+    }
+}
+
+#[test]
+fn divmodqi4() {
+    for n in i8::MIN..=i8::MAX {
+        for d in (i8::MIN..=i8::MAX).filter(|&d| d != 0) {
+            let out = call(
+                "__divmodqi4",
+                &[(24, &n.to_le_bytes()), (22, &d.to_le_bytes())],
+                &[(24, 1), (25, 1)],
+                &[0, 22, 23],
+            );
+            let expected = [n.wrapping_div(d) as u8, n.wrapping_rem(d) as u8];
+            assert_eq!(out, expected.map(u32::from), "{n} / {d}");
+        }
+    }
+}
+
+#[test]
+fn udivmodhi4() {
+    fuzz_2(N, |n: u16, d: u16| {
+        if d == 0 {
+            return;
+        }
+        let out = call(
+            "__udivmodhi4",
+            &[(24, &n.to_le_bytes()), (22, &d.to_le_bytes())],
+            &[(22, 2), (24, 2)],
+            &[21, 26, 27],
+        );
+        assert_eq!(out, [u32::from(n / d), u32::from(n % d)], "{n} / {d}");
+    });
+}
+
+#[test]
+fn divmodhi4() {
+    fuzz_2(N, |n: i16, d: i16| {
+        if d == 0 {
+            return;
+        }
+        let out = call(
+            "__divmodhi4",
+            &[(24, &n.to_le_bytes()), (22, &d.to_le_bytes())],
+            &[(22, 2), (24, 2)],
+            &[0, 21, 26, 27],
+        );
+        let expected = [n.wrapping_div(d) as u16, n.wrapping_rem(d) as u16];
+        assert_eq!(out, expected.map(u32::from), "{n} / {d}");
+    });
+}
@@ -0,0 +1,137 @@
+//! Tests for `__clear_cache`, used the way a JIT would: code is written to memory, the cache is
+//! cleared and the code is run. The same memory is then rewritten with different code, which has
+//! to be seen instead of stale instructions.
+
+#![cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "mips",
+        target_arch = "mips32r6",
+        target_arch = "mips64",
+        target_arch = "mips64r6",
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+    )
+))]
+
+use std::ffi::{c_int, c_long, c_void};
+
+use compiler_builtins::clear_cache::__clear_cache;
+
+const PROT_READ: c_int = 1;
+const PROT_WRITE: c_int = 2;
+const PROT_EXEC: c_int = 4;
+const MAP_PRIVATE: c_int = 0x02;
+#[cfg(not(any(
+    target_arch = "mips",
+    target_arch = "mips32r6",
+    target_arch = "mips64",
+    target_arch = "mips64r6"
+)))]
+const MAP_ANONYMOUS: c_int = 0x20;
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips32r6",
+    target_arch = "mips64",
+    target_arch = "mips64r6"
+))]
+const MAP_ANONYMOUS: c_int = 0x800;
+
+const LEN: usize = 0x10000;
+
+unsafe extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: c_long,
+    ) -> *mut c_void;
+    fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+/// Machine code for a function that returns `value`.
+fn returns(value: u8) -> Vec<u8> {
+    let words: &[u32] = if cfg!(target_arch = "aarch64") {
+        // movz w0, #value; ret
+        &[0x5280_0000 | (u32::from(value) << 5), 0xd65f_03c0]
+    } else if cfg!(target_arch = "arm") {
+        // Always A32, since the address is even: mov r0, #value; bx lr
+        &[0xe3a0_0000 | u32::from(value), 0xe12f_ff1e]
+    } else if cfg!(any(target_arch = "mips32r6", target_arch = "mips64r6")) {
+        // jalr $zero, $ra; addiu $v0, $zero, value (in the delay slot)
+        &[0x03e0_0009, 0x2402_0000 | u32::from(value)]
+    } else if cfg!(any(target_arch = "mips", target_arch = "mips64")) {
+        // jr $ra; addiu $v0, $zero, value (in the delay slot)
+        &[0x03e0_0008, 0x2402_0000 | u32::from(value)]
+    } else {
+        // li a0, value; ret
+        &[(u32::from(value) << 20) | 0x0000_0513, 0x0000_8067]
+    };
+    words.iter().flat_map(|w| w.to_ne_bytes()).collect()
+}
+
+/// A mapping that is either writable or executable.
+struct Jit(*mut u8);
+
+impl Jit {
+    fn new() -> Self {
+        let p = unsafe {
+            mmap(
+                std::ptr::null_mut(),
+                LEN,
+                PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert!(!p.is_null() && p.addr() != usize::MAX, "mmap failed");
+        Self(p.cast())
+    }
+
+    fn protect(&self, prot: c_int) {
+        assert_eq!(unsafe { mprotect(self.0.cast(), LEN, prot) }, 0);
+    }
+
+    /// Write `code` at `offset`, flush it and call it.
+    fn run(&self, offset: usize, code: &[u8]) -> u32 {
+        self.protect(PROT_READ | PROT_WRITE);
+        let start = unsafe { self.0.add(offset) };
+        unsafe {
+            start.copy_from_nonoverlapping(code.as_ptr(), code.len());
+            __clear_cache(start, start.add(code.len()));
+        }
+        self.protect(PROT_READ | PROT_EXEC);
+        let f: extern "C" fn() -> u32 = unsafe { std::mem::transmute(start) };
+        f()
+    }
+}
+
+impl Drop for Jit {
+    fn drop(&mut self) {
+        unsafe { munmap(self.0.cast(), LEN) };
+    }
+}
+
+#[test]
+fn rewrite_and_run() {
+    let jit = Jit::new();
+    // The last two put the code across a cache line and a page boundary.
+    for offset in [0, 0x40 - 4, 0x1000 - 4] {
+        for value in [1, 42, 0x7f, 0, 0xff] {
+            let ret = jit.run(offset, &returns(value));
+            assert_eq!(ret, u32::from(value), "offset {offset:#x}");
+        }
+    }
+}
+
+#[test]
+fn empty_range() {
+    let jit = Jit::new();
+    unsafe { __clear_cache(jit.0, jit.0) };
+}
@@ -0,0 +1,146 @@
+//! Tests for the x86_64 `__atomic_*_16` functions, both with `cmpxchg16b` and with the lock table.
+
+#![cfg(target_arch = "x86_64")]
+
+use std::ffi::c_int;
+use std::sync::{Barrier, Mutex};
+use std::thread;
+
+use builtins_test::*;
+use compiler_builtins::sync::x86_64::*;
+
+const RELAXED: c_int = 0;
+const SEQ_CST: c_int = 5;
+
+#[track_caller]
+fn with_maybe_cmpxchg16b(use_cmpxchg16b: bool, f: impl FnOnce()) {
+    // Ensure tests run in parallel don't interleave global settings
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _g = LOCK.lock().unwrap();
+    let old = get_have_cmpxchg16b();
+    // safety: as the caller of the unsafe fn `set_have_cmpxchg16b`, we have to ensure the CPU
+    // supports `cmpxchg16b`. This is why we make this assertion.
+    if use_cmpxchg16b || old {
+        assert!(std::arch::is_x86_feature_detected!("cmpxchg16b"));
+    }
+    unsafe { set_have_cmpxchg16b(use_cmpxchg16b) };
+    f();
+    unsafe { set_have_cmpxchg16b(old) };
+}
+
+/// Run `f` without `cmpxchg16b`, then with it if the CPU has it.
+fn with_both(f: impl Fn()) {
+    with_maybe_cmpxchg16b(false, &f);
+    if std::arch::is_x86_feature_detected!("cmpxchg16b") {
+        with_maybe_cmpxchg16b(true, &f);
+    }
+}
+
+/// `u128` is only 8-byte aligned on older compilers, `cmpxchg16b` needs 16.
+#[repr(align(16))]
+struct Aligned(u128);
+
+#[test]
+fn detected() {
+    assert_eq!(
+        get_have_cmpxchg16b(),
+        std::arch::is_x86_feature_detected!("cmpxchg16b")
+    );
+}
+
+#[test]
+fn load_store_exchange() {
+    with_both(|| {
+        fuzz_2(N, |x: u128, y: u128| unsafe {
+            let mut a = Aligned(x);
+            assert_eq!(__atomic_load_16(&a.0, SEQ_CST), x);
+            __atomic_store_16(&mut a.0, y, SEQ_CST);
+            assert_eq!(a.0, y);
+            assert_eq!(__atomic_exchange_16(&mut a.0, x, RELAXED), y);
+            assert_eq!(a.0, x);
+        });
+    });
+}
+
+#[test]
+fn compare_exchange() {
+    with_both(|| {
+        fuzz_2(N, |x: u128, y: u128| unsafe {
+            let mut a = Aligned(x);
+            let mut expected = x;
+            assert!(__atomic_compare_exchange_16(
+                &mut a.0,
+                &mut expected,
+                y,
+                SEQ_CST,
+                SEQ_CST
+            ));
+            assert_eq!((a.0, expected), (y, x));
+
+            // Only the high half differs.
+            let mut expected = y ^ (1 << 100);
+            assert!(!__atomic_compare_exchange_16(
+                &mut a.0,
+                &mut expected,
+                x,
+                SEQ_CST,
+                RELAXED
+            ));
+            assert_eq!((a.0, expected), (y, y));
+        });
+    });
+}
+
+#[test]
+fn fetch_ops() {
+    type Op = (
+        unsafe extern "C" fn(*mut u128, u128, c_int) -> u128,
+        fn(u128, u128) -> u128,
+    );
+    let ops: [Op; 6] = [
+        (__atomic_fetch_add_16, |a, b| a.wrapping_add(b)),
+        (__atomic_fetch_sub_16, |a, b| a.wrapping_sub(b)),
+        (__atomic_fetch_and_16, |a, b| a & b),
+        (__atomic_fetch_or_16, |a, b| a | b),
+        (__atomic_fetch_xor_16, |a, b| a ^ b),
+        (__atomic_fetch_nand_16, |a, b| !(a & b)),
+    ];
+    with_both(|| {
+        fuzz_2(N, |x: u128, y: u128| {
+            for (i, (f, op)) in ops.iter().enumerate() {
+                let mut a = Aligned(x);
+                assert_eq!(unsafe { f(&mut a.0, y, SEQ_CST) }, x, "op {i}");
+                assert_eq!(a.0, op(x, y), "op {i}: {x:#x}, {y:#x}");
+            }
+        });
+    });
+}
+
+/// Adds with a carry into the high half from several threads, so lost updates or torn values
+/// show up in the total.
+#[test]
+fn concurrent_fetch_add() {
+    const THREADS: usize = 8;
+    const ITERATIONS: usize = 20_000;
+    const STEP: u128 = (1 << 64) - 1;
+
+    struct Shared(*mut u128);
+    unsafe impl Sync for Shared {}
+
+    with_both(|| {
+        let mut a = Aligned(0);
+        let p = &Shared(&mut a.0);
+        let start = &Barrier::new(THREADS);
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move || {
+                    start.wait();
+                    for _ in 0..ITERATIONS {
+                        unsafe { __atomic_fetch_add_16(p.0, STEP, RELAXED) };
+                    }
+                });
+            }
+        });
+        assert_eq!(a.0, STEP * (THREADS * ITERATIONS) as u128);
+    });
+}
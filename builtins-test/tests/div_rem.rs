@@ -1,3 +1,4 @@
+#![cfg_attr(f16_enabled, feature(f16))]
 #![feature(f128)]
 #![allow(unused_macros, unused_features)]
 
@@ -143,6 +144,11 @@ macro_rules! float {
 mod float_div {
     use super::*;
 
+    #[cfg(f16_enabled)]
+    float! {
+        f16, __divhf3, Half, all();
+    }
+
     float! {
         f32, __divsf3, Single, all();
         f64, __divdf3, Double, all();
@@ -1,5 +1,6 @@
 #![allow(unused_macros, unused_features)]
 #![cfg_attr(f128_enabled, feature(f128))]
+#![cfg_attr(f16_enabled, feature(f16))]
 
 #[cfg_attr(x86_no_sse2, allow(unused))]
 use builtins_test::*;
@@ -53,20 +54,27 @@ macro_rules! pow {
     };
 }
 
+#[cfg(f16_enabled)]
+pow! {
+    f16, 1e-2, __powihf2, not(no_sys_f16);
+}
+
 #[cfg(not(x86_no_sse2))] // FIXME(i586): failure for powidf2
 pow! {
     f32, 1e-4, __powisf2, all();
     f64, 1e-12, __powidf2, all();
 }
 
+// The system `powi` squares in `f128` and can be tens of ULP off for large exponents; the
+// result here is checked against MPFR in `libm-test`.
 #[cfg(f128_enabled)]
 #[cfg(not(any(target_arch = "powerpc", target_arch = "powerpc64")))]
 pow! {
-    f128, 1e-36, __powitf2, not(no_sys_f128);
+    f128, 1e-28, __powitf2, not(no_sys_f128);
 }
 
 #[cfg(f128_enabled)]
 #[cfg(any(target_arch = "powerpc", target_arch = "powerpc64"))]
 pow! {
-    f128, 1e-36, __powikf2, not(no_sys_f128);
+    f128, 1e-28, __powikf2, not(no_sys_f128);
 }
@@ -0,0 +1,334 @@
+//! Tests for the `__atomic_*` library calls, including with several threads accessing the same
+//! objects.
+
+#![feature(macro_metavar_expr_concat)]
+#![cfg(target_has_atomic = "8")]
+
+use std::ffi::c_int;
+use std::sync::Barrier;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+
+use builtins_test::*;
+use compiler_builtins::sync::libatomic::*;
+
+const RELAXED: c_int = 0;
+const ACQUIRE: c_int = 2;
+const RELEASE: c_int = 3;
+const SEQ_CST: c_int = 5;
+
+const THREADS: usize = 8;
+const ITERATIONS: usize = 50_000;
+
+/// Wrapper to share a raw pointer between threads.
+#[derive(Clone, Copy)]
+struct Shared<T>(*mut T);
+
+unsafe impl<T> Send for Shared<T> {}
+unsafe impl<T> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    /// Closures capture the whole wrapper through this, rather than just the pointer.
+    fn get(self) -> *mut T {
+        self.0
+    }
+}
+
+macro_rules! sized {
+    ($($mod:ident: $ty:ty, $bytes:literal;)*) => {$(
+        mod $mod {
+            use super::*;
+
+            #[test]
+            fn load_store_exchange() {
+                fuzz_2(N, |x: $ty, y: $ty| unsafe {
+                    let mut a = x;
+                    assert_eq!(${concat(__atomic_load_, $bytes)}(&a, SEQ_CST), x);
+                    ${concat(__atomic_store_, $bytes)}(&mut a, y, RELEASE);
+                    assert_eq!(a, y);
+                    assert_eq!(${concat(__atomic_exchange_, $bytes)}(&mut a, x, RELAXED), y);
+                    assert_eq!(a, x);
+                });
+            }
+
+            #[test]
+            fn compare_exchange() {
+                fuzz_2(N, |x: $ty, y: $ty| unsafe {
+                    let mut a = x;
+                    let mut expected = x;
+                    let f = ${concat(__atomic_compare_exchange_, $bytes)};
+                    assert!(f(&mut a, &mut expected, y, SEQ_CST, SEQ_CST));
+                    assert_eq!((a, expected), (y, x));
+
+                    let mut expected = y.wrapping_add(1);
+                    assert!(!f(&mut a, &mut expected, x, SEQ_CST, ACQUIRE));
+                    assert_eq!((a, expected), (y, y));
+                });
+            }
+
+            #[test]
+            fn fetch_ops() {
+                type Op = (unsafe extern "C" fn(*mut $ty, $ty, c_int) -> $ty, fn($ty, $ty) -> $ty);
+                let ops: [Op; 6] = [
+                    (${concat(__atomic_fetch_add_, $bytes)}, |a, b| a.wrapping_add(b)),
+                    (${concat(__atomic_fetch_sub_, $bytes)}, |a, b| a.wrapping_sub(b)),
+                    (${concat(__atomic_fetch_and_, $bytes)}, |a, b| a & b),
+                    (${concat(__atomic_fetch_or_, $bytes)}, |a, b| a | b),
+                    (${concat(__atomic_fetch_xor_, $bytes)}, |a, b| a ^ b),
+                    (${concat(__atomic_fetch_nand_, $bytes)}, |a, b| !(a & b)),
+                ];
+                fuzz_2(N, |x: $ty, y: $ty| {
+                    for (i, (f, op)) in ops.iter().enumerate() {
+                        let mut a = x;
+                        assert_eq!(unsafe { f(&mut a, y, SEQ_CST) }, x, "op {i}");
+                        assert_eq!(a, op(x, y), "op {i}: {x:#x}, {y:#x}");
+                    }
+                });
+            }
+
+            /// Every thread adds to the same value, so increments get lost if the operations
+            /// aren't atomic.
+            #[test]
+            fn concurrent_fetch_add() {
+                let mut a: $ty = 0;
+                let p = Shared(&mut a);
+                let start = &Barrier::new(THREADS);
+                thread::scope(|s| {
+                    for _ in 0..THREADS {
+                        s.spawn(move || {
+                            start.wait();
+                            for _ in 0..ITERATIONS {
+                                let f = ${concat(__atomic_fetch_add_, $bytes)};
+                                unsafe { f(p.get(), 1, RELAXED) };
+                            }
+                        });
+                    }
+                });
+                assert_eq!(a, (THREADS * ITERATIONS) as $ty);
+            }
+
+            /// Increments with a compare-exchange loop, as LLVM does for operations without a
+            /// library call.
+            #[test]
+            fn concurrent_compare_exchange() {
+                let mut a: $ty = 0;
+                let p = Shared(&mut a);
+                let start = &Barrier::new(THREADS);
+                thread::scope(|s| {
+                    for _ in 0..THREADS {
+                        s.spawn(move || unsafe {
+                            start.wait();
+                            let mut expected = ${concat(__atomic_load_, $bytes)}(p.get(), RELAXED);
+                            for _ in 0..ITERATIONS {
+                                let f = ${concat(__atomic_compare_exchange_, $bytes)};
+                                loop {
+                                    let new = expected.wrapping_add(1);
+                                    if f(p.get(), &mut expected, new, SEQ_CST, RELAXED) {
+                                        break;
+                                    }
+                                }
+                            }
+                        });
+                    }
+                });
+                assert_eq!(a, (THREADS * ITERATIONS) as $ty);
+            }
+        }
+    )*};
+}
+
+sized! {
+    sized_1: u8, 1;
+    sized_2: u16, 2;
+    sized_4: u32, 4;
+    sized_8: u64, 8;
+    sized_16: u128, 16;
+}
+
+/// Sizes the target supports lock-free have to be atomic with respect to inline atomic
+/// instructions, so some threads here use those rather than the library calls.
+#[test]
+fn concurrent_with_inline_atomics() {
+    let a = AtomicU32::new(0);
+    let p = Shared(a.as_ptr());
+    let start = &Barrier::new(THREADS);
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let a = &a;
+            s.spawn(move || {
+                start.wait();
+                for _ in 0..ITERATIONS {
+                    match t % 3 {
+                        0 => _ = a.fetch_add(1, Ordering::Relaxed),
+                        1 => _ = unsafe { __atomic_fetch_add_4(p.get(), 1, RELAXED) },
+                        _ => unsafe {
+                            // The generic call, with an aligned object of a lock-free size.
+                            let mut expected = a.load(Ordering::Relaxed);
+                            loop {
+                                let new = expected + 1;
+                                let res = __atomic_compare_exchange(
+                                    4,
+                                    p.get().cast(),
+                                    (&raw mut expected).cast(),
+                                    (&raw const new).cast(),
+                                    SEQ_CST,
+                                    RELAXED,
+                                );
+                                if res {
+                                    break;
+                                }
+                            }
+                        },
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(a.into_inner(), (THREADS * ITERATIONS) as u32);
+}
+
+/// Sizes without a sized version, including ones that aren't a power of two.
+const SIZES: &[usize] = &[1, 3, 7, 12, 16, 24, 33, 64, 100];
+
+fn bytes(len: usize, seed: u8) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u8).wrapping_mul(31) ^ seed)
+        .collect()
+}
+
+#[test]
+fn generic_load_store_exchange() {
+    for &size in SIZES {
+        let x = bytes(size, 0x12);
+        let y = bytes(size, 0xa5);
+        let mut a = x.clone();
+        let mut out = vec![0; size];
+        unsafe {
+            __atomic_load(size, a.as_ptr(), out.as_mut_ptr(), SEQ_CST);
+            assert_eq!(out, x);
+            __atomic_store(size, a.as_mut_ptr(), y.as_ptr(), RELEASE);
+            assert_eq!(a, y);
+            __atomic_exchange(size, a.as_mut_ptr(), x.as_ptr(), out.as_mut_ptr(), ACQUIRE);
+            assert_eq!((&a, &out), (&x, &y));
+
+            // The previous value can be stored over the new one.
+            let mut v = y.clone();
+            __atomic_exchange(size, a.as_mut_ptr(), v.as_ptr(), v.as_mut_ptr(), SEQ_CST);
+            assert_eq!((&a, &v), (&y, &x));
+        }
+    }
+}
+
+#[test]
+fn generic_compare_exchange() {
+    for &size in SIZES {
+        let x = bytes(size, 0x12);
+        let y = bytes(size, 0xa5);
+        let mut a = x.clone();
+        let mut expected = x.clone();
+        unsafe {
+            let res = __atomic_compare_exchange(
+                size,
+                a.as_mut_ptr(),
+                expected.as_mut_ptr(),
+                y.as_ptr(),
+                SEQ_CST,
+                SEQ_CST,
+            );
+            assert!(res);
+            assert_eq!((&a, &expected), (&y, &x));
+
+            // Differing only in the last byte.
+            expected = y.clone();
+            expected[size - 1] ^= 1;
+            let res = __atomic_compare_exchange(
+                size,
+                a.as_mut_ptr(),
+                expected.as_mut_ptr(),
+                x.as_ptr(),
+                SEQ_CST,
+                RELAXED,
+            );
+            assert!(!res);
+            assert_eq!((&a, &expected), (&y, &y));
+        }
+    }
+}
+
+/// Writers store values with every byte the same while readers check that they never see a mix
+/// of two values.
+#[test]
+fn concurrent_generic_no_tearing() {
+    const SIZE: usize = 40;
+    let mut a = [0u8; SIZE];
+    let p = Shared(a.as_mut_ptr());
+    let start = &Barrier::new(THREADS);
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            s.spawn(move || {
+                start.wait();
+                let mut buf = [0u8; SIZE];
+                for i in 0..ITERATIONS {
+                    unsafe {
+                        if t % 2 == 0 {
+                            buf.fill((i * THREADS + t) as u8);
+                            __atomic_store(SIZE, p.get(), buf.as_ptr(), SEQ_CST);
+                        } else {
+                            __atomic_load(SIZE, p.get(), buf.as_mut_ptr(), SEQ_CST);
+                            assert!(buf.iter().all(|&b| b == buf[0]), "torn read: {buf:?}");
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Each thread exchanges its own values in, so every value must come out exactly once.
+#[test]
+fn concurrent_generic_exchange() {
+    const SIZE: usize = 24;
+    let mut a = [0u8; SIZE];
+    let p = Shared(a.as_mut_ptr());
+    let start = &Barrier::new(THREADS);
+    let seen: Vec<Vec<u32>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                s.spawn(move || {
+                    start.wait();
+                    let mut seen = Vec::new();
+                    let mut buf = [0u8; SIZE];
+                    for i in 0..ITERATIONS {
+                        let val = (t * ITERATIONS + i + 1) as u32;
+                        for chunk in buf.chunks_mut(4) {
+                            chunk.copy_from_slice(&val.to_ne_bytes());
+                        }
+                        unsafe {
+                            __atomic_exchange(
+                                SIZE,
+                                p.get(),
+                                buf.as_ptr(),
+                                buf.as_mut_ptr(),
+                                RELAXED,
+                            )
+                        };
+                        let old = u32::from_ne_bytes(buf[..4].try_into().unwrap());
+                        assert!(
+                            buf.chunks(4).all(|c| c == old.to_ne_bytes()),
+                            "torn exchange"
+                        );
+                        seen.push(old);
+                    }
+                    seen
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut all: Vec<u32> = seen.into_iter().flatten().collect();
+    all.push(u32::from_ne_bytes(a[..4].try_into().unwrap()));
+    all.sort_unstable();
+    let expected: Vec<u32> = (0..=(THREADS * ITERATIONS) as u32).collect();
+    assert_eq!(all, expected);
+}
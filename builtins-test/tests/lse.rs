@@ -9,10 +9,11 @@ use compiler_builtins::aarch64_outline_atomics::{get_have_lse_atomics, set_have_
 use compiler_builtins::support::{Int, MinInt};
 use compiler_builtins::{foreach_bytes, foreach_ordering};
 
+/// Ensure tests run in parallel don't interleave global settings
+static LOCK: Mutex<()> = Mutex::new(());
+
 #[track_caller]
 fn with_maybe_lse_atomics(use_lse: bool, f: impl FnOnce()) {
-    // Ensure tests run in parallel don't interleave global settings
-    static LOCK: Mutex<()> = Mutex::new(());
     let _g = LOCK.lock().unwrap();
     let old = get_have_lse_atomics();
     // safety: as the caller of the unsafe fn `set_have_lse_atomics`, we
@@ -25,6 +26,23 @@ fn with_maybe_lse_atomics(use_lse: bool, f: impl FnOnce()) {
     unsafe { set_have_lse_atomics(old) };
 }
 
+/// LSE should have been enabled at startup if the CPU has it. Under qemu, `/proc/cpuinfo` lists
+/// the emulated CPU's features.
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn lse_detected() {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap();
+    let Some(features) = cpuinfo.lines().find(|l| l.starts_with("Features")) else {
+        // Not all kernels list the features.
+        return;
+    };
+    let cpu_has_lse = features.split_whitespace().any(|f| f == "atomics");
+
+    let _g = LOCK.lock().unwrap();
+    assert_eq!(get_have_lse_atomics(), cpu_has_lse, "{features}");
+    assert_eq!(cpu_has_lse, std::arch::is_aarch64_feature_detected!("lse"));
+}
+
 pub fn run_fuzz_tests_with_lse_variants<I: Int, F: Fn(I, I) + Copy>(n: u32, f: F)
 where
     <I as MinInt>::Unsigned: Int,
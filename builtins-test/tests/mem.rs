@@ -1,5 +1,9 @@
 extern crate compiler_builtins;
-use compiler_builtins::mem::{memcmp, memcpy, memmove, memset};
+use compiler_builtins::mem::{
+    memchr, memcmp, memcpy, memmove, memrchr, memset, strcmp, strlen, strncmp, strnlen,
+};
+use rand_xoshiro::Xoshiro128StarStar;
+use rand_xoshiro::rand_core::{Rng, SeedableRng};
 
 const WORD_SIZE: usize = core::mem::size_of::<usize>();
 
@@ -284,3 +288,521 @@ fn memset_backward_aligned() {
         assert_eq!(arr.0, reference.0);
     }
 }
+
+/// Lengths that cover each size class of the architecture-specific implementations, including
+/// lengths on either side of their chunk sizes.
+const LENS: &[usize] = &[
+    0, 1, 2, 3, 4, 5, 7, 8, 9, 15, 16, 17, 31, 32, 33, 47, 48, 63, 64, 65, 79, 80, 127, 128, 129,
+    191, 255, 256, 257, 300, 511, 512, 1000, 4095, 4096, 4097,
+];
+const MAX_OFFSET: usize = 20;
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 251) as u8 ^ 0x5a).collect()
+}
+
+#[test]
+fn memcpy_lengths_and_offsets() {
+    for &len in LENS {
+        let src = pattern(len + MAX_OFFSET);
+        for src_off in 0..MAX_OFFSET {
+            for dst_off in 0..MAX_OFFSET {
+                let mut dst = vec![0u8; len + MAX_OFFSET];
+                let mut expected = dst.clone();
+                expected[dst_off..dst_off + len].copy_from_slice(&src[src_off..src_off + len]);
+                unsafe {
+                    memcpy(
+                        dst.as_mut_ptr().add(dst_off),
+                        src.as_ptr().add(src_off),
+                        len,
+                    )
+                };
+                assert_eq!(dst, expected, "src {src_off}, dst {dst_off}, len {len}");
+            }
+        }
+    }
+}
+
+#[test]
+fn memmove_lengths_and_offsets() {
+    for &len in LENS {
+        for src_off in 0..MAX_OFFSET {
+            for dst_off in 0..MAX_OFFSET {
+                let mut buf = pattern(len + MAX_OFFSET);
+                let mut expected = buf.clone();
+                expected.copy_within(src_off..src_off + len, dst_off);
+                let ptr = buf.as_mut_ptr();
+                unsafe { memmove(ptr.add(dst_off), ptr.add(src_off), len) };
+                assert_eq!(buf, expected, "src {src_off}, dst {dst_off}, len {len}");
+            }
+        }
+    }
+}
+
+#[test]
+fn memset_lengths_and_offsets() {
+    for &len in LENS {
+        for off in 0..MAX_OFFSET {
+            // Zero separately since it may use different instructions.
+            for c in [0, 0xa5] {
+                let mut buf = pattern(len + MAX_OFFSET);
+                let mut expected = buf.clone();
+                expected[off..off + len].fill(c);
+                unsafe { memset(buf.as_mut_ptr().add(off), c.into(), len) };
+                assert_eq!(buf, expected, "c {c:#x}, off {off}, len {len}");
+            }
+        }
+    }
+}
+
+#[test]
+fn memcmp_lengths_and_offsets() {
+    for &len in LENS.iter().filter(|&&len| len <= 512) {
+        for off in 0..MAX_OFFSET {
+            let a = pattern(len + MAX_OFFSET);
+            let a = &a[off..off + len];
+            assert_eq!(unsafe { memcmp(a.as_ptr(), a.to_vec().as_ptr(), len) }, 0);
+
+            // The larger byte has its top bit set to check the bytes are compared as unsigned.
+            for pos in 0..len {
+                let mut lo = a.to_vec();
+                let mut hi = a.to_vec();
+                lo[pos] = 0x10;
+                hi[pos] = 0x90;
+                let res = unsafe { memcmp(lo.as_ptr(), hi.as_ptr(), len) };
+                assert!(res < 0, "off {off}, len {len}, pos {pos}: {res}");
+                let res = unsafe { memcmp(hi.as_ptr(), lo.as_ptr(), len) };
+                assert!(res > 0, "off {off}, len {len}, pos {pos}: {res}");
+            }
+        }
+    }
+}
+
+/// Random bytes from a small alphabet, so that searched for bytes and NULs turn up often.
+fn random_bytes(rng: &mut Xoshiro128StarStar, len: usize) -> Vec<u8> {
+    const ALPHABET: &[u8] = &[0, 1, b'a', b'b', 0x7f, 0x80, 0xff];
+    // Vary how common NULs are, between none and about one in ten bytes.
+    let zero_weight = rng.next_u32() % 8;
+    (0..len)
+        .map(|_| {
+            let r = rng.next_u32();
+            if r % 64 < zero_weight {
+                0
+            } else {
+                ALPHABET[1 + (r as usize >> 8) % (ALPHABET.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+fn naive_strlen(s: &[u8]) -> usize {
+    s.iter().position(|&b| b == 0).unwrap_or(s.len())
+}
+
+fn naive_strncmp(a: &[u8], b: &[u8], n: usize) -> core::cmp::Ordering {
+    for i in 0..n {
+        if a[i] != b[i] || a[i] == 0 {
+            return a[i].cmp(&b[i]);
+        }
+    }
+    core::cmp::Ordering::Equal
+}
+
+/// A buffer of three pages with the middle one page aligned, for placing strings against page
+/// boundaries.
+struct Pages(Vec<u8>);
+
+impl Pages {
+    const SIZE: usize = 4096;
+
+    fn new() -> Self {
+        Self(vec![0; Self::SIZE * 4])
+    }
+
+    /// The middle page and the next one.
+    fn get(&mut self) -> &mut [u8] {
+        let start = self.0.as_ptr().align_offset(Self::SIZE) + Self::SIZE;
+        &mut self.0[start..start + Self::SIZE * 2]
+    }
+}
+
+#[test]
+fn memchr_fuzz() {
+    let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+    for _ in 0..10_000 {
+        let len = rng.next_u32() as usize % 300;
+        let off = rng.next_u32() as usize % 32;
+        let buf = random_bytes(&mut rng, off + len);
+        let s = &buf[off..];
+        let c = [0, 1, b'a', 0xff, b'z'][rng.next_u32() as usize % 5];
+
+        let res = unsafe { memchr(s.as_ptr(), c.into(), len) };
+        let expected = s.iter().position(|&b| b == c);
+        assert_eq!(
+            (!res.is_null()).then(|| res as usize - s.as_ptr() as usize),
+            expected,
+            "memchr {s:x?}, {c:#x}",
+        );
+
+        let res = unsafe { memrchr(s.as_ptr(), c.into(), len) };
+        let expected = s.iter().rposition(|&b| b == c);
+        assert_eq!(
+            (!res.is_null()).then(|| res as usize - s.as_ptr() as usize),
+            expected,
+            "memrchr {s:x?}, {c:#x}",
+        );
+    }
+}
+
+#[test]
+fn memchr_c_is_truncated() {
+    let s = [1u8, 2, 3, 0xff];
+    assert_eq!(
+        unsafe { memchr(s.as_ptr(), 0x1ff, 4) },
+        s[3..].as_ptr().cast_mut()
+    );
+    assert_eq!(
+        unsafe { memrchr(s.as_ptr(), -255, 4) },
+        s.as_ptr().cast_mut()
+    );
+}
+
+#[test]
+fn strlen_fuzz() {
+    let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+    for _ in 0..10_000 {
+        let len = rng.next_u32() as usize % 300;
+        let off = rng.next_u32() as usize % 32;
+        let mut buf = random_bytes(&mut rng, off + len + 1);
+        buf[off + len] = 0;
+        let s = &buf[off..];
+        let expected = naive_strlen(s);
+
+        assert_eq!(
+            unsafe { strlen(s.as_ptr().cast()) },
+            expected,
+            "strlen {s:x?}"
+        );
+        let n = rng.next_u32() as usize % (len + 2);
+        assert_eq!(
+            unsafe { strnlen(s.as_ptr().cast(), n) },
+            expected.min(n),
+            "strnlen {s:x?}, {n}",
+        );
+    }
+}
+
+#[test]
+fn strcmp_fuzz() {
+    let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+    for _ in 0..10_000 {
+        let len = rng.next_u32() as usize % 300;
+        let off_a = rng.next_u32() as usize % 32;
+        let off_b = rng.next_u32() as usize % 32;
+        let mut a = random_bytes(&mut rng, off_a + len + 1);
+        a[off_a + len] = 0;
+        let a = &a[off_a..];
+
+        // Mostly a copy of `a` with a few changes, so that the strings share a prefix.
+        let mut b = vec![0; off_b];
+        b.extend_from_slice(a);
+        for _ in 0..rng.next_u32() % 3 {
+            let pos = off_b + rng.next_u32() as usize % (len + 1);
+            b[pos] = random_bytes(&mut rng, 1)[0];
+        }
+        let b = &b[off_b..];
+
+        let res = unsafe { strcmp(a.as_ptr().cast(), b.as_ptr().cast()) };
+        let expected = naive_strncmp(a, b, len + 1);
+        assert_eq!(res.cmp(&0), expected, "strcmp {a:x?}, {b:x?}");
+
+        let n = rng.next_u32() as usize % (len + 2);
+        let res = unsafe { strncmp(a.as_ptr().cast(), b.as_ptr().cast(), n) };
+        let expected = naive_strncmp(a, b, n);
+        assert_eq!(res.cmp(&0), expected, "strncmp {a:x?}, {b:x?}, {n}");
+    }
+}
+
+#[test]
+fn string_fns_at_page_boundaries() {
+    let mut pages = Pages::new();
+    let mut other = Pages::new();
+    let buf = pages.get();
+    let other = other.get();
+    for len in 0..40 {
+        for end in Pages::SIZE - 20..Pages::SIZE + 20 {
+            let Some(start) = end.checked_sub(len + 1) else {
+                continue;
+            };
+            buf.fill(0xaa);
+            buf[start..end - 1].fill(b'a');
+            buf[end - 1] = 0;
+            let s = &buf[start..end];
+
+            // Compare against a string ending at a different point relative to its page.
+            for other_end in [Pages::SIZE - 7, Pages::SIZE, Pages::SIZE + 9] {
+                other.fill(0x55);
+                let other_start = other_end - len - 1;
+                other[other_start..other_end].copy_from_slice(s);
+                other[other_end - 2.min(len + 1)] = b'b';
+                let t = &other[other_start..other_end];
+
+                let res = unsafe { strcmp(s.as_ptr().cast(), t.as_ptr().cast()) };
+                assert_eq!(
+                    res.cmp(&0),
+                    naive_strncmp(s, t, len + 1),
+                    "len {len}, end {end}"
+                );
+                let res = unsafe { strncmp(s.as_ptr().cast(), t.as_ptr().cast(), len) };
+                assert_eq!(
+                    res.cmp(&0),
+                    naive_strncmp(s, t, len),
+                    "len {len}, end {end}"
+                );
+            }
+
+            assert_eq!(unsafe { strlen(s.as_ptr().cast()) }, len);
+            assert_eq!(unsafe { strnlen(s.as_ptr().cast(), len + 1) }, len);
+            assert_eq!(unsafe { strnlen(s.as_ptr().cast(), len / 2) }, len / 2);
+            let nul = unsafe { memchr(s.as_ptr(), 0, len + 1) };
+            assert_eq!(nul, s[len..].as_ptr().cast_mut());
+            let last = unsafe { memrchr(s.as_ptr(), b'a'.into(), len + 1) };
+            let expected = if len == 0 {
+                core::ptr::null()
+            } else {
+                &s[len - 1]
+            };
+            assert_eq!(last.cast_const(), expected);
+        }
+    }
+}
+
+/// Tests for the `__llvm_*_element_unordered_atomic_*` intrinsics with element type `$ty`, at
+/// different offsets of the buffers and lengths.
+macro_rules! element_unordered_atomic {
+    ($(
+        $(#[$attr:meta])*
+        mod $mod:ident: $ty:ty, $memcpy:ident, $memmove:ident, $memset:ident;
+    )*) => {$(
+        $(#[$attr])*
+        mod $mod {
+            use compiler_builtins::mem::{$memcpy, $memmove, $memset};
+
+            const SIZE: usize = core::mem::size_of::<$ty>();
+            const ELEMENTS: usize = 64;
+            const MAX_OFFSET: usize = 8;
+            const MAX_LEN: usize = 32;
+
+            /// Elements with distinct bytes, xored with `seed`.
+            fn pattern(seed: u8) -> Vec<$ty> {
+                let byte = |i: usize, j: usize| (i * SIZE + j) as u8 ^ seed;
+                (0..ELEMENTS)
+                    .map(|i| <$ty>::from_ne_bytes(core::array::from_fn(|j| byte(i, j))))
+                    .collect()
+            }
+
+            #[test]
+            fn memcpy() {
+                let src = pattern(0);
+                for src_off in 0..MAX_OFFSET {
+                    for dst_off in 0..MAX_OFFSET {
+                        for len in 0..=MAX_LEN {
+                            let mut dst = pattern(0x55);
+                            let mut expected = dst.clone();
+                            expected[dst_off..dst_off + len]
+                                .copy_from_slice(&src[src_off..src_off + len]);
+                            unsafe {
+                                $memcpy(
+                                    dst.as_mut_ptr().add(dst_off),
+                                    src.as_ptr().add(src_off),
+                                    len * SIZE,
+                                )
+                            };
+                            assert_eq!(dst, expected, "src {src_off}, dst {dst_off}, len {len}");
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn memmove() {
+                // Offsets up to twice the maximum make both overlapping and disjoint ranges.
+                for src_off in 0..MAX_OFFSET * 2 {
+                    for dst_off in 0..MAX_OFFSET * 2 {
+                        for len in 0..=MAX_LEN {
+                            let mut buf = pattern(0);
+                            let mut expected = buf.clone();
+                            expected.copy_within(src_off..src_off + len, dst_off);
+                            let ptr = buf.as_mut_ptr();
+                            unsafe { $memmove(ptr.add(dst_off), ptr.add(src_off), len * SIZE) };
+                            assert_eq!(buf, expected, "src {src_off}, dst {dst_off}, len {len}");
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn memset() {
+                for c in [0, 0x01, 0xa5, 0xff] {
+                    for off in 0..MAX_OFFSET {
+                        for len in 0..=MAX_LEN {
+                            let mut buf = pattern(0x55);
+                            let mut expected = buf.clone();
+                            expected[off..off + len].fill(<$ty>::from_ne_bytes([c; SIZE]));
+                            unsafe { $memset(buf.as_mut_ptr().add(off), c, len * SIZE) };
+                            assert_eq!(buf, expected, "c {c:#x}, off {off}, len {len}");
+                        }
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+element_unordered_atomic! {
+    mod element_unordered_atomic_1: u8,
+        __llvm_memcpy_element_unordered_atomic_1,
+        __llvm_memmove_element_unordered_atomic_1,
+        __llvm_memset_element_unordered_atomic_1;
+    mod element_unordered_atomic_2: u16,
+        __llvm_memcpy_element_unordered_atomic_2,
+        __llvm_memmove_element_unordered_atomic_2,
+        __llvm_memset_element_unordered_atomic_2;
+    mod element_unordered_atomic_4: u32,
+        __llvm_memcpy_element_unordered_atomic_4,
+        __llvm_memmove_element_unordered_atomic_4,
+        __llvm_memset_element_unordered_atomic_4;
+    mod element_unordered_atomic_8: u64,
+        __llvm_memcpy_element_unordered_atomic_8,
+        __llvm_memmove_element_unordered_atomic_8,
+        __llvm_memset_element_unordered_atomic_8;
+    #[cfg(target_has_atomic = "128")]
+    mod element_unordered_atomic_16: u128,
+        __llvm_memcpy_element_unordered_atomic_16,
+        __llvm_memmove_element_unordered_atomic_16,
+        __llvm_memset_element_unordered_atomic_16;
+}
+
+/// Each of the implementations that `memcpy`, `memmove` and `memset` select between at runtime,
+/// since only one of them gets used through the public functions.
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", feature = "arch"))]
+mod x86_64_variants {
+    use compiler_builtins::mem::x86_64::*;
+
+    type CopyFn = unsafe fn(*mut u8, *const u8, usize);
+    type SetFn = unsafe fn(*mut u8, u8, usize);
+
+    const MAX_OFFSET: usize = 8;
+    /// Lengths around the vector size and the loop boundaries, and some on either side of the
+    /// threshold for the string instructions.
+    const LENS: &[usize] = &[
+        0, 1, 7, 8, 15, 16, 31, 32, 33, 63, 64, 65, 95, 96, 97, 255, 256, 2047, 2048, 2049, 3001,
+    ];
+
+    fn avx2() -> bool {
+        std::is_x86_feature_detected!("avx2")
+    }
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+    }
+
+    fn check_forward(name: &str, f: CopyFn) {
+        for src_off in 0..MAX_OFFSET {
+            for dst_off in 0..MAX_OFFSET {
+                for &len in LENS {
+                    // Disjoint
+                    let src = pattern(len + MAX_OFFSET);
+                    let mut dst = vec![0u8; len + MAX_OFFSET];
+                    let mut expected = dst.clone();
+                    expected[dst_off..dst_off + len].copy_from_slice(&src[src_off..src_off + len]);
+                    unsafe {
+                        f(
+                            dst.as_mut_ptr().add(dst_off),
+                            src.as_ptr().add(src_off),
+                            len,
+                        )
+                    };
+                    assert_eq!(
+                        dst, expected,
+                        "{name} src {src_off}, dst {dst_off}, len {len}"
+                    );
+
+                    // Overlapping with the destination before the source
+                    let mut buf = pattern(len + 2 * MAX_OFFSET);
+                    let src_off = dst_off + src_off;
+                    let mut expected = buf.clone();
+                    expected.copy_within(src_off..src_off + len, dst_off);
+                    let ptr = buf.as_mut_ptr();
+                    unsafe { f(ptr.add(dst_off), ptr.add(src_off), len) };
+                    assert_eq!(
+                        buf, expected,
+                        "{name} src {src_off}, dst {dst_off}, len {len}"
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_backward(name: &str, f: CopyFn) {
+        for src_off in 0..MAX_OFFSET {
+            for dst_off in 0..MAX_OFFSET {
+                // `copy_backward` is never called with a zero length.
+                for &len in LENS.iter().filter(|&&len| len > 0) {
+                    let mut buf = pattern(len + 2 * MAX_OFFSET);
+                    let dst_off = src_off + dst_off;
+                    let mut expected = buf.clone();
+                    expected.copy_within(src_off..src_off + len, dst_off);
+                    let ptr = buf.as_mut_ptr();
+                    unsafe { f(ptr.add(dst_off), ptr.add(src_off), len) };
+                    assert_eq!(
+                        buf, expected,
+                        "{name} src {src_off}, dst {dst_off}, len {len}"
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_set(name: &str, f: SetFn) {
+        for c in [0, 0x01, 0xa5, 0xff] {
+            for off in 0..MAX_OFFSET {
+                for &len in LENS {
+                    let mut buf = pattern(len + 2 * MAX_OFFSET);
+                    let mut expected = buf.clone();
+                    expected[off..off + len].fill(c);
+                    unsafe { f(buf.as_mut_ptr().add(off), c, len) };
+                    assert_eq!(buf, expected, "{name} c {c:#x}, off {off}, len {len}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn copy_forward() {
+        check_forward("rep_movsb", copy_forward_rep_movsb);
+        check_forward("rep_movsq", copy_forward_rep_movsq);
+        if avx2() {
+            check_forward("avx2", copy_forward_avx2);
+            check_forward("ermsb_avx2", copy_forward_ermsb_avx2);
+        }
+    }
+
+    #[test]
+    fn copy_backward() {
+        check_backward("rep_movsq", copy_backward_rep_movsq);
+        if avx2() {
+            check_backward("avx2", copy_backward_avx2);
+        }
+    }
+
+    #[test]
+    fn set_bytes() {
+        check_set("rep_stosb", set_bytes_rep_stosb);
+        check_set("rep_stosq", set_bytes_rep_stosq);
+        if avx2() {
+            check_set("avx2", set_bytes_avx2);
+            check_set("ermsb_avx2", set_bytes_ermsb_avx2);
+        }
+    }
+}
@@ -168,6 +168,82 @@ fn trailing_zeros() {
     });
 }
 
+#[test]
+fn ffs() {
+    use compiler_builtins::int::trailing_zeros::{__ffsdi2, __ffssi2, __ffsti2};
+    assert_eq!(__ffssi2(0), 0);
+    assert_eq!(__ffsdi2(0), 0);
+    assert_eq!(__ffsti2(0), 0);
+    fuzz(N, |x: u32| {
+        if x != 0 {
+            assert_eq!(__ffssi2(x), x.trailing_zeros() as i32 + 1, "__ffssi2({x})");
+        }
+    });
+    fuzz(N, |x: u64| {
+        if x != 0 {
+            assert_eq!(__ffsdi2(x), x.trailing_zeros() as i32 + 1, "__ffsdi2({x})");
+        }
+    });
+    fuzz(N, |x: u128| {
+        if x != 0 {
+            assert_eq!(__ffsti2(x), x.trailing_zeros() as i32 + 1, "__ffsti2({x})");
+        }
+    });
+}
+
+#[test]
+fn popcount() {
+    use compiler_builtins::int::popcount::{__popcountdi2, __popcountsi2, __popcountti2};
+    fuzz(N, |x: u32| {
+        assert_eq!(
+            __popcountsi2(x),
+            x.count_ones() as i32,
+            "__popcountsi2({x})"
+        );
+    });
+    fuzz(N, |x: u64| {
+        assert_eq!(
+            __popcountdi2(x),
+            x.count_ones() as i32,
+            "__popcountdi2({x})"
+        );
+    });
+    fuzz(N, |x: u128| {
+        assert_eq!(
+            __popcountti2(x),
+            x.count_ones() as i32,
+            "__popcountti2({x})"
+        );
+    });
+    assert_eq!(__popcountti2(u128::MAX), 128);
+}
+
+#[test]
+fn parity() {
+    use compiler_builtins::int::popcount::{__paritydi2, __paritysi2, __parityti2};
+    fuzz(N, |x: u32| {
+        assert_eq!(
+            __paritysi2(x),
+            x.count_ones() as i32 % 2,
+            "__paritysi2({x})"
+        );
+    });
+    fuzz(N, |x: u64| {
+        assert_eq!(
+            __paritydi2(x),
+            x.count_ones() as i32 % 2,
+            "__paritydi2({x})"
+        );
+    });
+    fuzz(N, |x: u128| {
+        assert_eq!(
+            __parityti2(x),
+            x.count_ones() as i32 % 2,
+            "__parityti2({x})"
+        );
+    });
+}
+
 #[test]
 fn bswap() {
     use compiler_builtins::int::bswap::{__bswapdi2, __bswapsi2};
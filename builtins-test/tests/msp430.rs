@@ -0,0 +1,70 @@
+//! Tests for the MSP430 EABI helpers that are written in Rust, which also build on the host. The
+//! assembly ones only build for MSP430.
+
+use builtins_test::*;
+
+macro_rules! binary {
+    ($($i:ty, $fn_std:ident, $fn_builtins:ident, $nonzero:literal;)*) => {
+        $(
+            #[test]
+            fn $fn_builtins() {
+                use compiler_builtins::msp430::$fn_builtins;
+
+                fuzz_2(N, |a: $i, b: $i| {
+                    if $nonzero && b == 0 {
+                        return;
+                    }
+                    let tmp0: $i = a.$fn_std(b);
+                    let tmp1: $i = $fn_builtins(a, b);
+                    if tmp0 != tmp1 {
+                        panic!(
+                            "{}({}, {}): std: {}, builtins: {}",
+                            stringify!($fn_builtins), a, b, tmp0, tmp1
+                        );
+                    }
+                });
+            }
+        )*
+    };
+}
+
+binary! {
+    u16, wrapping_mul, __mspabi_mpyi, false;
+    u32, wrapping_mul, __mspabi_mpyl, false;
+    i16, wrapping_div, __mspabi_divi, true;
+    u16, wrapping_div, __mspabi_divu, true;
+    i16, wrapping_rem, __mspabi_remi, true;
+    u16, wrapping_rem, __mspabi_remu, true;
+    i32, wrapping_rem, __mspabi_remli, true;
+}
+
+macro_rules! shift {
+    ($($i:ty, $fn_std:ident, $fn_builtins:ident;)*) => {
+        $(
+            #[test]
+            fn $fn_builtins() {
+                use compiler_builtins::msp430::$fn_builtins;
+
+                fuzz_shift(|x: $i, s: u32| {
+                    let tmp0: $i = x.$fn_std(s);
+                    let tmp1: $i = $fn_builtins(x, s as u16);
+                    if tmp0 != tmp1 {
+                        panic!(
+                            "{}({}, {}): std: {}, builtins: {}",
+                            stringify!($fn_builtins), x, s, tmp0, tmp1
+                        );
+                    }
+                });
+            }
+        )*
+    };
+}
+
+shift! {
+    u16, wrapping_shl, __mspabi_slli;
+    i16, wrapping_shr, __mspabi_srai;
+    u16, wrapping_shr, __mspabi_srli;
+    u32, wrapping_shl, __mspabi_slll;
+    i32, wrapping_shr, __mspabi_sral;
+    u32, wrapping_shr, __mspabi_srll;
+}
@@ -0,0 +1,143 @@
+//! Tests for `__rust_probestack`, calling it with the same convention LLVM uses.
+//!
+//! Probing past the end of the stack has to hit the guard page, which kills the process. Those
+//! cases are run in a child process of this test binary and the child is expected to report a
+//! stack overflow.
+
+#![cfg(all(
+    target_os = "linux",
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+        target_arch = "loongarch64",
+        all(target_arch = "powerpc64", target_abi = "elfv2"),
+    )
+))]
+
+use std::arch::asm;
+use std::hint::black_box;
+use std::process::Command;
+use std::thread;
+
+use compiler_builtins::probestack::__rust_probestack;
+
+/// Set in child processes to the size to probe.
+const CHILD_ENV: &str = "BUILTINS_TEST_PROBESTACK_SIZE";
+
+/// Stack size of the threads that do the probing.
+const STACK_SIZE: usize = 256 * 1024;
+
+/// Probe `size` bytes below the stack pointer, returning the value of a scratch register that
+/// must be preserved and was set to `marker` before the call.
+#[inline(never)]
+fn probe(size: usize, marker: usize) -> usize {
+    let out: usize;
+    // SAFETY: the probe only reads from the stack. It preserves all registers except the return
+    // address register, and `r11` on x86_64.
+    unsafe {
+        #[cfg(target_arch = "x86_64")]
+        asm!(
+            "call {f}",
+            f = sym __rust_probestack,
+            in("rax") size,
+            inout("rcx") marker => out,
+            out("r11") _,
+        );
+        #[cfg(target_arch = "aarch64")]
+        asm!(
+            "bl {f}",
+            f = sym __rust_probestack,
+            in("x15") size,
+            inout("x14") marker => out,
+            out("lr") _,
+        );
+        #[cfg(target_arch = "riscv64")]
+        asm!(
+            "call {f}",
+            f = sym __rust_probestack,
+            in("t1") size,
+            inout("t3") marker => out,
+            out("ra") _,
+        );
+        #[cfg(target_arch = "loongarch64")]
+        asm!(
+            "bl {f}",
+            f = sym __rust_probestack,
+            in("$t0") size,
+            inout("$t2") marker => out,
+            out("$ra") _,
+        );
+        #[cfg(target_arch = "powerpc64")]
+        asm!(
+            "bl {f}",
+            "nop",
+            f = sym __rust_probestack,
+            in("r0") size,
+            inout("r10") marker => out,
+            out("lr") _,
+        );
+    }
+    out
+}
+
+/// Probe `size` bytes from a new thread with a small stack.
+fn probe_on_thread(size: usize) {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let marker = black_box(0x5a5a_1234);
+            assert_eq!(probe(size, marker), marker, "probing {size:#x} bytes");
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn fits_in_stack() {
+    // Sizes that aren't a multiple of the page, and some less than a page.
+    for size in [
+        0,
+        1,
+        0x800,
+        0xfff,
+        0x1000,
+        0x1001,
+        0x3456,
+        0x10000,
+        STACK_SIZE / 2,
+    ] {
+        probe_on_thread(size);
+    }
+}
+
+#[test]
+fn hits_guard_page() {
+    if let Ok(size) = std::env::var(CHILD_ENV) {
+        probe_on_thread(size.parse().unwrap());
+        std::process::exit(0);
+    }
+
+    let exe = std::env::current_exe().unwrap();
+    // Just past the stack, and far enough past the guard page that only probing every page
+    // finds it.
+    for size in [STACK_SIZE + 0x2000, 16 * STACK_SIZE] {
+        let out = Command::new(&exe)
+            .args([
+                "hits_guard_page",
+                "--exact",
+                "--nocapture",
+                "--test-threads=1",
+            ])
+            .env(CHILD_ENV, size.to_string())
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(
+            !out.status.success() && stderr.contains("has overflowed its stack"),
+            "probing {size:#x} bytes did not overflow: {}\n{stderr}",
+            out.status,
+        );
+    }
+}
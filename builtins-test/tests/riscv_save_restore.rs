@@ -0,0 +1,172 @@
+//! Tests for the `-msave-restore` routines, called the way a function compiled with it calls
+//! them. Each test checks that the callee-saved registers and stack pointer survive, and that the
+//! frame has the size and layout the compiler assumes.
+
+#![cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+
+use std::arch::naked_asm;
+
+#[cfg(target_arch = "riscv32")]
+macro_rules! sx {
+    () => {
+        "sw"
+    };
+}
+#[cfg(target_arch = "riscv32")]
+macro_rules! lx {
+    () => {
+        "lw"
+    };
+}
+#[cfg(target_arch = "riscv64")]
+macro_rules! sx {
+    () => {
+        "sd"
+    };
+}
+#[cfg(target_arch = "riscv64")]
+macro_rules! lx {
+    () => {
+        "ld"
+    };
+}
+
+/// Register size in bytes.
+const W: usize = size_of::<usize>();
+
+/// Number of callee-saved `s` registers, and the stack alignment.
+#[cfg(not(target_feature = "e"))]
+const SREGS: usize = 12;
+#[cfg(not(target_feature = "e"))]
+const ALIGN: usize = 16;
+#[cfg(target_feature = "e")]
+const SREGS: usize = 2;
+#[cfg(target_feature = "e")]
+const ALIGN: usize = W;
+
+/// `buf` passed to `run`:
+/// - The `s` registers are loaded from the start of it before the call, and stored back after.
+/// - The change in the stack pointer across the call is stored to `buf[SP]`.
+/// - The size of the frame pushed by the save routine is stored to `buf[FRAME_SIZE]`.
+/// - The frame itself is copied to `buf[FRAME..]`.
+const SP: usize = 12;
+const FRAME_SIZE: usize = 13;
+const FRAME: usize = 14;
+const BUF_LEN: usize = FRAME + 128 / W;
+
+macro_rules! save_restore {
+    ($($name:ident: $n:literal,)*) => {$(
+        mod $name {
+            use super::*;
+
+            /// A function that spills its registers with `__riscv_save_N` and clobbers them.
+            /// `run` passes the stack pointer from before the call in `a1`.
+            #[unsafe(naked)]
+            extern "C" fn callee() {
+                naked_asm!(
+                    "call t0, __riscv_save_{n}",
+                    "sub a2, a1, sp",
+                    concat!(sx!(), " a2, {frame_size}*{w}(a0)"),
+                    // Copy the frame to the buffer.
+                    "mv a3, sp",
+                    "addi a4, a0, {frame}*{w}",
+                    "2:",
+                    "bgeu a3, a1, 3f",
+                    concat!(lx!(), " a5, 0(a3)"),
+                    concat!(sx!(), " a5, 0(a4)"),
+                    "addi a3, a3, {w}",
+                    "addi a4, a4, {w}",
+                    "j 2b",
+                    "3:",
+                    ".irp i, 0,1,2,3,4,5,6,7,8,9,10,11",
+                    ".if \\i < {n}",
+                    "not s\\i, s\\i",
+                    ".endif",
+                    ".endr",
+                    "tail __riscv_restore_{n}",
+                    n = const $n,
+                    w = const W,
+                    frame_size = const FRAME_SIZE,
+                    frame = const FRAME,
+                );
+            }
+
+            /// Load the `s` registers from `buf`, call `callee` and store the results to `buf`.
+            #[unsafe(naked)]
+            unsafe extern "C" fn run(buf: *mut usize) {
+                naked_asm!(
+                    "addi sp, sp, -16*{w}",
+                    concat!(sx!(), " ra, 0(sp)"),
+                    ".irp i, 0,1,2,3,4,5,6,7,8,9,10,11",
+                    ".if \\i < {sregs}",
+                    concat!(sx!(), " s\\i, (1+\\i)*{w}(sp)"),
+                    concat!(lx!(), " s\\i, \\i*{w}(a0)"),
+                    ".endif",
+                    ".endr",
+                    "mv a1, sp",
+                    "call {callee}",
+                    "sub a1, sp, a1",
+                    concat!(sx!(), " a1, {sp}*{w}(a0)"),
+                    ".irp i, 0,1,2,3,4,5,6,7,8,9,10,11",
+                    ".if \\i < {sregs}",
+                    concat!(sx!(), " s\\i, \\i*{w}(a0)"),
+                    concat!(lx!(), " s\\i, (1+\\i)*{w}(sp)"),
+                    ".endif",
+                    ".endr",
+                    concat!(lx!(), " ra, 0(sp)"),
+                    "addi sp, sp, 16*{w}",
+                    "ret",
+                    w = const W,
+                    sregs = const SREGS,
+                    sp = const SP,
+                    callee = sym callee,
+                );
+            }
+
+            #[test]
+            fn save_restore() {
+                check($n, run);
+            }
+        }
+    )*};
+}
+
+fn check(n: usize, run: unsafe extern "C" fn(*mut usize)) {
+    let mut buf = [0usize; BUF_LEN];
+    for (i, x) in buf[..SREGS].iter_mut().enumerate() {
+        *x = 0x1357_9bdf_usize.wrapping_mul(i + 1);
+    }
+    let regs = buf;
+    unsafe { run(buf.as_mut_ptr()) };
+
+    assert_eq!(buf[..SREGS], regs[..SREGS], "N = {n}: s registers");
+    assert_eq!(buf[SP], 0, "N = {n}: stack pointer");
+    let size = ((n + 1) * W).next_multiple_of(ALIGN);
+    assert_eq!(buf[FRAME_SIZE], size, "N = {n}: frame size");
+
+    // `ra` is at the top of the frame, then `s0` and so on.
+    let frame = &buf[FRAME..][..size / W];
+    for (i, &reg) in regs[..n].iter().enumerate() {
+        assert_eq!(frame[frame.len() - 2 - i], reg, "N = {n}: s{i} in frame");
+    }
+}
+
+save_restore! {
+    save_restore_0: 0,
+    save_restore_1: 1,
+    save_restore_2: 2,
+}
+
+#[cfg(not(target_feature = "e"))]
+save_restore! {
+    save_restore_3: 3,
+    save_restore_4: 4,
+    save_restore_5: 5,
+    save_restore_6: 6,
+    save_restore_7: 7,
+    save_restore_8: 8,
+    save_restore_9: 9,
+    save_restore_10: 10,
+    save_restore_11: 11,
+    save_restore_12: 12,
+}
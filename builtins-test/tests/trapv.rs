@@ -0,0 +1,162 @@
+//! Tests for the `-ftrapv` intrinsics, which abort on overflow.
+//!
+//! An abort can't be caught within the test process, so each overflowing input is run in a child
+//! process of this test binary and the child is expected to die.
+
+use std::hint::black_box;
+use std::process::Command;
+
+use builtins_test::*;
+use compiler_builtins::int::addsub::{
+    __absvdi2, __absvsi2, __absvti2, __addvdi3, __addvsi3, __addvti3, __negvdi2, __negvsi2,
+    __negvti2, __subvdi3, __subvsi3, __subvti3,
+};
+use compiler_builtins::int::mul::{__mulvdi3, __mulvsi3, __mulvti3};
+
+/// Set in child processes to the test name and index of the input that should abort.
+const CHILD_ENV: &str = "BUILTINS_TEST_TRAPV_CASE";
+
+/// Check that each of `cases` aborts.
+///
+/// This must be called first in the test named `test`. In a child process it runs the selected
+/// case and exits, which makes the parent fail if the case didn't abort.
+fn assert_aborts(test: &str, cases: &[fn()]) {
+    if let Ok(case) = std::env::var(CHILD_ENV) {
+        let idx: usize = case
+            .strip_prefix(&format!("{test}:"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        cases[idx]();
+        std::process::exit(0);
+    }
+
+    let exe = std::env::current_exe().unwrap();
+    for idx in 0..cases.len() {
+        let out = Command::new(&exe)
+            .args([test, "--exact", "--nocapture", "--test-threads=1"])
+            .env(CHILD_ENV, format!("{test}:{idx}"))
+            .output()
+            .unwrap();
+
+        // A panic in the child, rather than an abort, exits with 101.
+        assert!(
+            !out.status.success() && out.status.code() != Some(101),
+            "{test} case {idx} did not abort: {}\n{}",
+            out.status,
+            String::from_utf8_lossy(&out.stderr),
+        );
+    }
+}
+
+/// Closures calling `$fn` with each list of arguments. `MIN` and `MAX` are the limits of `$i`.
+macro_rules! overflow_cases {
+    ($i:ty, $fn:ident, [$(($($arg:expr),*)),*]) => {
+        [$(
+            (|| {
+                #[allow(dead_code)]
+                const MIN: $i = <$i>::MIN;
+                #[allow(dead_code)]
+                const MAX: $i = <$i>::MAX;
+                $fn($(black_box($arg)),*);
+            }) as fn()
+        ),*]
+    };
+}
+
+/// Check inputs near the limits of `$i` that don't overflow.
+macro_rules! check_limits {
+    ($i:ty, $fn:ident, $checked:ident, [$(($a:ident, $b:expr)),*]) => {
+        $(
+            let (a, b): ($i, $i) = (<$i>::$a, $b);
+            assert_eq!($fn(a, b), a.$checked(b).unwrap(), "{}({a}, {b})", stringify!($fn));
+        )*
+    };
+}
+
+macro_rules! binop {
+    (
+        $test:ident, $checked:ident,
+        overflows: $overflows:tt,
+        limits: $limits:tt,
+        $($i:ty, $fn:ident);*;
+    ) => {
+        #[test]
+        fn $test() {
+            let mut cases = Vec::new();
+            $(cases.extend(overflow_cases!($i, $fn, $overflows));)*
+            assert_aborts(stringify!($test), &cases);
+
+            $(
+                fuzz_2(N, |x: $i, y: $i| {
+                    if let Some(expected) = x.$checked(y) {
+                        assert_eq!($fn(x, y), expected, "{}({x}, {y})", stringify!($fn));
+                    }
+                });
+                check_limits!($i, $fn, $checked, $limits);
+            )*
+        }
+    };
+}
+
+binop! {
+    addv, checked_add,
+    overflows: [(MAX, 1), (MIN, -1), (MAX, MAX), (MIN, MIN)],
+    limits: [(MAX, -1), (MIN, 1), (MAX, 0), (MIN, 0)],
+    i32, __addvsi3;
+    i64, __addvdi3;
+    i128, __addvti3;
+}
+
+binop! {
+    subv, checked_sub,
+    overflows: [(MIN, 1), (MAX, -1), (0, MIN), (MIN, MAX)],
+    limits: [(MAX, 1), (MIN, -1), (MAX, 0), (MIN, 0)],
+    i32, __subvsi3;
+    i64, __subvdi3;
+    i128, __subvti3;
+}
+
+binop! {
+    mulv, checked_mul,
+    overflows: [(MAX, 2), (MIN, -1), (-1, MIN), (MAX, MIN)],
+    limits: [(MAX, -1), (MIN, 1), (MAX, 1), (MIN, 0)],
+    i32, __mulvsi3;
+    i64, __mulvdi3;
+    i128, __mulvti3;
+}
+
+macro_rules! unop {
+    ($test:ident, $checked:ident, $($i:ty, $fn:ident);*;) => {
+        #[test]
+        fn $test() {
+            let mut cases = Vec::new();
+            $(cases.extend(overflow_cases!($i, $fn, [(MIN)]));)*
+            assert_aborts(stringify!($test), &cases);
+
+            $(
+                fuzz(N, |x: $i| {
+                    if let Some(expected) = x.$checked() {
+                        assert_eq!($fn(x), expected, "{}({x})", stringify!($fn));
+                    }
+                });
+                assert_eq!($fn(<$i>::MAX), <$i>::MAX.$checked().unwrap());
+                assert_eq!($fn(<$i>::MIN + 1), (<$i>::MIN + 1).$checked().unwrap());
+            )*
+        }
+    };
+}
+
+unop! {
+    negv, checked_neg,
+    i32, __negvsi2;
+    i64, __negvdi2;
+    i128, __negvti2;
+}
+
+unop! {
+    absv, checked_abs,
+    i32, __absvsi2;
+    i64, __absvdi2;
+    i128, __absvti2;
+}
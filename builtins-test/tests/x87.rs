@@ -0,0 +1,424 @@
+//! Builtins for the x87 80-bit `long double`, checked against the FPU of the host where it can
+//! do the operation and against `rustc_apfloat` otherwise (the FPU has no `f128` or 128-bit
+//! integer support).
+#![cfg(all(
+    target_arch = "x86_64",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+#![cfg_attr(f128_enabled, feature(f128))]
+
+use core::arch::asm;
+use core::arch::x86_64::__m128i;
+use core::mem::transmute;
+
+use builtins_test::*;
+use compiler_builtins::float::complex::{__divtc3, __divxc3, __multc3, __mulxc3, Complex};
+use compiler_builtins::float::{conv, extend, trunc};
+use compiler_builtins::support::{Float, f80};
+use rand_xoshiro::Xoshiro128StarStar;
+use rand_xoshiro::rand_core::{Rng, SeedableRng};
+use rustc_apfloat::ieee::{Quad, X87DoubleExtended};
+use rustc_apfloat::{Float as _, FloatConvert as _};
+
+/// `2^64` as an `f32`, for scaling on the FPU.
+const TWO_POW_64: f32 = 18446744073709551616.0;
+
+/// Call a builtin with `x` as the bits of its `long double` argument. Returns `rax:rdx` and
+/// `xmm0`, which between them hold any of the return types.
+fn call_with_xf(f: unsafe extern "C" fn(), x: u128) -> (u128, u128) {
+    let lo: u64;
+    let hi: u64;
+    let xmm: __m128i;
+    unsafe {
+        asm!(
+            "sub rsp, 16",
+            "mov [rsp], {x_lo}",
+            "mov [rsp + 8], {x_hi}",
+            "call {f}",
+            "add rsp, 16",
+            f = in(reg) f,
+            x_lo = in(reg) x as u64,
+            x_hi = in(reg) (x >> 64) as u64,
+            out("rax") lo,
+            out("rdx") hi,
+            out("xmm0") xmm,
+            clobber_abi("C"),
+        );
+        (
+            (u128::from(hi) << 64) | u128::from(lo),
+            transmute::<__m128i, u128>(xmm),
+        )
+    }
+}
+
+/// Call a builtin that returns a `long double`, with `rdi:rsi` and `xmm0` set to `args`. Returns
+/// the x87 bits of the result.
+fn call_returning_xf(f: unsafe extern "C" fn(), args: (u128, u128)) -> u128 {
+    let lo: u64;
+    let hi: u64;
+    unsafe {
+        asm!(
+            "call {f}",
+            "sub rsp, 16",
+            "fstp tbyte ptr [rsp]",
+            "mov rcx, [rsp]",
+            "movzx r8d, word ptr [rsp + 8]",
+            "add rsp, 16",
+            f = in(reg) f,
+            in("rdi") args.0 as u64,
+            in("rsi") (args.0 >> 64) as u64,
+            in("xmm0") transmute::<u128, __m128i>(args.1),
+            out("rcx") lo,
+            out("r8") hi,
+            clobber_abi("C"),
+        );
+    }
+    (u128::from(hi) << 64) | u128::from(lo)
+}
+
+/// Call a `long double _Complex` builtin with `args` as the bits of its arguments. Returns the x87
+/// bits of `st(0)` and `st(1)`.
+fn call_complex_xf(f: unsafe extern "C" fn(), args: [u128; 4]) -> [u128; 2] {
+    let mut out = [0u128; 2];
+    unsafe {
+        asm!(
+            // The output pointer is kept above the arguments, since the call clobbers registers.
+            "sub rsp, 80",
+            "mov [rsp + 64], {out}",
+            "movups xmm0, [{args}]",
+            "movups [rsp], xmm0",
+            "movups xmm0, [{args} + 16]",
+            "movups [rsp + 16], xmm0",
+            "movups xmm0, [{args} + 32]",
+            "movups [rsp + 32], xmm0",
+            "movups xmm0, [{args} + 48]",
+            "movups [rsp + 48], xmm0",
+            "call {f}",
+            "mov rax, [rsp + 64]",
+            "add rsp, 80",
+            "fstp tbyte ptr [rax]",
+            "fstp tbyte ptr [rax + 16]",
+            f = in(reg) f,
+            args = in(reg) &args,
+            out = in(reg) &mut out,
+            clobber_abi("C"),
+        );
+    }
+    out.map(|x| x & ((1 << 80) - 1))
+}
+
+/// Convert a 128-bit integer split into `hi * 2^64 + lo` on the FPU. `hi` and `lo` are loaded
+/// exactly, so the addition is the only rounding.
+fn x87_from_parts(hi: u64, hi_signed: bool, lo: u64) -> u128 {
+    let hi_fixup = !hi_signed && (hi as i64) < 0;
+    let lo_fixup = (lo as i64) < 0;
+    let mut out = [0u64; 2];
+    unsafe {
+        asm!(
+            "fild qword ptr [{hi}]",
+            "test {hi_fixup}, {hi_fixup}",
+            "jz 2f",
+            "fadd dword ptr [{scale}]",
+            "2:",
+            "fmul dword ptr [{scale}]",
+            "fild qword ptr [{lo}]",
+            "test {lo_fixup}, {lo_fixup}",
+            "jz 3f",
+            "fadd dword ptr [{scale}]",
+            "3:",
+            "faddp st(1), st",
+            "fstp tbyte ptr [{dst}]",
+            hi = in(reg) &hi,
+            lo = in(reg) &lo,
+            hi_fixup = in(reg) u64::from(hi_fixup),
+            lo_fixup = in(reg) u64::from(lo_fixup),
+            scale = in(reg) &TWO_POW_64,
+            dst = in(reg) out.as_mut_ptr(),
+            out("st(0)") _,
+            out("st(1)") _,
+        );
+    }
+    (u128::from(out[1] as u16) << 64) | u128::from(out[0])
+}
+
+/// Truncate to an `i64` on the FPU, only valid if the result is in range.
+fn x87_to_i64(x: u128) -> i64 {
+    let mut out = 0i64;
+    unsafe {
+        asm!(
+            "fld tbyte ptr [{x}]",
+            "fisttp qword ptr [{dst}]",
+            x = in(reg) &x,
+            dst = in(reg) &mut out,
+            out("st(0)") _,
+        );
+    }
+    out
+}
+
+/// Extend an `f64` on the FPU.
+fn x87_from_f64(x: f64) -> u128 {
+    let mut out = [0u64; 2];
+    unsafe {
+        asm!(
+            "fld qword ptr [{x}]",
+            "fstp tbyte ptr [{dst}]",
+            x = in(reg) &x,
+            dst = in(reg) out.as_mut_ptr(),
+            out("st(0)") _,
+        );
+    }
+    (u128::from(out[1] as u16) << 64) | u128::from(out[0])
+}
+
+fn apfloat_xf(x: f80) -> X87DoubleExtended {
+    X87DoubleExtended::from_bits(x.to_x87_bits())
+}
+
+#[test]
+fn int_to_xf() {
+    use conv::{__floatdixf, __floattixf, __floatundixf, __floatuntixf};
+
+    fuzz(N, |x: u128| {
+        let expected = x87_from_parts((x >> 64) as u64, false, x as u64);
+        let args = (x, 0);
+        assert_eq!(
+            __floatuntixf(x).to_x87_bits(),
+            expected,
+            "__floatuntixf({x})"
+        );
+        assert_eq!(
+            call_returning_xf(__floatuntixf::__floatuntixf, args),
+            expected
+        );
+    });
+
+    fuzz(N, |x: i128| {
+        let expected = x87_from_parts((x >> 64) as u64, true, x as u64);
+        let args = (x as u128, 0);
+        assert_eq!(__floattixf(x).to_x87_bits(), expected, "__floattixf({x})");
+        assert_eq!(call_returning_xf(__floattixf::__floattixf, args), expected);
+    });
+
+    fuzz(N, |x: u64| {
+        let expected = x87_from_parts(0, false, x);
+        let args = (x.into(), 0);
+        assert_eq!(
+            __floatundixf(x).to_x87_bits(),
+            expected,
+            "__floatundixf({x})"
+        );
+        assert_eq!(
+            call_returning_xf(__floatundixf::__floatundixf, args),
+            expected
+        );
+    });
+
+    fuzz(N, |x: i64| {
+        let expected = x87_from_parts((x >> 63) as u64, true, x as u64);
+        let args = (x as u64 as u128, 0);
+        assert_eq!(__floatdixf(x).to_x87_bits(), expected, "__floatdixf({x})");
+        assert_eq!(call_returning_xf(__floatdixf::__floatdixf, args), expected);
+    });
+}
+
+#[test]
+fn xf_to_int() {
+    use conv::{__fixunsxfdi, __fixunsxfsi, __fixunsxfti, __fixxfdi, __fixxfti};
+
+    fuzz_float(N, |x: f80| {
+        if x.is_nan() {
+            return;
+        }
+
+        let bits = x.to_x87_bits();
+        let apf = apfloat_xf(x);
+        let to_u = |width| apf.to_u128(width).value;
+        let to_i = |width| apf.to_i128(width).value;
+
+        let (u32_res, u64_res, u128_res) = (__fixunsxfsi(x), __fixunsxfdi(x), __fixunsxfti(x));
+        let (i64_res, i128_res) = (__fixxfdi(x), __fixxfti(x));
+        assert_eq!(u128::from(u32_res), to_u(32), "__fixunsxfsi({x:?})");
+        assert_eq!(u128::from(u64_res), to_u(64), "__fixunsxfdi({x:?})");
+        assert_eq!(u128_res, to_u(128), "__fixunsxfti({x:?})");
+        assert_eq!(i128::from(i64_res), to_i(64), "__fixxfdi({x:?})");
+        assert_eq!(i128_res, to_i(128), "__fixxfti({x:?})");
+
+        // The FPU result is only meaningful when it is in range.
+        let abs = x.abs().to_f128();
+        if abs < 9223372036854775808.0 {
+            assert_eq!(i64_res, x87_to_i64(bits), "__fixxfdi({x:?})");
+        }
+        if !x.is_sign_negative() && abs < 4294967296.0 {
+            assert_eq!(i64::from(u32_res), x87_to_i64(bits), "__fixunsxfsi({x:?})");
+        }
+
+        let call = |f| call_with_xf(f, bits).0;
+        assert_eq!(call(__fixunsxfsi::__fixunsxfsi) as u32, u32_res);
+        assert_eq!(call(__fixunsxfdi::__fixunsxfdi) as u64, u64_res);
+        assert_eq!(call(__fixunsxfti::__fixunsxfti), u128_res);
+        assert_eq!(call(__fixxfdi::__fixxfdi) as i64, i64_res);
+        assert_eq!(call(__fixxfti::__fixxfti) as i128, i128_res);
+    });
+}
+
+#[test]
+fn extend_xf() {
+    use extend::__extendxftf2;
+
+    fuzz_float(N, |x: f80| {
+        let expected: Quad = apfloat_xf(x).convert(&mut false).value;
+        let expected = f128::from_bits(expected.to_bits());
+        let res = __extendxftf2(x);
+        assert!(
+            Float::eq_repr(res, expected),
+            "__extendxftf2({x:?}): {res:?}"
+        );
+
+        let res = call_with_xf(__extendxftf2::__extendxftf2, x.to_x87_bits()).1;
+        assert!(Float::eq_repr(f128::from_bits(res), expected));
+    });
+
+    // Anything that can be loaded from an `f64` gives the same result as extending directly.
+    fuzz_float(N, |x: f64| {
+        let res = call_with_xf(__extendxftf2::__extendxftf2, x87_from_f64(x)).1;
+        assert!(Float::eq_repr(f128::from_bits(res), x as f128), "{x:?}");
+    });
+}
+
+#[test]
+fn trunc_xf() {
+    use extend::__extendxftf2;
+    use trunc::__trunctfxf2;
+
+    fuzz_float(N, |x: f128| {
+        let apf = Quad::from_bits(x.to_bits());
+        let expected: X87DoubleExtended = apf.convert(&mut false).value;
+        let expected = f80::from_x87_bits(expected.to_bits());
+        let res = __trunctfxf2(x);
+        assert!(
+            Float::eq_repr(res, expected),
+            "__trunctfxf2({x:?}): {res:?}"
+        );
+
+        let res = call_returning_xf(__trunctfxf2::__trunctfxf2, (0, x.to_bits()));
+        assert!(Float::eq_repr(f80::from_x87_bits(res), expected));
+    });
+
+    // Every `f80` round trips.
+    fuzz_float(N, |x: f80| {
+        let res = __trunctfxf2(__extendxftf2(x));
+        assert!(Float::eq_repr(res, x), "{x:?}: {res:?}");
+    });
+
+    // Narrowing an `f64` is exact and matches the FPU.
+    fuzz_float(N, |x: f64| {
+        if !x.is_nan() {
+            assert_eq!(
+                __trunctfxf2(x as f128).to_x87_bits(),
+                x87_from_f64(x),
+                "{x:?}"
+            );
+        }
+    });
+}
+
+#[test]
+fn noncanonical_encodings() {
+    use conv::__fixxfdi;
+    use extend::__extendxftf2;
+
+    // A pseudo-denormal has the value it would with the smallest normal exponent.
+    let pseudo_denormal = 0x0000_8000_0000_0000_0001;
+    let res = call_with_xf(__extendxftf2::__extendxftf2, pseudo_denormal).1;
+    assert_eq!(res, 0x0001_0000_0000_0000_0002_0000_0000_0000);
+
+    // Unnormals and pseudo-infinities are invalid operands. Padding above the 80 bits is ignored.
+    for bits in [0x3fff_4000_0000_0000_0000, 0x7fff_0000_0000_0000_0000] {
+        let bits = bits | (u128::MAX << 80);
+        let res = call_with_xf(__extendxftf2::__extendxftf2, bits).1;
+        assert!(f128::from_bits(res).is_nan(), "{bits:#x}");
+        assert_eq!(call_with_xf(__fixxfdi::__fixxfdi, bits).0 as i64, 0);
+    }
+}
+
+/// A random `f80` with an exponent in `-1000..1000`, so products and quotients stay far from
+/// overflow and subnormals.
+fn random_xf(rng: &mut Xoshiro128StarStar) -> f80 {
+    let sig = u128::from(rng.next_u64()) & f80::SIG_MASK;
+    let exp = (rng.next_u32() % 2000) as i32 - 1000;
+    let biased = (exp + f80::EXP_BIAS as i32) as u32;
+    f80::from_parts(rng.next_u32() & 1 != 0, biased, sig)
+}
+
+/// Check that `res` is within `ulp` units of `f80` precision of `expected`, normwise since a part
+/// can be far from exact if it cancelled. `expected` is computed in `f128`, whose 49 extra bits
+/// make its own errors negligible.
+#[track_caller]
+fn assert_close_xf(res: Complex<f80>, expected: Complex<f128>, ulp: f128, msg: &str) {
+    let err_re = res.re.to_f128() - expected.re;
+    let err_im = res.im.to_f128() - expected.im;
+    let err = err_re * err_re + err_im * err_im;
+    let norm = expected.re * expected.re + expected.im * expected.im;
+    let bound = ulp * f80::EPSILON.to_f128();
+    assert!(
+        err <= bound * bound * norm,
+        "{msg}: {res:?}, expected {expected:?}"
+    );
+}
+
+#[test]
+fn complex_xf() {
+    let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+    for _ in 0..N {
+        let args = [(); 4].map(|_| random_xf(&mut rng));
+        let [a, b, c, d] = args;
+        let [a128, b128, c128, d128] = args.map(f80::to_f128);
+        let bits = args.map(f80::to_x87_bits);
+
+        let res = __mulxc3(a, b, c, d);
+        let msg = format!("__mulxc3{args:?}");
+        assert_close_xf(res, __multc3(a128, b128, c128, d128), 4.0, &msg);
+        let [re, im] = call_complex_xf(__mulxc3::__mulxc3, bits);
+        assert_eq!(
+            [re, im],
+            [res.re.to_x87_bits(), res.im.to_x87_bits()],
+            "{msg}"
+        );
+
+        let res = __divxc3(a, b, c, d);
+        let msg = format!("__divxc3{args:?}");
+        assert_close_xf(res, __divtc3(a128, b128, c128, d128), 8.0, &msg);
+        let [re, im] = call_complex_xf(__divxc3::__divxc3, bits);
+        assert_eq!(
+            [re, im],
+            [res.re.to_x87_bits(), res.im.to_x87_bits()],
+            "{msg}"
+        );
+    }
+}
+
+#[test]
+fn complex_xf_special_values() {
+    let is_inf = |z: Complex<f80>| z.re.is_infinite() || z.im.is_infinite();
+    let (inf, nan, one, zero) = (f80::INFINITY, f80::NAN, f80::ONE, f80::ZERO);
+
+    // Annex G recovers infinities and zeros where the naive formulas give NaN.
+    assert!(is_inf(__mulxc3(inf, nan, one, zero)));
+    assert!(is_inf(__mulxc3(f80::MAX, f80::MAX, f80::MAX, -f80::MAX)));
+    assert!(is_inf(__divxc3(one, one, zero, zero)));
+    assert!(is_inf(__divxc3(inf, nan, one, one)));
+    let z = __divxc3(one, -one, inf, inf);
+    assert!(z.re == zero && z.im == zero, "{z:?}");
+    let z = __mulxc3(nan, nan, one, one);
+    assert!(z.re.is_nan() && z.im.is_nan(), "{z:?}");
+
+    // Scaling keeps `c * c + d * d` in range.
+    let big = f80::from_parts(false, f80::EXP_BIAS + 16358, 0);
+    let small = f80::from_parts(false, f80::EXP_BIAS - 16358, 0);
+    for x in [big, small] {
+        let z = __divxc3(x, x, x, x);
+        assert!(z.re == one && z.im == zero, "{x:?}: {z:?}");
+    }
+}
@@ -0,0 +1,122 @@
+//! The i386 calling convention of the x87 `long double` builtins. What they compute is checked in
+//! `x87.rs` on x86-64, so this compares the exported symbols with the Rust functions.
+#![cfg(all(
+    target_arch = "x86",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+
+use core::arch::asm;
+
+use builtins_test::*;
+use compiler_builtins::float::complex::{__divxc3, __mulxc3};
+use compiler_builtins::float::{X87LongDouble, conv};
+use compiler_builtins::support::{Float, f80};
+use rand_xoshiro::Xoshiro128StarStar;
+use rand_xoshiro::rand_core::{Rng, SeedableRng};
+
+/// Call a builtin that takes a 64-bit integer and returns a `long double` in `st(0)`. Returns the
+/// x87 bits of the result.
+fn call_returning_xf(f: unsafe extern "C" fn(), x: u64) -> u128 {
+    let mut out = [0u32; 3];
+    unsafe {
+        asm!(
+            // Align the stack for the call, with the output pointer above the argument. `edi` is
+            // callee-saved.
+            "mov edi, esp",
+            "and esp, -16",
+            "sub esp, 4",
+            "push {out}",
+            "push dword ptr [{x} + 4]",
+            "push dword ptr [{x}]",
+            "call {f}",
+            "mov eax, [esp + 8]",
+            "mov esp, edi",
+            "fstp tbyte ptr [eax]",
+            f = in(reg) f,
+            x = in(reg) &x,
+            out = in(reg) &mut out,
+            out("edi") _,
+            clobber_abi("C"),
+        );
+    }
+    X87LongDouble(out).to_f80().to_x87_bits()
+}
+
+/// Convert on the FPU.
+fn x87_from_i64(x: i64) -> u128 {
+    let mut out = [0u32; 3];
+    unsafe {
+        asm!(
+            "fild qword ptr [{x}]",
+            "fstp tbyte ptr [{dst}]",
+            x = in(reg) &x,
+            dst = in(reg) &mut out,
+            out("st(0)") _,
+        );
+    }
+    X87LongDouble(out).to_f80().to_x87_bits()
+}
+
+/// Any bit pattern, including NaNs and noncanonical encodings.
+fn random_xf(rng: &mut Xoshiro128StarStar) -> f80 {
+    let bits = (u128::from(rng.next_u32() as u16) << 64) | u128::from(rng.next_u64());
+    f80::from_x87_bits(bits)
+}
+
+#[test]
+fn int_to_xf() {
+    use conv::{__floatdixf, __floatundixf};
+
+    fuzz(N, |x: u64| {
+        let expected = __floatundixf(x).to_x87_bits();
+        assert_eq!(
+            call_returning_xf(__floatundixf::__floatundixf, x),
+            expected,
+            "__floatundixf({x})"
+        );
+    });
+
+    fuzz(N, |x: i64| {
+        let expected = x87_from_i64(x);
+        assert_eq!(__floatdixf(x).to_x87_bits(), expected, "__floatdixf({x})");
+        assert_eq!(
+            call_returning_xf(__floatdixf::__floatdixf, x as u64),
+            expected,
+            "__floatdixf({x})"
+        );
+    });
+}
+
+#[test]
+fn xf_to_int() {
+    use conv::{__fixunsxfdi, __fixunsxfsi, __fixxfdi};
+
+    fuzz_float(N, |x: f80| {
+        let arg = X87LongDouble::from_f80(x);
+        assert_eq!(__fixunsxfsi::__fixunsxfsi(arg), __fixunsxfsi(x), "{x:?}");
+        assert_eq!(__fixunsxfdi::__fixunsxfdi(arg), __fixunsxfdi(x), "{x:?}");
+        assert_eq!(__fixxfdi::__fixxfdi(arg), __fixxfdi(x), "{x:?}");
+    });
+}
+
+#[test]
+fn complex_xf() {
+    let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+    for _ in 0..N {
+        let [a, b, c, d] = [(); 4].map(|_| random_xf(&mut rng));
+        let [a1, b1, c1, d1] = [a, b, c, d].map(X87LongDouble::from_f80);
+
+        let expected = __mulxc3(a, b, c, d);
+        let res = __mulxc3::__mulxc3(a1, b1, c1, d1);
+        let msg = format!("__mulxc3({a:?}, {b:?}, {c:?}, {d:?})");
+        assert_eq!(res.re.to_f80().to_bits(), expected.re.to_bits(), "{msg}");
+        assert_eq!(res.im.to_f80().to_bits(), expected.im.to_bits(), "{msg}");
+
+        let expected = __divxc3(a, b, c, d);
+        let res = __divxc3::__divxc3(a1, b1, c1, d1);
+        let msg = format!("__divxc3({a:?}, {b:?}, {c:?}, {d:?})");
+        assert_eq!(res.re.to_f80().to_bits(), expected.re.to_bits(), "{msg}");
+        assert_eq!(res.im.to_f80().to_bits(), expected.im.to_bits(), "{msg}");
+    }
+}
@@ -275,57 +275,16 @@ mod c {
         }
 
         let mut sources = Sources::new();
-        sources.extend(&[
-            ("__absvdi2", "absvdi2.c"),
-            ("__absvsi2", "absvsi2.c"),
-            ("__addvdi3", "addvdi3.c"),
-            ("__addvsi3", "addvsi3.c"),
-            ("__cmpdi2", "cmpdi2.c"),
-            ("__int_util", "int_util.c"),
-            ("__mulvdi3", "mulvdi3.c"),
-            ("__mulvsi3", "mulvsi3.c"),
-            ("__negdi2", "negdi2.c"),
-            ("__negvdi2", "negvdi2.c"),
-            ("__negvsi2", "negvsi2.c"),
-            ("__paritydi2", "paritydi2.c"),
-            ("__paritysi2", "paritysi2.c"),
-            ("__popcountdi2", "popcountdi2.c"),
-            ("__popcountsi2", "popcountsi2.c"),
-            ("__subvdi3", "subvdi3.c"),
-            ("__subvsi3", "subvsi3.c"),
-            ("__ucmpdi2", "ucmpdi2.c"),
-        ]);
+        sources.extend(&[("__int_util", "int_util.c"), ("__negdi2", "negdi2.c")]);
 
         if consider_float_intrinsics {
-            sources.extend(&[
-                ("__divdc3", "divdc3.c"),
-                ("__divsc3", "divsc3.c"),
-                ("__muldc3", "muldc3.c"),
-                ("__mulsc3", "mulsc3.c"),
-                ("__negdf2", "negdf2.c"),
-                ("__negsf2", "negsf2.c"),
-            ]);
+            sources.extend(&[("__negdf2", "negdf2.c"), ("__negsf2", "negsf2.c")]);
         }
 
         // On iOS and 32-bit OSX these are all just empty intrinsics, no need to
         // include them.
         if cfg.target_vendor != "apple" || cfg.target_arch != "x86" {
-            sources.extend(&[
-                ("__absvti2", "absvti2.c"),
-                ("__addvti3", "addvti3.c"),
-                ("__cmpti2", "cmpti2.c"),
-                ("__ffsti2", "ffsti2.c"),
-                ("__mulvti3", "mulvti3.c"),
-                ("__negti2", "negti2.c"),
-                ("__parityti2", "parityti2.c"),
-                ("__popcountti2", "popcountti2.c"),
-                ("__subvti3", "subvti3.c"),
-                ("__ucmpti2", "ucmpti2.c"),
-            ]);
-
-            if consider_float_intrinsics {
-                sources.extend(&[("__negvti2", "negvti2.c")]);
-            }
+            sources.extend(&[("__negti2", "negti2.c")]);
         }
 
         if cfg.target_vendor == "apple" {
@@ -384,18 +343,6 @@ mod c {
             if cfg.target_os == "freebsd" {
                 sources.extend(&[("__clear_cache", "clear_cache.c")]);
             }
-
-            // First of all aeabi_cdcmp and aeabi_cfcmp are never called by LLVM.
-            // Second are little-endian only, so build fail on big-endian targets.
-            // Temporally workaround: exclude these files for big-endian targets.
-            if !llvm_target[0].starts_with("thumbeb") && !llvm_target[0].starts_with("armeb") {
-                sources.extend(&[
-                    ("__aeabi_cdcmp", "arm/aeabi_cdcmp.S"),
-                    ("__aeabi_cdcmpeq_check_nan", "arm/aeabi_cdcmpeq_check_nan.c"),
-                    ("__aeabi_cfcmp", "arm/aeabi_cfcmp.S"),
-                    ("__aeabi_cfcmpeq_check_nan", "arm/aeabi_cfcmpeq_check_nan.c"),
-                ]);
-            }
         }
 
         if llvm_target[0] == "armv7" {
@@ -493,10 +440,6 @@ mod c {
             sources.remove(&to_remove);
         }
 
-        if llvm_target[0] == "thumbv7m" || llvm_target[0] == "thumbv7em" {
-            sources.remove(&["__aeabi_cdcmp", "__aeabi_cfcmp"]);
-        }
-
         // Android and Cygwin uses emulated TLS so we need a runtime support function.
         if cfg.target_os == "android" || cfg.target_os == "cygwin" {
             sources.extend(&[("__emutls_get_address", "emutls.c")]);
@@ -34,6 +34,38 @@ intrinsics! {
     }
 }
 
+/// With the `lse-init` feature on Linux, check for LSE at startup like compiler-rt's
+/// `__init_cpu_features` does, so that calling `__rust_enable_lse` isn't needed. This runs before
+/// most other constructors, since they may already use atomics.
+#[cfg(all(any(target_os = "linux", target_os = "android"), feature = "lse-init"))]
+#[used]
+#[unsafe(link_section = ".init_array.00090")]
+static INIT_HAVE_LSE_ATOMICS: extern "C" fn() = detect_lse_atomics;
+
+/// Enable LSE if `AT_HWCAP` says the CPU has it.
+#[cfg(all(any(target_os = "linux", target_os = "android"), feature = "lse-init"))]
+extern "C" fn detect_lse_atomics() {
+    use core::ffi::c_ulong;
+
+    const AT_HWCAP: c_ulong = 16;
+    const HWCAP_ATOMICS: c_ulong = 1 << 8;
+
+    unsafe extern "C" {
+        // Weak so that this doesn't add a dependency on libc, in case it comes later in the link
+        // or doesn't have `getauxval`.
+        #[linkage = "extern_weak"]
+        static getauxval: Option<unsafe extern "C" fn(c_ulong) -> c_ulong>;
+    }
+
+    // SAFETY: `getauxval` is either null or the libc function, which is safe to call with any
+    // type.
+    if let Some(get) = unsafe { getauxval }
+        && unsafe { get(AT_HWCAP) } & HWCAP_ATOMICS != 0
+    {
+        HAVE_LSE_ATOMICS.store(1, Ordering::Relaxed);
+    }
+}
+
 /// Function to enable/disable LSE. To be used only for testing purposes.
 #[cfg(feature = "unstable-public-internals")]
 pub unsafe fn set_have_lse_atomics(has_lse: bool) {
@@ -76,6 +76,87 @@ intrinsics! {
         );
     }
 
+    // NOTE The flag-setting comparisons must preserve all registers except `ip` and `lr`, so they
+    // save `r0-r3` around a call to the comparison. `r4` is only pushed to keep the stack aligned.
+    // The result is 0, 1 or 2 for less, equal and greater or unordered, which comparing with 1
+    // turns into the flags: C clear for less, Z and C set for equal and only C set otherwise.
+
+    /// Compares the `f32` values in `r0` and `r1` for equality, setting the Z flag if they are
+    /// equal.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __aeabi_cfcmpeq() {
+        core::arch::naked_asm!(
+            "push {{r0-r4, lr}}",
+            "bl {cmp}",
+            "cmp r0, #1",
+            "pop {{r0-r4, pc}}",
+            cmp = sym crate::float::cmp::aeabi_cfcmp,
+        );
+    }
+
+    /// Compares the `f32` values in `r0` and `r1`, clearing the C flag if the first is less and
+    /// setting the Z flag if they are equal.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __aeabi_cfcmple() {
+        core::arch::naked_asm!(
+            "push {{r0-r4, lr}}",
+            "bl {cmp}",
+            "cmp r0, #1",
+            "pop {{r0-r4, pc}}",
+            cmp = sym crate::float::cmp::aeabi_cfcmp,
+        );
+    }
+
+    /// `__aeabi_cfcmple` with the arguments swapped.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __aeabi_cfrcmple() {
+        core::arch::naked_asm!(
+            "push {{r0-r4, lr}}",
+            "bl {cmp}",
+            "cmp r0, #1",
+            "pop {{r0-r4, pc}}",
+            cmp = sym crate::float::cmp::aeabi_cfrcmp,
+        );
+    }
+
+    /// Compares the `f64` values in `r0:r1` and `r2:r3` for equality, setting the Z flag if they
+    /// are equal.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __aeabi_cdcmpeq() {
+        core::arch::naked_asm!(
+            "push {{r0-r4, lr}}",
+            "bl {cmp}",
+            "cmp r0, #1",
+            "pop {{r0-r4, pc}}",
+            cmp = sym crate::float::cmp::aeabi_cdcmp,
+        );
+    }
+
+    /// Compares the `f64` values in `r0:r1` and `r2:r3`, clearing the C flag if the first is less
+    /// and setting the Z flag if they are equal.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __aeabi_cdcmple() {
+        core::arch::naked_asm!(
+            "push {{r0-r4, lr}}",
+            "bl {cmp}",
+            "cmp r0, #1",
+            "pop {{r0-r4, pc}}",
+            cmp = sym crate::float::cmp::aeabi_cdcmp,
+        );
+    }
+
+    /// `__aeabi_cdcmple` with the arguments swapped.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __aeabi_cdrcmple() {
+        core::arch::naked_asm!(
+            "push {{r0-r4, lr}}",
+            "bl {cmp}",
+            "cmp r0, #1",
+            "pop {{r0-r4, pc}}",
+            cmp = sym crate::float::cmp::aeabi_cdrcmp,
+        );
+    }
+
     // FIXME(arm): The `*4` and `*8` variants should be defined as aliases.
 
     /// `memcpy` provided with the `aapcs` ABI.
@@ -0,0 +1,200 @@
+//! `__clear_cache`, which makes code written to memory visible to instruction fetches. JITs call
+//! it through `__builtin___clear_cache` after writing code and before running it.
+//!
+//! On AArch64 the caches are maintained directly from userspace. ARM, MIPS and RISC-V Linux leave
+//! it to the kernel, which knows about the other cores that may run the code. Hexagon's version is
+//! in its own module.
+
+#![cfg(any(
+    all(target_arch = "aarch64", not(target_vendor = "apple"), not(windows)),
+    all(target_arch = "arm", any(target_os = "linux", target_os = "android")),
+    all(
+        any(
+            target_arch = "mips",
+            target_arch = "mips32r6",
+            target_arch = "mips64",
+            target_arch = "mips64r6"
+        ),
+        target_os = "linux"
+    ),
+    all(
+        any(target_arch = "riscv32", target_arch = "riscv64"),
+        any(target_os = "linux", target_os = "none")
+    ),
+))]
+
+use core::arch::asm;
+
+/// `__ARM_NR_cacheflush`, from the ARM private syscall range.
+#[cfg(target_arch = "arm")]
+const SYS_CACHEFLUSH: u32 = 0x0f_0002;
+
+/// `__NR_cacheflush` for the o32 ABI.
+#[cfg(any(target_arch = "mips", target_arch = "mips32r6"))]
+const SYS_CACHEFLUSH: usize = 4000 + 147;
+
+/// `__NR_cacheflush` for the n64 ABI.
+#[cfg(any(target_arch = "mips64", target_arch = "mips64r6"))]
+const SYS_CACHEFLUSH: usize = 5000 + 197;
+
+/// `BCACHE`, to flush both the instruction and data caches.
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips32r6",
+    target_arch = "mips64",
+    target_arch = "mips64r6"
+))]
+const BCACHE: usize = 3;
+
+/// `__NR_riscv_flush_icache`.
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_os = "linux"
+))]
+const SYS_RISCV_FLUSH_ICACHE: usize = 259;
+
+#[cfg(target_arch = "aarch64")]
+intrinsics! {
+    /// Makes instructions written to `start..end` visible to instruction fetches.
+    ///
+    /// # Safety
+    ///
+    /// The range must be mapped. It is only touched by cache maintenance instructions.
+    pub unsafe extern "C" fn __clear_cache(start: *mut u8, end: *mut u8) {
+        let ctr_el0: u64;
+        // SAFETY: Linux and most other kernels allow reading `CTR_EL0` from EL0.
+        unsafe {
+            asm!("mrs {}, ctr_el0", out(reg) ctr_el0, options(nomem, nostack, preserves_flags))
+        };
+        let (start, end) = (start.addr(), end.addr());
+
+        // Without `IDC`, the data cache has to be cleaned to the point of unification first.
+        // `DminLine` and `IminLine` are log2 of the line sizes in words.
+        if ctr_el0 & (1 << 28) == 0 {
+            let line = 4 << ((ctr_el0 >> 16) & 0xf);
+            let mut addr = start & !(line - 1);
+            while addr < end {
+                unsafe { asm!("dc cvau, {}", in(reg) addr, options(nostack, preserves_flags)) };
+                addr += line;
+            }
+        }
+        unsafe { asm!("dsb ish", options(nostack, preserves_flags)) };
+
+        // Without `DIC`, the instruction cache has to be invalidated too.
+        if ctr_el0 & (1 << 29) == 0 {
+            let line = 4 << (ctr_el0 & 0xf);
+            let mut addr = start & !(line - 1);
+            while addr < end {
+                unsafe { asm!("ic ivau, {}", in(reg) addr, options(nostack, preserves_flags)) };
+                addr += line;
+            }
+            unsafe { asm!("dsb ish", options(nostack, preserves_flags)) };
+        }
+        unsafe { asm!("isb", options(nostack, preserves_flags)) };
+    }
+}
+
+#[cfg(target_arch = "arm")]
+intrinsics! {
+    /// Makes instructions written to `start..end` visible to instruction fetches.
+    ///
+    /// # Safety
+    ///
+    /// The range must be mapped.
+    pub unsafe extern "C" fn __clear_cache(start: *mut u8, end: *mut u8) {
+        // `r7` holds the syscall number, but it is the frame pointer in Thumb code and can't be
+        // an operand, so it is swapped in and out.
+        unsafe {
+            asm!(
+                "mov {tmp}, r7",
+                "mov r7, {nr}",
+                "svc #0",
+                "mov r7, {tmp}",
+                nr = in(reg) SYS_CACHEFLUSH,
+                tmp = out(reg) _,
+                inout("r0") start => _,
+                in("r1") end,
+                in("r2") 0,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
+
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips32r6",
+    target_arch = "mips64",
+    target_arch = "mips64r6"
+))]
+intrinsics! {
+    /// Makes instructions written to `start..end` visible to instruction fetches.
+    ///
+    /// # Safety
+    ///
+    /// The range must be mapped.
+    pub unsafe extern "C" fn __clear_cache(start: *mut u8, end: *mut u8) {
+        // The kernel may clobber `$3` and the temporaries, as in glibc's `__SYSCALL_CLOBBERS` for
+        // o32 and n64, and reports errors in `$7`.
+        unsafe {
+            asm!(
+                "syscall",
+                inlateout("$2") SYS_CACHEFLUSH => _,
+                lateout("$3") _,
+                in("$4") start,
+                in("$5") end.addr() - start.addr(),
+                in("$6") BCACHE,
+                lateout("$7") _,
+                lateout("$8") _,
+                lateout("$9") _,
+                lateout("$10") _,
+                lateout("$11") _,
+                lateout("$12") _,
+                lateout("$13") _,
+                lateout("$14") _,
+                lateout("$15") _,
+                lateout("$24") _,
+                lateout("$25") _,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
+
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+intrinsics! {
+    /// Makes instructions written to `start..end` visible to instruction fetches.
+    ///
+    /// # Safety
+    ///
+    /// The range must be mapped.
+    pub unsafe extern "C" fn __clear_cache(start: *mut u8, end: *mut u8) {
+        // Other harts may run the code, and only the kernel can make them fence. A flags value
+        // of zero asks for every thread in the process.
+        #[cfg(target_os = "linux")]
+        unsafe {
+            asm!(
+                "ecall",
+                in("a7") SYS_RISCV_FLUSH_ICACHE,
+                inlateout("a0") start => _,
+                in("a1") end,
+                in("a2") 0,
+                options(nostack, preserves_flags),
+            );
+        }
+
+        // On bare metal, assume there is only this hart.
+        #[cfg(target_os = "none")]
+        let _ = (start, end);
+        #[cfg(target_os = "none")]
+        unsafe {
+            asm!(
+                ".option push",
+                ".option arch, +zifencei",
+                "fence.i",
+                ".option pop",
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
@@ -1,7 +1,21 @@
-use crate::support::{CastFrom, CastInto, Float, Int, MinInt};
+use super::rounding::{nan_status, round_pack};
+use crate::support::{CastFrom, CastInto, Float, FpResult, Int, MinInt, Round, Status};
 
 /// Returns `a + b`
 fn add<F: Float>(a: F, b: F) -> F
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+    i32: CastInto<F::Int>,
+    F::Int: CastInto<i32>,
+{
+    add_round(a, b, Round::Nearest).val
+}
+
+/// Returns `a + b` rounded in the direction of `round`, along with the exception flags that the
+/// operation raises.
+#[inline(always)]
+pub fn add_round<F: Float>(a: F, b: F, round: Round) -> FpResult<F>
 where
     u32: CastInto<F::Int>,
     F::Int: CastInto<u32>,
@@ -13,7 +27,6 @@ where
 
     let bits: F::Int = F::BITS.cast();
     let significand_bits = F::SIG_BITS;
-    let max_exponent = F::EXP_SAT;
 
     let implicit_bit = F::IMPLICIT_BIT;
     let significand_mask = F::SIG_MASK;
@@ -33,41 +46,45 @@ where
     if a_abs.wrapping_sub(one) >= inf_rep - one || b_abs.wrapping_sub(one) >= inf_rep - one {
         // NaN + anything = qNaN
         if a_abs > inf_rep {
-            return F::from_bits(a_abs | quiet_bit);
+            return FpResult::new(F::from_bits(a_abs | quiet_bit), nan_status(a, b));
         }
         // anything + NaN = qNaN
         if b_abs > inf_rep {
-            return F::from_bits(b_abs | quiet_bit);
+            return FpResult::new(F::from_bits(b_abs | quiet_bit), nan_status(a, b));
         }
 
         if a_abs == inf_rep {
             // +/-infinity + -/+infinity = qNaN
             if (a.to_bits() ^ b.to_bits()) == sign_bit {
-                return F::from_bits(qnan_rep);
+                return FpResult::new(F::from_bits(qnan_rep), Status::INVALID);
             } else {
                 // +/-infinity + anything remaining = +/- infinity
-                return a;
+                return FpResult::ok(a);
             }
         }
 
         // anything remaining + +/-infinity = +/-infinity
         if b_abs == inf_rep {
-            return b;
+            return FpResult::ok(b);
         }
 
         // zero + anything = anything
         if a_abs == MinInt::ZERO {
-            // but we need to get the sign right for zero + zero
+            // but we need to get the sign right for zero + zero, which is only negative if both
+            // are (or either is when rounding down)
             if b_abs == MinInt::ZERO {
-                return F::from_bits(a.to_bits() & b.to_bits());
+                if round == Round::Negative {
+                    return FpResult::ok(F::from_bits(a.to_bits() | b.to_bits()));
+                }
+                return FpResult::ok(F::from_bits(a.to_bits() & b.to_bits()));
             } else {
-                return b;
+                return FpResult::ok(b);
             }
         }
 
         // anything + zero = anything
         if b_abs == MinInt::ZERO {
-            return a;
+            return FpResult::ok(a);
         }
     }
 
@@ -124,9 +141,14 @@ where
     }
     if subtraction {
         a_significand = a_significand.wrapping_sub(b_significand);
-        // If a == -b, return +zero.
+        // If a == -b, return +zero (-zero when rounding down).
         if a_significand == MinInt::ZERO {
-            return F::from_bits(MinInt::ZERO);
+            let res = if round == Round::Negative {
+                F::NEG_ZERO
+            } else {
+                F::ZERO
+            };
+            return FpResult::ok(res);
         }
 
         // If partial cancellation occurred, we need to left-shift the result
@@ -150,43 +172,9 @@ where
         }
     }
 
-    // If we have overflowed the type, return +/- infinity:
-    if a_exponent >= max_exponent as i32 {
-        return F::from_bits(inf_rep | result_sign);
-    }
-
-    if a_exponent <= 0 {
-        // Result is denormal before rounding; the exponent is zero and we
-        // need to shift the significand.
-        let shift = (1 - a_exponent).cast();
-        let sticky = F::Int::from_bool(
-            (a_significand << u32::cast_from(bits.wrapping_sub(shift))) != MinInt::ZERO,
-        );
-        a_significand = (a_significand >> u32::cast_from(shift)) | sticky;
-        a_exponent = 0;
-    }
-
-    // Low three bits are round, guard, and sticky.
-    let a_significand_i32: i32 = a_significand.cast_lossy();
-    let round_guard_sticky: i32 = a_significand_i32 & 0x7;
-
-    // Shift the significand into place, and mask off the implicit bit.
-    let mut result = (a_significand >> 3) & significand_mask;
-
-    // Insert the exponent and sign.
-    result |= a_exponent.cast() << significand_bits;
-    result |= result_sign;
-
-    // Final rounding.  The result may overflow to infinity, but that is the
-    // correct result in that case.
-    if round_guard_sticky > 0x4 {
-        result += one;
-    }
-    if round_guard_sticky == 0x4 {
-        result += result & one;
-    }
-
-    F::from_bits(result)
+    // Round and handle overflow or a denormal result. The low three bits of the significand are
+    // round, guard, and sticky.
+    round_pack(result_sign != zero, a_exponent, a_significand, round)
 }
 
 intrinsics! {
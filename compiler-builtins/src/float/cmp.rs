@@ -263,6 +263,37 @@ intrinsics! {
     }
 }
 
+/// The result for the flag-setting `__aeabi_cfcmp*` and `__aeabi_cdcmp*` in `arm.rs`, which
+/// compare it with 1: 0 if `a < b`, 1 if `a == b` and 2 if `a > b` or either is NaN.
+#[cfg(target_arch = "arm")]
+fn aeabi_flags_cmp_result(res: Result) -> u32 {
+    match res {
+        Result::Less => 0,
+        Result::Equal => 1,
+        Result::Greater | Result::Unordered => 2,
+    }
+}
+
+#[cfg(target_arch = "arm")]
+pub(crate) extern "aapcs" fn aeabi_cfcmp(a: f32, b: f32) -> u32 {
+    aeabi_flags_cmp_result(cmp(a, b))
+}
+
+#[cfg(target_arch = "arm")]
+pub(crate) extern "aapcs" fn aeabi_cfrcmp(a: f32, b: f32) -> u32 {
+    aeabi_flags_cmp_result(cmp(b, a))
+}
+
+#[cfg(target_arch = "arm")]
+pub(crate) extern "aapcs" fn aeabi_cdcmp(a: f64, b: f64) -> u32 {
+    aeabi_flags_cmp_result(cmp(a, b))
+}
+
+#[cfg(target_arch = "arm")]
+pub(crate) extern "aapcs" fn aeabi_cdrcmp(a: f64, b: f64) -> u32 {
+    aeabi_flags_cmp_result(cmp(b, a))
+}
+
 #[cfg(target_arch = "arm")]
 intrinsics! {
     pub extern "aapcs" fn __aeabi_fcmple(a: f32, b: f32) -> i32 {
@@ -0,0 +1,310 @@
+//! Multiplication and division of C `_Complex` values.
+//!
+//! The naive formulas give NaN for many operations involving infinities, such as `(inf + 0i) *
+//! (1 + 1i)` where `0 * inf` shows up in the imaginary part. C Annex G specifies that a complex
+//! value with an infinite part is an infinity regardless of the other part, so when both parts of
+//! the result are NaN these recover infinities (or zeros, for division by an infinity) from the
+//! operands. Division also scales the divisor close to one so `c * c + d * d` does not overflow
+//! or underflow.
+
+use crate::float::extend::extend;
+use crate::float::trunc::trunc;
+use crate::math::libm_math::generic::{ilogb, scalbn};
+use crate::support::{CastFrom, CastInto, Float};
+
+/// A complex number, laid out like C's `_Complex`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Complex<F> {
+    pub re: F,
+    pub im: F,
+}
+
+fn is_finite<F: Float>(x: F) -> bool {
+    !x.is_nan() && !x.is_infinite()
+}
+
+/// Replace an infinity by one and anything else by zero, keeping the sign.
+fn inf_to_one<F: Float>(x: F) -> F {
+    if x.is_infinite() { F::ONE } else { F::ZERO }.copysign(x)
+}
+
+/// Replace NaN by zero, keeping the sign.
+fn nan_to_zero<F: Float>(x: F) -> F {
+    if x.is_nan() { F::ZERO.copysign(x) } else { x }
+}
+
+fn mul<F: Float>(mut a: F, mut b: F, mut c: F, mut d: F) -> Complex<F> {
+    let ac = a * c;
+    let bd = b * d;
+    let ad = a * d;
+    let bc = b * c;
+    let mut re = ac - bd;
+    let mut im = ad + bc;
+
+    if re.is_nan() && im.is_nan() {
+        let mut recalc = false;
+
+        if a.is_infinite() || b.is_infinite() {
+            a = inf_to_one(a);
+            b = inf_to_one(b);
+            c = nan_to_zero(c);
+            d = nan_to_zero(d);
+            recalc = true;
+        }
+
+        if c.is_infinite() || d.is_infinite() {
+            c = inf_to_one(c);
+            d = inf_to_one(d);
+            a = nan_to_zero(a);
+            b = nan_to_zero(b);
+            recalc = true;
+        }
+
+        // The intermediate products overflowed and then cancelled.
+        if !recalc && (ac.is_infinite() || bd.is_infinite() || ad.is_infinite() || bc.is_infinite())
+        {
+            a = nan_to_zero(a);
+            b = nan_to_zero(b);
+            c = nan_to_zero(c);
+            d = nan_to_zero(d);
+            recalc = true;
+        }
+
+        if recalc {
+            re = F::INFINITY * (a * c - b * d);
+            im = F::INFINITY * (a * d + b * c);
+        }
+    }
+
+    Complex { re, im }
+}
+
+fn div<F: Float>(mut a: F, mut b: F, mut c: F, mut d: F) -> Complex<F>
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastFrom<i32>,
+    F::Int: CastFrom<u32>,
+{
+    // Like `fmax`, a NaN is ignored if the other value is a number.
+    let max = if c.abs() < d.abs() || c.is_nan() {
+        d.abs()
+    } else {
+        c.abs()
+    };
+
+    let mut scale = 0;
+    if is_finite(max) && max != F::ZERO {
+        scale = ilogb(max);
+        c = scalbn(c, -scale);
+        d = scalbn(d, -scale);
+    }
+
+    let denom = c * c + d * d;
+    let mut re = scalbn((a * c + b * d) / denom, -scale);
+    let mut im = scalbn((b * c - a * d) / denom, -scale);
+
+    if re.is_nan() && im.is_nan() {
+        if denom == F::ZERO && (!a.is_nan() || !b.is_nan()) {
+            re = F::INFINITY.copysign(c) * a;
+            im = F::INFINITY.copysign(c) * b;
+        } else if (a.is_infinite() || b.is_infinite()) && is_finite(c) && is_finite(d) {
+            a = inf_to_one(a);
+            b = inf_to_one(b);
+            re = F::INFINITY * (a * c + b * d);
+            im = F::INFINITY * (b * c - a * d);
+        } else if max.is_infinite() && is_finite(a) && is_finite(b) {
+            c = inf_to_one(c);
+            d = inf_to_one(d);
+            re = F::ZERO * (a * c + b * d);
+            im = F::ZERO * (b * c - a * d);
+        }
+    }
+
+    Complex { re, im }
+}
+
+/// `f16` is computed in `f32`, where the products are exact and cannot overflow.
+#[cfg(f16_enabled)]
+fn via_f32(op: fn(f32, f32, f32, f32) -> Complex<f32>, args: [f16; 4]) -> Complex<f16> {
+    let [a, b, c, d] = args.map(extend::<f16, f32>);
+    let res = op(a, b, c, d);
+    Complex {
+        re: trunc(res.re),
+        im: trunc(res.im),
+    }
+}
+
+/// `_Complex` values are returned like a struct of two elements by most C ABIs, the exceptions
+/// are handled here.
+macro_rules! complex_intrinsics {
+    ($(
+        $(#[$($attr:tt)*])*
+        fn $name:ident($a:ident, $b:ident, $c:ident, $d:ident: $ty:ty) $body:block
+    )*) => {
+        #[cfg(not(any(
+            target_arch = "x86",
+            target_arch = "mips",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        )))]
+        intrinsics! {
+            $(
+                $(#[$($attr)*])*
+                pub extern "C" fn $name(
+                    $a: $ty, $b: $ty, $c: $ty, $d: $ty
+                ) -> crate::float::complex::Complex<$ty> {
+                    $body
+                }
+            )*
+        }
+
+        // i386 returns `_Complex` values of up to eight bytes in `eax:edx`, where a struct would
+        // be returned in memory.
+        #[cfg(target_arch = "x86")]
+        intrinsics! {
+            $(
+                $(#[$($attr)*])*
+                pub extern "C" fn $name(
+                    $a: $ty, $b: $ty, $c: $ty, $d: $ty
+                ) -> <$ty as crate::float::complex::X86ComplexRet>::Ret {
+                    X86ComplexRet::to_ret($body)
+                }
+            )*
+        }
+
+        // These return the two parts in floating point registers but small structs in memory or
+        // integer registers. The `unadjusted` ABI returns `{ F, F }` as is.
+        #[cfg(any(
+            target_arch = "mips",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        ))]
+        intrinsics! {
+            $(
+                $(#[$($attr)*])*
+                pub extern "unadjusted" fn $name(
+                    $a: $ty, $b: $ty, $c: $ty, $d: $ty
+                ) -> crate::float::complex::Complex<$ty> {
+                    $body
+                }
+            )*
+        }
+    };
+}
+
+/// How i386 returns a `_Complex` of each type.
+#[cfg(target_arch = "x86")]
+pub trait X86ComplexRet: Sized {
+    type Ret;
+    fn to_ret(z: Complex<Self>) -> Self::Ret;
+    fn from_ret(r: Self::Ret) -> Complex<Self>;
+}
+
+#[cfg(target_arch = "x86")]
+const _: () = {
+    #[cfg(f16_enabled)]
+    impl X86ComplexRet for f16 {
+        type Ret = u32;
+        fn to_ret(z: Complex<Self>) -> u32 {
+            (u32::from(z.im.to_bits()) << 16) | u32::from(z.re.to_bits())
+        }
+        fn from_ret(r: u32) -> Complex<Self> {
+            Complex {
+                re: f16::from_bits(r as u16),
+                im: f16::from_bits((r >> 16) as u16),
+            }
+        }
+    }
+
+    impl X86ComplexRet for f32 {
+        type Ret = u64;
+        fn to_ret(z: Complex<Self>) -> u64 {
+            (u64::from(z.im.to_bits()) << 32) | u64::from(z.re.to_bits())
+        }
+        fn from_ret(r: u64) -> Complex<Self> {
+            Complex {
+                re: f32::from_bits(r as u32),
+                im: f32::from_bits((r >> 32) as u32),
+            }
+        }
+    }
+
+    impl X86ComplexRet for f64 {
+        type Ret = Complex<f64>;
+        fn to_ret(z: Complex<Self>) -> Complex<f64> {
+            z
+        }
+        fn from_ret(r: Complex<f64>) -> Complex<Self> {
+            r
+        }
+    }
+
+    #[cfg(f128_enabled)]
+    impl X86ComplexRet for f128 {
+        type Ret = Complex<f128>;
+        fn to_ret(z: Complex<Self>) -> Complex<f128> {
+            z
+        }
+        fn from_ret(r: Complex<f128>) -> Complex<Self> {
+            r
+        }
+    }
+};
+
+complex_intrinsics! {
+    #[cfg(f16_enabled)]
+    fn __mulhc3(a, b, c, d: f16) {
+        via_f32(mul, [a, b, c, d])
+    }
+
+    fn __mulsc3(a, b, c, d: f32) {
+        mul(a, b, c, d)
+    }
+
+    fn __muldc3(a, b, c, d: f64) {
+        mul(a, b, c, d)
+    }
+
+    #[ppc_name = __mulkc3]
+    #[cfg(f128_enabled)]
+    fn __multc3(a, b, c, d: f128) {
+        mul(a, b, c, d)
+    }
+
+    #[cfg(f16_enabled)]
+    fn __divhc3(a, b, c, d: f16) {
+        via_f32(div, [a, b, c, d])
+    }
+
+    fn __divsc3(a, b, c, d: f32) {
+        div(a, b, c, d)
+    }
+
+    fn __divdc3(a, b, c, d: f64) {
+        div(a, b, c, d)
+    }
+
+    #[ppc_name = __divkc3]
+    #[cfg(f128_enabled)]
+    fn __divtc3(a, b, c, d: f128) {
+        div(a, b, c, d)
+    }
+}
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_complex_abi]
+    pub extern "C" fn __mulxc3(a: f80, b: f80, c: f80, d: f80) -> Complex<f80> {
+        mul(a, b, c, d)
+    }
+
+    #[x87_complex_abi]
+    pub extern "C" fn __divxc3(a: f80, b: f80, c: f80, d: f80) -> Complex<f80> {
+        div(a, b, c, d)
+    }
+}
@@ -1,6 +1,13 @@
 use core::ops::Neg;
 
-use crate::support::{CastFrom, CastInto, Float, Int, MinInt};
+use super::rounding::round_pack;
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+use crate::support::f80;
+use crate::support::{CastFrom, CastInto, Float, FpResult, Int, MinInt, Round};
 
 /// Conversions from integers to floats.
 ///
@@ -22,7 +29,7 @@ use crate::support::{CastFrom, CastInto, Float, Int, MinInt};
 /// - `m_base`: the mantissa before adjusting for truncated bits. Implicit bit is usually set.
 /// - `adj`: the bits that will be truncated, possibly compressed in some way.
 /// - `m`: the resulting mantissa. Implicit bit is usually set.
-mod int_to_float {
+pub(super) mod int_to_float {
     use super::*;
 
     /// Calculate the exponent from the number of leading zeros.
@@ -204,6 +211,103 @@ mod int_to_float {
         let e = exp::<u128, f128>(n) - 1;
         repr::<f128>(e, m)
     }
+
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        not(any(windows, target_os = "cygwin", target_os = "uefi")),
+        f128_enabled
+    ))]
+    pub fn u64_to_f80_bits(i: u64) -> u128 {
+        if i == 0 {
+            return 0;
+        }
+        let n = i.leading_zeros();
+        // Mantissa with implicit bit set. The significand is as wide as `u64`, so this only needs
+        // the leading zeros shifted out and is always exact.
+        let m = (i << n) as u128;
+        let e = exp::<u64, f80>(n) - 1;
+        repr::<f80>(e, m)
+    }
+
+    #[cfg(all(
+        target_arch = "x86_64",
+        not(any(windows, target_os = "cygwin", target_os = "uefi")),
+        f128_enabled
+    ))]
+    pub fn u128_to_f80_bits(i: u128) -> u128 {
+        if i == 0 {
+            return 0;
+        }
+        let n = i.leading_zeros();
+        let i_m = i << n;
+        // Mantissa with implicit bit set
+        let m_base = i_m >> shift_f_lt_i::<u128, f80>();
+        // The entire lower half of `i_m` is truncated. It fits below the top bit of the 79-bit
+        // representation without needing to be compressed.
+        let adj = (i_m as u64 as u128) << f80::EXP_BITS;
+        let m = m_adj::<f80>(m_base, adj);
+        let e = exp::<u128, f80>(n) - 1;
+        repr::<f80>(e, m)
+    }
+
+    /// Conversion of any unsigned integer to `f16`.
+    ///
+    /// Every integer that does not fit in a `u16` is larger than `f16::MAX`, so the conversion
+    /// only needs to be done for `u16`; anything larger saturates to infinity.
+    #[cfg(f16_enabled)]
+    pub fn uint_to_f16_bits<I: Int>(i: I) -> u16 {
+        if i > I::cast_from(u16::MAX) {
+            return f16::EXP_MASK;
+        }
+
+        let i: u16 = i.cast();
+        if i == 0 {
+            return 0;
+        }
+        let n = i.leading_zeros();
+        // Mantissa with implicit bit set (significant bits)
+        let m_base = (i << n) >> f16::EXP_BITS;
+        // Bits that will be dropped (insignificant bits)
+        let adj = (i << n) << (f16::SIG_BITS + 1);
+        let m = m_adj::<f16>(m_base, adj);
+        // Values in `65520..=65535` round up into the infinity exponent with a zero mantissa.
+        let e = exp::<u16, f16>(n) - 1;
+        repr::<f16>(e, m)
+    }
+}
+
+/// Convert an integer to a float rounded in the direction of `round`, along with the exception
+/// flags that the conversion raises.
+pub fn int_to_float_round<I, F>(i: I, round: Round) -> FpResult<F>
+where
+    I: Int<Unsigned: Int>,
+    F: Float,
+    F::Int: CastFrom<I::Unsigned>,
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+{
+    let negative = i < I::ZERO;
+    let m = if negative { i.wrapping_neg() } else { i }.unsigned();
+    if m == MinInt::ZERO {
+        return FpResult::ok(F::ZERO);
+    }
+
+    // Number of significant bits, which gives the exponent.
+    let n = I::BITS - m.leading_zeros();
+    let exp = (F::EXP_BIAS + n - 1) as i32;
+
+    // Move the highest set bit to the implicit bit position with three bits below it, keeping any
+    // bits shifted out as sticky.
+    let width = F::SIG_BITS + 4;
+    let sig = if n <= width {
+        F::Int::cast_from(m) << (width - n)
+    } else {
+        let shift = n - width;
+        let sticky = m << (I::BITS - shift) != MinInt::ZERO;
+        F::Int::cast_from(m >> shift) | F::Int::from_bool(sticky)
+    };
+
+    round_pack(negative, exp, sig, round)
 }
 
 // Conversions from unsigned integers to floats.
@@ -248,6 +352,28 @@ intrinsics! {
         f64::from_bits(int_to_float::u128_to_f64_bits((u128::from(hi) << 64) | u128::from(lo)))
     }
 
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __floatunsihf(i: u32) -> f16 {
+        f16::from_bits(int_to_float::uint_to_f16_bits(i))
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __floatundihf(i: u64) -> f16 {
+        f16::from_bits(int_to_float::uint_to_f16_bits(i))
+    }
+
+    #[cfg(f16_enabled)]
+    #[cfg(not(all(target_os = "uefi", target_arch = "x86_64")))]
+    pub extern "C" fn __floatuntihf(i: u128) -> f16 {
+        f16::from_bits(int_to_float::uint_to_f16_bits(i))
+    }
+
+    #[cfg(f16_enabled)]
+    #[cfg(all(target_os = "uefi", target_arch = "x86_64"))]
+    pub extern "C" fn __floatuntihf(lo: u64, hi: u64) -> f16 {
+        f16::from_bits(int_to_float::uint_to_f16_bits((u128::from(hi) << 64) | u128::from(lo)))
+    }
+
     #[ppc_name = __floatunsikf]
     #[cfg(f128_enabled)]
     pub extern "C" fn __floatunsitf(i: u32) -> f128 {
@@ -309,6 +435,28 @@ intrinsics! {
         int_to_float::signed((i128::from(hi) << 64) | i128::from(lo), int_to_float::u128_to_f64_bits)
     }
 
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __floatsihf(i: i32) -> f16 {
+        int_to_float::signed(i, int_to_float::uint_to_f16_bits)
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __floatdihf(i: i64) -> f16 {
+        int_to_float::signed(i, int_to_float::uint_to_f16_bits)
+    }
+
+    #[cfg(f16_enabled)]
+    #[cfg(not(all(target_os = "uefi", target_arch = "x86_64")))]
+    pub extern "C" fn __floattihf(i: i128) -> f16 {
+        int_to_float::signed(i, int_to_float::uint_to_f16_bits)
+    }
+
+    #[cfg(f16_enabled)]
+    #[cfg(all(target_os = "uefi", target_arch = "x86_64"))]
+    pub extern "C" fn __floattihf(lo: u64, hi: u64) -> f16 {
+        int_to_float::signed((i128::from(hi) << 64) | i128::from(lo), int_to_float::uint_to_f16_bits)
+    }
+
     #[ppc_name = __floatsikf]
     #[cfg(f128_enabled)]
     pub extern "C" fn __floatsitf(i: i32) -> f128 {
@@ -328,6 +476,42 @@ intrinsics! {
     }
 }
 
+// Conversions from integers to x87 `long double`.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_ret_abi]
+    pub extern "C" fn __floatundixf(i: u64) -> f80 {
+        f80::from_bits(int_to_float::u64_to_f80_bits(i))
+    }
+
+    #[x87_ret_abi]
+    pub extern "C" fn __floatdixf(i: i64) -> f80 {
+        int_to_float::signed(i, int_to_float::u64_to_f80_bits)
+    }
+}
+
+// C has no 128-bit integers on i386.
+#[cfg(all(
+    target_arch = "x86_64",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_ret_abi]
+    pub extern "C" fn __floatuntixf(i: u128) -> f80 {
+        f80::from_bits(int_to_float::u128_to_f80_bits(i))
+    }
+
+    #[x87_ret_abi]
+    pub extern "C" fn __floattixf(i: i128) -> f80 {
+        int_to_float::signed(i, int_to_float::u128_to_f80_bits)
+    }
+}
+
 /// Generic float to unsigned int conversions.
 fn float_to_unsigned_int<F, U>(f: F) -> U
 where
@@ -379,7 +563,9 @@ where
     F::Int: CastFrom<u32>,
     u32: CastFrom<F::Int>,
 {
-    let int_max_exp = F::EXP_BIAS + I::MAX.ilog2() + 1;
+    // Biased exponent of the first power of two that doesn't fit in `I`. For small floats such as
+    // `f16` this can be past the exponent range, in which case only infinity is out of bounds.
+    let int_max_exp = (F::EXP_BIAS + I::MAX.ilog2() + 1).min(F::EXP_SAT);
     let foobar = F::EXP_BIAS + I::Unsigned::BITS - 1;
 
     if fbits < F::ONE.to_bits() {
@@ -390,7 +576,7 @@ where
         let m_base = if I::Unsigned::BITS >= F::Int::BITS {
             I::Unsigned::cast_from(fbits) << (I::BITS - F::SIG_BITS - 1)
         } else {
-            I::Unsigned::cast_from_lossy(fbits >> (F::SIG_BITS - I::BITS + 1))
+            I::Unsigned::cast_from_lossy(fbits >> (F::SIG_BITS + 1 - I::BITS))
         };
 
         // Set the implicit 1-bit.
@@ -439,6 +625,21 @@ intrinsics! {
         float_to_unsigned_int(f)
     }
 
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __fixunshfsi(f: f16) -> u32 {
+        float_to_unsigned_int(f)
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __fixunshfdi(f: f16) -> u64 {
+        float_to_unsigned_int(f)
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __fixunshfti(f: f16) -> u128 {
+        float_to_unsigned_int(f)
+    }
+
     #[ppc_name = __fixunskfsi]
     #[cfg(f128_enabled)]
     pub extern "C" fn __fixunstfsi(f: f128) -> u32 {
@@ -488,6 +689,21 @@ intrinsics! {
         float_to_signed_int(f)
     }
 
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __fixhfsi(f: f16) -> i32 {
+        float_to_signed_int(f)
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __fixhfdi(f: f16) -> i64 {
+        float_to_signed_int(f)
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __fixhfti(f: f16) -> i128 {
+        float_to_signed_int(f)
+    }
+
     #[ppc_name = __fixkfsi]
     #[cfg(f128_enabled)]
     pub extern "C" fn __fixtfsi(f: f128) -> i32 {
@@ -506,3 +722,44 @@ intrinsics! {
         float_to_signed_int(f)
     }
 }
+
+// Conversions from x87 `long double` to integers.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_arg_abi]
+    pub extern "C" fn __fixunsxfsi(f: f80) -> u32 {
+        float_to_unsigned_int(f)
+    }
+
+    #[x87_arg_abi]
+    pub extern "C" fn __fixunsxfdi(f: f80) -> u64 {
+        float_to_unsigned_int(f)
+    }
+
+    #[x87_arg_abi]
+    pub extern "C" fn __fixxfdi(f: f80) -> i64 {
+        float_to_signed_int(f)
+    }
+}
+
+// C has no 128-bit integers on i386.
+#[cfg(all(
+    target_arch = "x86_64",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_arg_abi]
+    pub extern "C" fn __fixunsxfti(f: f80) -> u128 {
+        float_to_unsigned_int(f)
+    }
+
+    #[x87_arg_abi]
+    pub extern "C" fn __fixxfti(f: f80) -> i128 {
+        float_to_signed_int(f)
+    }
+}
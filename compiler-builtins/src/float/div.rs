@@ -82,7 +82,10 @@
 use core::mem::size_of;
 use core::ops;
 
-use crate::support::{CastFrom, CastInto, DInt, Float, HInt, HalfRep, Int, MinInt};
+use super::rounding::{nan_status, round_pack};
+use crate::support::{
+    CastFrom, CastInto, DInt, Float, FpResult, HInt, HalfRep, Int, MinInt, Round, Status,
+};
 
 fn div<F: Float>(a: F, b: F) -> F
 where
@@ -91,10 +94,10 @@ where
     F::Int: From<u8>,
     F::Int: HInt + DInt,
     <F::Int as HInt>::D: ops::Shr<u32, Output = <F::Int as HInt>::D>,
-    F::Int: From<u32>,
     u16: CastInto<F::Int>,
     i32: CastInto<F::Int>,
     u32: CastInto<F::Int>,
+    u128: CastInto<F::Int>,
     u128: CastInto<HalfRep<F>>,
 {
     let one = F::Int::ONE;
@@ -391,11 +394,12 @@ where
         // abs(E_N) <= 2^-W * [ 4 * (u_{N-1} + 3.01) * 2^-HW + max(4 + 2 * (u_{N-1} + 3.01)^2, 8) ]
         x_uq0
     } else {
-        // C is (3/4 + 1/sqrt(2)) - 1 truncated to 64 fractional bits as UQ0.n
-        let c: F::Int = F::Int::from(0x7504F333u32) << (F::BITS - 32);
+        // C is (3/4 + 1/sqrt(2)) - 1 truncated to W0 fractional bits as UQ0.n, with W0 being
+        // either 16 or 32.
+        let c: F::Int = c_full::<F>();
         let mut x_uq0: F::Int = c.wrapping_sub(b_uq1);
 
-        // E_0 <= 3/4 - 1/sqrt(2) + 2 * 2^-64
+        // E_0 <= 3/4 - 1/sqrt(2) + 2^-W0
         // x_uq0
         for _ in 0..full_iterations {
             x_uq0 = next_guess(x_uq0, b_uq1);
@@ -455,6 +459,7 @@ where
     //   P < 2^(W-4-SB)
     // Generally, for at most R NextAfter() to be enough,
     //   P < (2*R - 1) * 2^(W-4-SB)
+    // For f16 (0+2): 4 < 5 < 4 * 3, so two NextAfter() are required
     // For f32 (0+3): 10 < 32 (OK)
     // For f32 (2+1): 32 < 74 < 32 * 3, so two NextAfter() are required
     // For f64: 220 < 256 (OK)
@@ -470,7 +475,7 @@ where
     // r = a - b * q
     let mut abs_result = if res_exponent > 0 {
         let mut ret = quotient & significand_mask;
-        ret |= F::Int::from(res_exponent as u32) << significand_bits;
+        ret |= res_exponent.cast() << significand_bits;
         residual_lo <<= 1;
         ret
     } else {
@@ -489,7 +494,7 @@ where
     // conditionally turns the below LT comparison into LTE
     abs_result += u8::from(residual_lo > b_significand).into();
 
-    if F::BITS == 128 || (F::BITS == 32 && half_iterations > 0) {
+    if F::BITS == 128 || F::BITS == 16 || (F::BITS == 32 && half_iterations > 0) {
         // Do not round Infinity to NaN
         abs_result +=
             u8::from(abs_result < inf_rep && residual_lo > (2 + 1).cast() * b_significand).into();
@@ -503,6 +508,109 @@ where
     F::from_bits(abs_result | quotient_sign)
 }
 
+/// Returns `a / b` rounded in the direction of `round`, along with the exception flags that the
+/// operation raises.
+///
+/// Rather than using a reciprocal, this computes the quotient one bit at a time so the remainder
+/// is known exactly. This is much slower than the division intrinsics.
+pub fn div_round<F: Float>(a: F, b: F, round: Round) -> FpResult<F>
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+{
+    let zero = F::Int::ZERO;
+    let one = F::Int::ONE;
+    let quiet_bit = F::IMPLICIT_BIT >> 1;
+    let qnan_rep = F::EXP_MASK | quiet_bit;
+
+    let a_rep = a.to_bits();
+    let b_rep = b.to_bits();
+    let a_abs = a_rep & !F::SIGN_MASK;
+    let b_abs = b_rep & !F::SIGN_MASK;
+    let quotient_sign = (a_rep ^ b_rep) & F::SIGN_MASK;
+
+    // NaN / anything = qNaN
+    if a_abs > F::EXP_MASK {
+        return FpResult::new(F::from_bits(a_rep | quiet_bit), nan_status(a, b));
+    }
+
+    // anything / NaN = qNaN
+    if b_abs > F::EXP_MASK {
+        return FpResult::new(F::from_bits(b_rep | quiet_bit), nan_status(a, b));
+    }
+
+    if a_abs == F::EXP_MASK {
+        if b_abs == F::EXP_MASK {
+            // infinity / infinity = NaN
+            return FpResult::new(F::from_bits(qnan_rep), Status::INVALID);
+        }
+        // infinity / anything else = +/- infinity
+        return FpResult::ok(F::from_bits(a_abs | quotient_sign));
+    }
+
+    // anything else / infinity = +/- 0
+    if b_abs == F::EXP_MASK {
+        return FpResult::ok(F::from_bits(quotient_sign));
+    }
+
+    if a_abs == zero {
+        if b_abs == zero {
+            // zero / zero = NaN
+            return FpResult::new(F::from_bits(qnan_rep), Status::INVALID);
+        }
+        // zero / anything else = +/- zero
+        return FpResult::ok(F::from_bits(quotient_sign));
+    }
+
+    // anything else / zero = +/- infinity
+    if b_abs == zero {
+        return FpResult::new(
+            F::from_bits(F::EXP_MASK | quotient_sign),
+            Status::DIVIDE_BY_ZERO,
+        );
+    }
+
+    let (a_exponent, a_significand) = if a_abs < F::IMPLICIT_BIT {
+        F::normalize(a_abs)
+    } else {
+        (a.ex() as i32, a.frac() | F::IMPLICIT_BIT)
+    };
+    let (b_exponent, b_significand) = if b_abs < F::IMPLICIT_BIT {
+        F::normalize(b_abs)
+    } else {
+        (b.ex() as i32, b.frac() | F::IMPLICIT_BIT)
+    };
+
+    let mut res_exponent = a_exponent - b_exponent + F::EXP_BIAS as i32;
+
+    // Make the quotient of the significands lie in `[1, 2)`.
+    let mut residual = a_significand;
+    if residual < b_significand {
+        residual <<= 1;
+        res_exponent -= 1;
+    }
+
+    // Long division producing the significand and three more bits. The residual stays
+    // below `2 * b_significand`, which cannot overflow.
+    let mut quotient = zero;
+    for _ in 0..F::SIG_BITS + 4 {
+        quotient <<= 1;
+        if residual >= b_significand {
+            residual -= b_significand;
+            quotient |= one;
+        }
+        residual <<= 1;
+    }
+
+    let sticky = F::Int::from_bool(residual != zero);
+    round_pack(
+        quotient_sign != zero,
+        res_exponent,
+        quotient | sticky,
+        round,
+    )
+}
+
 /// Calculate the number of iterations required for a float type's precision.
 ///
 /// This returns `(h, f)` where `h` is the number of iterations to be done using integers at half
@@ -521,6 +629,11 @@ const fn get_iterations<F: Float>() -> (usize, usize) {
         // If widening multiplication will be efficient (uses word-sized integers), there is no
         // reason to use half-sized iterations.
         (0, total_iterations)
+    } else if F::BITS == 16 {
+        // Half-width iterations would need to be done with `u8`, which does not leave enough
+        // precision to get a correctly rounded result with a small number of corrections.
+        // Widening `u16` multiplication is cheap enough even on 16-bit targets.
+        (0, total_iterations)
     } else {
         // Otherwise, do as many iterations as possible at half width.
         (total_iterations - 1, 1)
@@ -540,13 +653,13 @@ const fn get_iterations<F: Float>() -> (usize, usize) {
 ///   define full1(un) { return 4.0 * (un + 3.01) / 2.0^hw + 2.0 * (un + 3.01)^2 + 4.0; }
 ///   define full2(un) { return 4.0 * (un + 3.01) / 2.0^hw + 8.0; }
 ///
-///             | f32 (0 + 3) | f32 (2 + 1)  | f64 (3 + 1)  | f128 (4 + 1)
-/// u_0         | < 184224974 | < 2812.1     | < 184224974  | < 791240234244348797
-/// u_1         | < 15804007  | < 242.7      | < 15804007   | < 67877681371350440
-/// u_2         | < 116308    | < 2.81       | < 116308     | < 499533100252317
-/// u_3         | < 7.31      |              | < 7.31       | < 27054456580
-/// u_4         |             |              |              | < 80.4
-/// Final (U_N) | same as u_3 | < 72         | < 218        | < 13920
+///             | f16 (0 + 2) | f32 (0 + 3) | f32 (2 + 1)  | f64 (3 + 1)  | f128 (4 + 1)
+/// u_0         | < 2812.1    | < 184224974 | < 2812.1     | < 184224974  | < 791240234244348797
+/// u_1         | < 242.7     | < 15804007  | < 242.7      | < 15804007   | < 67877681371350440
+/// u_2         | < 2.81      | < 116308    | < 2.81       | < 116308     | < 499533100252317
+/// u_3         |             | < 7.31      |              | < 7.31       | < 27054456580
+/// u_4         |             |             |              |              | < 80.4
+/// Final (U_N) | same as u_2 | same as u_3 | < 72         | < 218        | < 13920
 /// ````
 ///
 /// Add 2 to `U_N` due to final decrement.
@@ -558,8 +671,10 @@ const fn reciprocal_precision<F: Float>() -> u16 {
     }
 
     // FIXME(tgross35): calculate this programmatically
-    if F::BITS == 32 && half_iterations == 2 && full_iterations == 1 {
-        74u16
+    if F::BITS == 16 && half_iterations == 0 && full_iterations == 2 {
+        5u16
+    } else if F::BITS == 32 && half_iterations == 2 && full_iterations == 1 {
+        74
     } else if F::BITS == 32 && half_iterations == 0 && full_iterations == 3 {
         10
     } else if F::BITS == 64 && half_iterations == 3 && full_iterations == 1 {
@@ -585,6 +700,20 @@ where
     const { C_U128 >> (u128::BITS - <HalfRep<F>>::BITS) }.cast()
 }
 
+/// The value of `C` at full width, used when there are no half-width iterations.
+///
+/// This is the same constant as in [`c_hw`] but truncated to W0 fractional bits as UQ0.n, where
+/// W0 is 32 (or 16 for `f16`) and the remaining low bits are zero.
+fn c_full<F: Float>() -> F::Int
+where
+    u128: CastInto<F::Int>,
+{
+    const C_U128: u128 = 0x7504f333f9de6108b2fb1366eaa6a542;
+    let w0 = if F::BITS < 32 { F::BITS } else { 32 };
+    let c: u128 = (C_U128 >> (u128::BITS - w0)) << (F::BITS - w0);
+    c.cast()
+}
+
 /// Perform one iteration at any width to approach `1/b`, given previous guess `x`. Returns
 /// the next `x` as a UQ0 number.
 ///
@@ -605,6 +734,11 @@ where
 }
 
 intrinsics! {
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __divhf3(a: f16, b: f16) -> f16 {
+        div(a, b)
+    }
+
     #[arm_aeabi_alias = __aeabi_fdiv]
     pub extern "C" fn __divsf3(a: f32, b: f32) -> f32 {
         div(a, b)
@@ -0,0 +1,301 @@
+//! IBM double-double arithmetic, the `long double` format on most PowerPC targets (LLVM's
+//! `ppc_fp128`).
+//!
+//! A double-double is the unevaluated sum `hi + lo` of two `f64`s, where `hi` is the sum rounded
+//! to nearest so `|lo| <= ulp(hi) / 2`. This gives 106 bits of significand with the exponent range
+//! of `f64`. Infinities, NaNs and zeros are carried in `hi` and have `lo` zero.
+//!
+//! These are the same algorithms as libgcc (where the arithmetic is known as `__gcc_q*`), other
+//! than addition. That is done with the "accurate" algorithm from Joldes, Muller and Popescu,
+//! "Tight and rigorous error bounds for basic building blocks of double-word arithmetic", which
+//! does not lose precision to cancellation. The relative error bounds for normal results are:
+//!
+//! - addition: `3 * 2^-106`
+//! - multiplication: `5 * 2^-106`
+//! - division: `15 * 2^-106`
+//!
+//! Conversions to integers are exact and saturate like Rust's `as` casts, conversions from
+//! integers round to nearest.
+//!
+//! Rust has no type for `ppc_fp128`. Arguments are passed as two `f64`s, which is how the
+//! calling convention splits them, and results as a [`DoubleDouble`] with the `unadjusted` ABI.
+//! That ABI returns the struct as LLVM's `{ double, double }`, which lands in `f1:f2` like a
+//! `long double`, rather than applying the C rules for aggregates.
+
+use super::conv::int_to_float;
+use crate::math::libm_math::floor;
+use crate::support::{Float, hf64};
+
+/// An IBM double-double value, see the [module documentation](self).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    const fn new(hi: f64, lo: f64) -> Self {
+        Self { hi, lo }
+    }
+
+    /// A value that only needs `hi`, such as a special value or one that is exact as an `f64`.
+    const fn from_f64(hi: f64) -> Self {
+        Self { hi, lo: 0.0 }
+    }
+
+    const fn neg(self) -> Self {
+        Self::new(-self.hi, -self.lo)
+    }
+}
+
+/// `s + e = a + b` exactly, with `s = fl(a + b)`.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    let e = (a - (s - bb)) + (b - bb);
+    (s, e)
+}
+
+/// Build the result from an approximation `s` and correction `e` that may overlap.
+fn finish(s: f64, e: f64) -> DoubleDouble {
+    // Keep the sign of zero.
+    if e == 0.0 {
+        return DoubleDouble::from_f64(s);
+    }
+
+    let hi = s + e;
+    if !hi.is_finite() {
+        return DoubleDouble::from_f64(hi);
+    }
+
+    DoubleDouble::new(hi, e - (hi - s))
+}
+
+fn add(a: DoubleDouble, b: DoubleDouble) -> DoubleDouble {
+    let (s, e) = two_sum(a.hi, b.hi);
+    if !s.is_finite() {
+        return DoubleDouble::from_f64(s);
+    }
+
+    let (t, f) = two_sum(a.lo, b.lo);
+    let c = e + t;
+    if c == 0.0 {
+        return finish(s, f);
+    }
+
+    let hi = s + c;
+    let c = c - (hi - s);
+    finish(hi, c + f)
+}
+
+fn mul(a: DoubleDouble, b: DoubleDouble) -> DoubleDouble {
+    let p = a.hi * b.hi;
+    if p == 0.0 || !p.is_finite() {
+        return DoubleDouble::from_f64(p);
+    }
+
+    // The exact low part of `a.hi * b.hi`, then the terms of the next order.
+    let e = a.hi.fma(b.hi, -p);
+    let e = e + (a.hi * b.lo + a.lo * b.hi);
+    finish(p, e)
+}
+
+const DIV_SCALE_THRESHOLD: f64 = hf64("0x1p-969");
+const DIV_SCALE: f64 = hf64("0x1p106");
+
+fn div(mut a: DoubleDouble, mut b: DoubleDouble) -> DoubleDouble {
+    let t = a.hi / b.hi;
+    if t == 0.0 || !t.is_finite() {
+        return DoubleDouble::from_f64(t);
+    }
+
+    // The correction relies on the low part of `b.hi * t` being exact, which is not the case if
+    // it would be subnormal. Scale both up, which leaves the quotient the same.
+    if a.hi.abs() <= DIV_SCALE_THRESHOLD {
+        a = DoubleDouble::new(a.hi * DIV_SCALE, a.lo * DIV_SCALE);
+        b = DoubleDouble::new(b.hi * DIV_SCALE, b.lo * DIV_SCALE);
+    }
+
+    // `s + sigma = b.hi * t` exactly, and `a - b * t` is what remains to be divided.
+    let s = b.hi * t;
+    let sigma = b.hi.fma(t, -s);
+    let w = a.lo - b.lo * t;
+    let v = a.hi - s;
+    let tau = ((v - sigma) + w) / b.hi;
+    finish(t, tau)
+}
+
+/// The integral `x` modulo `2^128`.
+fn wrapping_u128(x: f64) -> u128 {
+    let e = x.exp_unbiased();
+    if e < 0 {
+        return 0;
+    }
+
+    let sig = u128::from(x.frac() | f64::IMPLICIT_BIT);
+    let e = e as u32;
+    let m = if e < f64::SIG_BITS {
+        sig >> (f64::SIG_BITS - e)
+    } else if e - f64::SIG_BITS < u128::BITS {
+        sig << (e - f64::SIG_BITS)
+    } else {
+        0
+    };
+
+    if x.is_sign_negative() {
+        m.wrapping_neg()
+    } else {
+        m
+    }
+}
+
+const TWO_POW_128: f64 = hf64("0x1p128");
+
+/// Split `trunc(a)` into its sign and magnitude, with the magnitude `None` if it is at least
+/// `2^128`. NaN must be handled by the caller.
+fn trunc_parts(a: DoubleDouble) -> (bool, Option<u128>) {
+    let negative = a.hi.is_sign_negative();
+    let a = if negative { a.neg() } else { a };
+
+    if a.hi >= TWO_POW_128 {
+        return (negative, None);
+    }
+
+    // With `hi = n + r`, `trunc(a) = n + floor(r + lo)`. If `hi` has a fractional part, `lo` is
+    // smaller than it in magnitude and `r + lo` is in `[-1, 1)`.
+    let n = floor(a.hi);
+    let r = a.hi - n;
+    let n = wrapping_u128(n);
+    let m = if r == 0.0 {
+        n.wrapping_add(wrapping_u128(floor(a.lo)))
+    } else {
+        n.wrapping_sub(u128::from(a.lo < -r))
+    };
+
+    (negative, Some(m))
+}
+
+/// Truncate to an unsigned integer with at most 128 bits, saturating.
+fn to_unsigned(a: DoubleDouble, max: u128) -> u128 {
+    if a.hi.is_nan() {
+        return 0;
+    }
+
+    match trunc_parts(a) {
+        (true, _) => 0,
+        (false, m) => m.map_or(max, |m| m.min(max)),
+    }
+}
+
+/// Truncate to a signed integer with at most 128 bits, saturating. The result is sign extended.
+fn to_signed(a: DoubleDouble, max: i128) -> i128 {
+    if a.hi.is_nan() {
+        return 0;
+    }
+
+    match trunc_parts(a) {
+        (true, m) => {
+            let limit = max.unsigned_abs() + 1;
+            m.map_or(limit, |m| m.min(limit)).wrapping_neg() as i128
+        }
+        (false, m) => {
+            let limit = max.unsigned_abs();
+            m.map_or(limit, |m| m.min(limit)) as i128
+        }
+    }
+}
+
+/// Convert an integer with at most 128 bits, rounding to nearest.
+fn from_parts(negative: bool, m: u128) -> DoubleDouble {
+    let hi = f64::from_bits(int_to_float::u128_to_f64_bits(m));
+
+    // `hi` may have rounded up to `2^128`, but the remainder is small so computing it modulo
+    // `2^128` gives the right result.
+    let rem = m.wrapping_sub(wrapping_u128(hi)) as i128;
+    let lo = f64::from_bits(int_to_float::u128_to_f64_bits(rem.unsigned_abs()));
+    let res = DoubleDouble::new(hi, if rem < 0 { -lo } else { lo });
+
+    if negative { res.neg() } else { res }
+}
+
+// `ppc_fp128` arithmetic.
+intrinsics! {
+    pub extern "unadjusted" fn __gcc_qadd(
+        a: f64, aa: f64, c: f64, cc: f64
+    ) -> crate::float::double_double::DoubleDouble {
+        add(DoubleDouble::new(a, aa), DoubleDouble::new(c, cc))
+    }
+
+    pub extern "unadjusted" fn __gcc_qsub(
+        a: f64, aa: f64, c: f64, cc: f64
+    ) -> crate::float::double_double::DoubleDouble {
+        add(DoubleDouble::new(a, aa), DoubleDouble::new(-c, -cc))
+    }
+
+    pub extern "unadjusted" fn __gcc_qmul(
+        a: f64, aa: f64, c: f64, cc: f64
+    ) -> crate::float::double_double::DoubleDouble {
+        mul(DoubleDouble::new(a, aa), DoubleDouble::new(c, cc))
+    }
+
+    pub extern "unadjusted" fn __gcc_qdiv(
+        a: f64, aa: f64, c: f64, cc: f64
+    ) -> crate::float::double_double::DoubleDouble {
+        div(DoubleDouble::new(a, aa), DoubleDouble::new(c, cc))
+    }
+}
+
+// Conversions from integers to `ppc_fp128`.
+intrinsics! {
+    pub extern "unadjusted" fn __floatunsitf(i: u32) -> crate::float::double_double::DoubleDouble {
+        from_parts(false, i.into())
+    }
+
+    pub extern "unadjusted" fn __floatunditf(i: u64) -> crate::float::double_double::DoubleDouble {
+        from_parts(false, i.into())
+    }
+
+    pub extern "unadjusted" fn __floatuntitf(i: u128) -> crate::float::double_double::DoubleDouble {
+        from_parts(false, i)
+    }
+
+    pub extern "unadjusted" fn __floatsitf(i: i32) -> crate::float::double_double::DoubleDouble {
+        from_parts(i < 0, i.unsigned_abs().into())
+    }
+
+    pub extern "unadjusted" fn __floatditf(i: i64) -> crate::float::double_double::DoubleDouble {
+        from_parts(i < 0, i.unsigned_abs().into())
+    }
+
+    pub extern "unadjusted" fn __floattitf(i: i128) -> crate::float::double_double::DoubleDouble {
+        from_parts(i < 0, i.unsigned_abs())
+    }
+}
+
+// Conversions from `ppc_fp128` to integers.
+intrinsics! {
+    pub extern "C" fn __fixunstfsi(a: f64, aa: f64) -> u32 {
+        to_unsigned(DoubleDouble::new(a, aa), u32::MAX.into()) as u32
+    }
+
+    pub extern "C" fn __fixunstfdi(a: f64, aa: f64) -> u64 {
+        to_unsigned(DoubleDouble::new(a, aa), u64::MAX.into()) as u64
+    }
+
+    pub extern "C" fn __fixunstfti(a: f64, aa: f64) -> u128 {
+        to_unsigned(DoubleDouble::new(a, aa), u128::MAX)
+    }
+
+    pub extern "C" fn __fixtfsi(a: f64, aa: f64) -> i32 {
+        to_signed(DoubleDouble::new(a, aa), i32::MAX.into()) as i32
+    }
+
+    pub extern "C" fn __fixtfdi(a: f64, aa: f64) -> i64 {
+        to_signed(DoubleDouble::new(a, aa), i64::MAX.into()) as i64
+    }
+
+    pub extern "C" fn __fixtfti(a: f64, aa: f64) -> i128 {
+        to_signed(DoubleDouble::new(a, aa), i128::MAX)
+    }
+}
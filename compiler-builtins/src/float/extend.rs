@@ -1,7 +1,7 @@
-use crate::support::{CastInto, Float, Int, MinInt};
+use crate::support::{CastInto, Float, Int, MinInt, bf16};
 
 /// Generic conversion from a narrower to a wider IEEE-754 floating-point type
-fn extend<F: Float, R: Float>(a: F) -> R
+pub(crate) fn extend<F: Float, R: Float>(a: F) -> R
 where
     F::Int: CastInto<u64>,
     u64: CastInto<F::Int>,
@@ -34,7 +34,15 @@ where
     let a_abs = a.to_bits() & src_abs_mask;
     let mut abs_result = R::Int::ZERO;
 
-    if a_abs.wrapping_sub(src_min_normal) < src_infinity.wrapping_sub(src_min_normal) {
+    // If the exponent ranges match (e.g. `bf16` to `f32`), subnormals stay subnormal and only need
+    // to be shifted like normal numbers.
+    let shift_start = if exp_bias_delta == 0 {
+        src_zero
+    } else {
+        src_min_normal
+    };
+
+    if a_abs.wrapping_sub(shift_start) < src_infinity.wrapping_sub(shift_start) {
         // a is a normal number.
         // Extend to the destination type by shifting the significand and
         // exponent into the proper position and rebiasing the exponent.
@@ -113,3 +121,24 @@ intrinsics! {
         extend(a)
     }
 }
+
+// There is no `bf16` primitive. It is passed and returned the same way as `f16`, so that is used
+// as the ABI type.
+intrinsics! {
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __extendbfsf2(a: f16) -> f32 {
+        extend(bf16::from_bits(a.to_bits()))
+    }
+}
+
+#[cfg(all(
+    target_arch = "x86_64",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_arg_abi]
+    pub extern "C" fn __extendxftf2(a: f80) -> f128 {
+        extend(a)
+    }
+}
@@ -1,9 +1,55 @@
 pub mod add;
 pub mod cmp;
+pub mod complex;
 pub mod conv;
 pub mod div;
+// Enabled with "unstable-public-internals" so the double-double algorithms can be tested on any
+// host, the symbols only have the right ABI on PowerPC. Elsewhere the `tf` names belong to `f128`,
+// so they can't be exported.
+#[cfg(any(
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    all(
+        feature = "unstable-public-internals",
+        not(feature = "unmangled-names")
+    )
+))]
+pub mod double_double;
 pub mod extend;
 pub mod mul;
 pub mod pow;
+mod rounding;
 pub mod sub;
 pub mod trunc;
+
+/// A `long double` as i386 passes it on the stack and stores it in structs: the 80 bits of the x87
+/// format padded to 12 bytes.
+#[cfg(all(
+    target_arch = "x86",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct X87LongDouble(pub [u32; 3]);
+
+#[cfg(all(
+    target_arch = "x86",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+impl X87LongDouble {
+    pub fn from_f80(x: crate::support::f80) -> Self {
+        let bits = x.to_x87_bits();
+        Self([bits as u32, (bits >> 32) as u32, (bits >> 64) as u32])
+    }
+
+    pub fn to_f80(self) -> crate::support::f80 {
+        let [lo, mid, hi] = self.0;
+        let bits = ((hi as u128) << 64) | ((mid as u128) << 32) | lo as u128;
+        crate::support::f80::from_x87_bits(bits)
+    }
+}
+
+// Used by the `*_round` functions, which take a rounding mode and report exception flags.
+pub use crate::support::{FpResult, Round, Status};
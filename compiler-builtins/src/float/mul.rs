@@ -1,6 +1,22 @@
-use crate::support::{CastInto, DInt, Float, HInt, Int, MinInt};
+use super::rounding::{nan_status, round_pack};
+use crate::support::{CastInto, DInt, Float, FpResult, HInt, Int, MinInt, Round, Status};
 
+/// Returns `a * b`
 fn mul<F: Float>(a: F, b: F) -> F
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+    i32: CastInto<F::Int>,
+    F::Int: CastInto<i32>,
+    F::Int: HInt,
+{
+    mul_round(a, b, Round::Nearest).val
+}
+
+/// Returns `a * b` rounded in the direction of `round`, along with the exception flags that the
+/// operation raises.
+#[inline(always)]
+pub fn mul_round<F: Float>(a: F, b: F, round: Round) -> FpResult<F>
 where
     u32: CastInto<F::Int>,
     F::Int: CastInto<u32>,
@@ -47,41 +63,41 @@ where
 
         // NaN + anything = qNaN
         if a_abs > inf_rep {
-            return F::from_bits(a_rep | quiet_bit);
+            return FpResult::new(F::from_bits(a_rep | quiet_bit), nan_status(a, b));
         }
         // anything + NaN = qNaN
         if b_abs > inf_rep {
-            return F::from_bits(b_rep | quiet_bit);
+            return FpResult::new(F::from_bits(b_rep | quiet_bit), nan_status(a, b));
         }
 
         if a_abs == inf_rep {
             if b_abs != zero {
                 // infinity * non-zero = +/- infinity
-                return F::from_bits(a_abs | product_sign);
+                return FpResult::ok(F::from_bits(a_abs | product_sign));
             } else {
                 // infinity * zero = NaN
-                return F::from_bits(qnan_rep);
+                return FpResult::new(F::from_bits(qnan_rep), Status::INVALID);
             }
         }
 
         if b_abs == inf_rep {
             if a_abs != zero {
                 // infinity * non-zero = +/- infinity
-                return F::from_bits(b_abs | product_sign);
+                return FpResult::ok(F::from_bits(b_abs | product_sign));
             } else {
                 // infinity * zero = NaN
-                return F::from_bits(qnan_rep);
+                return FpResult::new(F::from_bits(qnan_rep), Status::INVALID);
             }
         }
 
         // zero * anything = +/- zero
         if a_abs == zero {
-            return F::from_bits(product_sign);
+            return FpResult::ok(F::from_bits(product_sign));
         }
 
         // anything * zero = +/- zero
         if b_abs == zero {
-            return F::from_bits(product_sign);
+            return FpResult::ok(F::from_bits(product_sign));
         }
 
         // one or both of a or b is denormal, the other (if applicable) is a
@@ -130,52 +146,11 @@ where
         product_low <<= 1;
     }
 
-    // If we have overflowed the type, return +/- infinity.
-    if product_exponent >= max_exponent as i32 {
-        return F::from_bits(inf_rep | product_sign);
-    }
-
-    if product_exponent <= 0 {
-        // Result is denormal before rounding
-        //
-        // If the result is so small that it just underflows to zero, return
-        // a zero of the appropriate sign.  Mathematically there is no need to
-        // handle this case separately, but we make it a special case to
-        // simplify the shift logic.
-        let shift: u32 = one.wrapping_sub(product_exponent.cast_lossy()).cast();
-        if shift >= bits {
-            return F::from_bits(product_sign);
-        }
-
-        // Otherwise, shift the significand of the result so that the round
-        // bit is the high bit of `product_low`.
-        // Ensure one of the non-highest bits in `product_low` is set if the shifted out bit are
-        // not all zero so that the result is correctly rounded below.
-        let sticky = product_low << (bits - shift) != zero;
-        product_low =
-            (product_high << (bits - shift)) | (product_low >> shift) | (sticky as u32).cast();
-        product_high >>= shift;
-    } else {
-        // Result is normal before rounding; insert the exponent.
-        product_high &= significand_mask;
-        product_high |= product_exponent.cast() << significand_bits;
-    }
-
-    // Insert the sign of the result:
-    product_high |= product_sign;
-
-    // Final rounding.  The final result may overflow to infinity, or underflow
-    // to zero, but those are the correct results in those cases.  We use the
-    // default IEEE-754 round-to-nearest, ties-to-even rounding mode.
-    if product_low > sign_bit {
-        product_high += one;
-    }
-
-    if product_low == sign_bit {
-        product_high += product_high & one;
-    }
-
-    F::from_bits(product_high)
+    // Keep three bits of `product_low` below the significand, the last one sticky, then round
+    // and handle overflow or a denormal result.
+    let sticky = product_low << 3 != zero;
+    let significand = (product_high << 3) | (product_low >> (bits - 3)) | (sticky as u32).cast();
+    round_pack(product_sign != zero, product_exponent, significand, round)
 }
 
 intrinsics! {
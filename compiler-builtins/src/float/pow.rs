@@ -1,7 +1,149 @@
-use crate::support::{Float, Int};
+use crate::support::{Float, Int, MinInt};
 
-/// Returns `a` raised to the power `b`
-fn pow<F: Float>(a: F, b: i32) -> F {
+/// A value of `(hi + lo) * 2^exp`.
+///
+/// `hi` is kept in `[1, 2)` and `lo` holds the rounding error of `hi`, so the pair carries
+/// roughly twice the precision of `F`. Keeping the exponent separately means intermediate
+/// results can neither overflow nor underflow.
+#[derive(Clone, Copy)]
+struct Wide<F> {
+    hi: F,
+    lo: F,
+    exp: i64,
+}
+
+impl<F: Float> Wide<F> {
+    const ONE: Self = Self {
+        hi: F::ONE,
+        lo: F::ZERO,
+        exp: 0,
+    };
+
+    /// Split a finite, nonzero, positive `F` into a significand and an exponent.
+    fn new(a: F) -> Self {
+        let (exp, sig) = if a.is_subnormal() {
+            F::normalize(a.frac())
+        } else {
+            (a.ex() as i32, a.to_bits())
+        };
+
+        Self {
+            hi: F::from_parts(false, F::EXP_BIAS, sig),
+            lo: F::ZERO,
+            exp: i64::from(exp) - i64::from(F::EXP_BIAS),
+        }
+    }
+
+    /// Multiply two values, keeping `hi` normalized.
+    fn mul(self, other: Self) -> Self {
+        let (p, e) = two_prod(self.hi, other.hi);
+        let e = e + (self.hi * other.lo + self.lo * other.hi);
+        let (hi, lo) = fast_two_sum(p, e);
+
+        // Both significands are in `[1, 2)` so the product is in `[1, 4)`.
+        if hi >= two::<F>() {
+            let half = F::from_parts(false, F::EXP_BIAS - 1, F::Int::ZERO);
+            Self {
+                hi: hi * half,
+                lo: lo * half,
+                exp: self.exp + other.exp + 1,
+            }
+        } else {
+            Self {
+                hi,
+                lo,
+                exp: self.exp + other.exp,
+            }
+        }
+    }
+
+    /// Compute `1 / self`, keeping `hi` normalized.
+    fn recip(self) -> Self {
+        let q = F::ONE / self.hi;
+
+        // Residual of the first quotient. `1 - p` is exact since `p` is close to one.
+        let (p, e) = two_prod(q, self.hi);
+        let r = ((F::ONE - p) - e) - q * self.lo;
+        let (hi, lo) = fast_two_sum(q, r / self.hi);
+
+        // The reciprocal of a significand in `[1, 2)` is in `(0.5, 1]`.
+        if hi < F::ONE {
+            Self {
+                hi: hi * two::<F>(),
+                lo: lo * two::<F>(),
+                exp: -self.exp - 1,
+            }
+        } else {
+            Self {
+                hi,
+                lo,
+                exp: -self.exp,
+            }
+        }
+    }
+
+    /// Round to `F`, applying the exponent and sign.
+    fn to_float(self, negative: bool) -> F {
+        let m = self.hi + self.lo;
+        let res = if self.exp > i64::from(F::EXP_MAX) {
+            F::INFINITY
+        } else if self.exp >= i64::from(F::EXP_MIN) {
+            // This can still overflow if `m` rounded up to two.
+            m * pow2::<F>(self.exp as i32)
+        } else if self.exp >= i64::from(F::EXP_MIN_SUBNORM) - 2 {
+            // Subnormal result. Scale to the minimum normal exponent exactly, then round once
+            // with the remaining scale.
+            let rem = self.exp - i64::from(F::EXP_MIN);
+            m * pow2::<F>(F::EXP_MIN) * pow2::<F>(rem as i32)
+        } else {
+            // Less than half of the smallest subnormal.
+            F::ZERO
+        };
+
+        if negative { -res } else { res }
+    }
+}
+
+/// `2.0` as an `F`.
+fn two<F: Float>() -> F {
+    F::ONE + F::ONE
+}
+
+/// `2^exp` for an `exp` within the normal range of `F`.
+fn pow2<F: Float>(exp: i32) -> F {
+    F::from_parts(false, (exp + F::EXP_BIAS as i32) as u32, F::Int::ZERO)
+}
+
+/// Sum of two values where `|a| >= |b|`, returned as the rounded sum and its error.
+fn fast_two_sum<F: Float>(a: F, b: F) -> (F, F) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// Split `x` into two halves that each fit in half of the significand (Veltkamp).
+fn split<F: Float>(x: F) -> (F, F) {
+    let shift = (F::SIG_BITS + 2) / 2;
+    let c = F::from_parts(false, F::EXP_BIAS + shift, F::Int::ZERO) + F::ONE;
+    let t = c * x;
+    let hi = t - (t - x);
+    (hi, x - hi)
+}
+
+/// Exact product of two values, returned as the rounded product and its error (Dekker).
+///
+/// This avoids relying on a fused multiply-add, which may not be available in hardware. Inputs
+/// must be far enough from the limits of `F` that the split cannot overflow.
+fn two_prod<F: Float>(a: F, b: F) -> (F, F) {
+    let p = a * b;
+    let (ah, al) = split(a);
+    let (bh, bl) = split(b);
+    let e = ((ah * bh - p) + ah * bl + al * bh) + al * bl;
+    (p, e)
+}
+
+/// Square-and-multiply directly in `F`. This is only accurate if every product is exact, which
+/// is the case for zero, infinity and NaN.
+fn pow_exact<F: Float>(a: F, b: i32) -> F {
     let mut a = a;
     let recip = b < 0;
     let mut pow = Int::abs_diff(b, 0);
@@ -20,7 +162,44 @@ fn pow<F: Float>(a: F, b: i32) -> F {
     if recip { F::ONE / mul } else { mul }
 }
 
+/// Returns `a` raised to the power `b`.
+///
+/// Squaring and multiplying in `F` loses up to an ULP per operation, which compounds to large
+/// errors for big exponents. Instead, the intermediate values are held in the extended
+/// precision [`Wide`] representation and the result is only rounded once at the end.
+fn pow<F: Float>(a: F, b: i32) -> F {
+    if a.to_bits() & !F::SIGN_MASK == F::Int::ZERO || a.ex() == F::EXP_SAT {
+        return pow_exact(a, b);
+    }
+
+    let negative = a.is_sign_negative() && b & 1 != 0;
+    let mut base = Wide::new(a.abs());
+    let mut pow = b.unsigned_abs();
+    let mut mul = Wide::ONE;
+    loop {
+        if (pow & 1) != 0 {
+            mul = mul.mul(base);
+        }
+        pow >>= 1;
+        if pow == 0 {
+            break;
+        }
+        base = base.mul(base);
+    }
+
+    if b < 0 {
+        mul = mul.recip();
+    }
+
+    mul.to_float(negative)
+}
+
 intrinsics! {
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __powihf2(a: f16, b: i32) -> f16 {
+        pow(a, b)
+    }
+
     pub extern "C" fn __powisf2(a: f32, b: i32) -> f32 {
         pow(a, b)
     }
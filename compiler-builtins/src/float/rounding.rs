@@ -0,0 +1,113 @@
+//! Rounding and status flags shared by the `*_round` variants of the arithmetic and conversion
+//! routines.
+//!
+//! Results are built from a sign, a biased exponent and a significand with the implicit bit at
+//! `F::SIG_BITS + 3`. The three low bits are the round bit, a guard bit and a sticky bit that is
+//! set if any lower bit of the exact result is nonzero. Underflow is signaled for inexact results
+//! that are tiny after rounding, like x86 and RISC-V (IEEE 754 allows detecting tininess before or
+//! after rounding).
+
+use crate::support::{CastFrom, CastInto, Float, FpResult, Int, MinInt, Round, Status};
+
+/// Whether a result with the given low bits should have its magnitude rounded up. `odd` is the
+/// lowest bit that is kept.
+fn round_up(round: Round, negative: bool, odd: bool, rgs: u32) -> bool {
+    match round {
+        Round::Nearest => rgs > 0b100 || (rgs == 0b100 && odd),
+        Round::Negative => negative && rgs != 0,
+        Round::Positive => !negative && rgs != 0,
+        Round::Zero => false,
+    }
+}
+
+/// The result of an operation whose exponent is too large for `F`: infinity, or the largest
+/// finite value when rounding toward zero.
+pub(crate) fn overflow<F: Float>(negative: bool, round: Round) -> FpResult<F> {
+    let to_inf = match round {
+        Round::Nearest => true,
+        Round::Negative => negative,
+        Round::Positive => !negative,
+        Round::Zero => false,
+    };
+
+    let abs = if to_inf { F::INFINITY } else { F::MAX };
+    let val = if negative { -abs } else { abs };
+    FpResult::new(val, Status::OVERFLOW.with(Status::INEXACT))
+}
+
+/// The status for an operation with a NaN operand, `INVALID` if either operand is a signaling NaN.
+pub(crate) fn nan_status<F: Float>(a: F, b: F) -> Status {
+    let quiet_bit = F::IMPLICIT_BIT >> 1;
+    let is_snan = |x: F| x.is_nan() && x.to_bits() & quiet_bit == F::Int::ZERO;
+    if is_snan(a) || is_snan(b) {
+        Status::INVALID
+    } else {
+        Status::OK
+    }
+}
+
+/// Round `sig * 2^(exp - F::EXP_BIAS - F::SIG_BITS - 3)` to `F`.
+///
+/// `sig` must be less than `2 << (F::SIG_BITS + 3)`. It must have the implicit bit set if `exp` is
+/// positive; smaller exponents give subnormal results and can be arbitrarily small.
+#[inline(always)]
+pub(crate) fn round_pack<F: Float>(
+    negative: bool,
+    mut exp: i32,
+    mut sig: F::Int,
+    round: Round,
+) -> FpResult<F>
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+{
+    let zero = F::Int::ZERO;
+    let one = F::Int::ONE;
+    let rgs_mask = F::Int::cast_from(0b111u32);
+    let mut status = Status::OK;
+
+    if exp >= F::EXP_SAT as i32 {
+        return overflow(negative, round);
+    }
+
+    if exp <= 0 {
+        // Only a result just below the smallest normal can round up to it.
+        let max_sig = (F::IMPLICIT_BIT << 1) - one;
+        let rounds_to_normal = exp == 0
+            && sig >> 3 == max_sig
+            && round_up(round, negative, true, (sig & rgs_mask).cast());
+
+        // Shift into subnormal position, keeping the shifted out bits as sticky.
+        let shift = (1 - exp) as u32;
+        let (shifted, sticky) = if shift < F::BITS {
+            (sig >> shift, sig << (F::BITS - shift) != zero)
+        } else {
+            (zero, sig != zero)
+        };
+        sig = shifted | F::Int::from_bool(sticky);
+        exp = 0;
+
+        if !rounds_to_normal && sig & rgs_mask != zero {
+            status = Status::UNDERFLOW;
+        }
+    }
+
+    let rgs: u32 = (sig & rgs_mask).cast();
+    let mut res = ((sig >> 3) & F::SIG_MASK) | (F::Int::cast_from(exp as u32) << F::SIG_BITS);
+
+    if rgs != 0 {
+        status = status.with(Status::INEXACT);
+    }
+
+    // This may carry into the exponent, including up to infinity.
+    if round_up(round, negative, res & one != zero, rgs) {
+        res += one;
+    }
+
+    if res == F::EXP_MASK {
+        status = status.with(Status::OVERFLOW);
+    }
+
+    let sign = if negative { F::SIGN_MASK } else { zero };
+    FpResult::new(F::from_bits(res | sign), status)
+}
@@ -1,4 +1,17 @@
-use crate::support::Float;
+use crate::float::add::add_round;
+use crate::support::{CastInto, Float, FpResult, Round};
+
+/// Returns `a - b` rounded in the direction of `round`, along with the exception flags that the
+/// operation raises.
+pub fn sub_round<F: Float>(a: F, b: F, round: Round) -> FpResult<F>
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+    i32: CastInto<F::Int>,
+    F::Int: CastInto<i32>,
+{
+    add_round(a, F::from_bits(b.to_bits() ^ F::SIGN_MASK), round)
+}
 
 intrinsics! {
     #[cfg(f16_enabled)]
@@ -1,6 +1,7 @@
-use crate::support::{CastInto, Float, Int, MinInt};
+use super::rounding::{nan_status, round_pack};
+use crate::support::{CastFrom, CastInto, Float, FpResult, Int, MinInt, Round, bf16};
 
-fn trunc<F: Float, R: Float>(a: F) -> R
+pub(crate) fn trunc<F: Float, R: Float>(a: F) -> R
 where
     F::Int: CastInto<u64>,
     F::Int: CastInto<u32>,
@@ -31,7 +32,13 @@ where
     let dst_inf_exp = R::EXP_SAT;
     let dst_exp_bias = R::EXP_BIAS;
 
-    let underflow_exponent: F::Int = (src_exp_bias + 1 - dst_exp_bias).cast();
+    // If the exponent ranges match (e.g. `f32` to `bf16`), subnormals line up and can be handled
+    // the same way as normal numbers.
+    let underflow_exponent: F::Int = if src_exp_bias == dst_exp_bias {
+        src_zero
+    } else {
+        (src_exp_bias + 1 - dst_exp_bias).cast()
+    };
     let overflow_exponent: F::Int = (src_exp_bias + dst_inf_exp - dst_exp_bias).cast();
     let underflow: F::Int = underflow_exponent << F::SIG_BITS;
     let overflow: F::Int = overflow_exponent << F::SIG_BITS;
@@ -113,6 +120,41 @@ where
     R::from_bits(abs_result | sign.wrapping_shr(src_bits - dst_bits).cast())
 }
 
+/// Convert `a` to a narrower float rounded in the direction of `round`, along with the exception
+/// flags that the conversion raises.
+pub fn trunc_round<F: Float, R: Float>(a: F, round: Round) -> FpResult<R>
+where
+    F::Int: CastInto<u64>,
+    F::Int: CastInto<u32>,
+    u64: CastInto<F::Int>,
+    u32: CastInto<F::Int>,
+    R::Int: CastInto<u32>,
+    u32: CastInto<R::Int>,
+    F::Int: CastInto<R::Int>,
+{
+    let a_abs = a.to_bits() & !F::SIGN_MASK;
+    let negative = a.is_sign_negative();
+
+    // NaN, infinity and zero convert exactly (other than the NaN payload).
+    if a_abs >= F::EXP_MASK || a_abs == F::Int::ZERO {
+        return FpResult::new(trunc(a), nan_status(a, a));
+    }
+
+    let (exp, sig) = if a_abs < F::IMPLICIT_BIT {
+        F::normalize(a_abs)
+    } else {
+        (a.ex() as i32, a.frac() | F::IMPLICIT_BIT)
+    };
+    let exp = exp - F::EXP_BIAS as i32 + R::EXP_BIAS as i32;
+
+    // Keep three bits below the significand of `R`, the last one sticky.
+    let shift = F::SIG_BITS - R::SIG_BITS - 3;
+    let sticky = sig << (F::BITS - shift) != F::Int::ZERO;
+    let sig = R::Int::cast_from_lossy(sig >> shift) | R::Int::from_bool(sticky);
+
+    round_pack(negative, exp, sig, round)
+}
+
 intrinsics! {
     #[arm_aeabi_alias = __aeabi_d2f]
     pub extern "C" fn __truncdfsf2(a: f64) -> f32 {
@@ -159,3 +201,35 @@ intrinsics! {
         trunc(a)
     }
 }
+
+// There is no `bf16` primitive. It is passed and returned the same way as `f16`, so that is used
+// as the ABI type.
+intrinsics! {
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __truncsfbf2(a: f32) -> f16 {
+        f16::from_bits(trunc::<_, bf16>(a).to_bits())
+    }
+
+    #[cfg(f16_enabled)]
+    pub extern "C" fn __truncdfbf2(a: f64) -> f16 {
+        f16::from_bits(trunc::<_, bf16>(a).to_bits())
+    }
+
+    #[ppc_name = __trunckfbf2]
+    #[cfg(all(f16_enabled, f128_enabled))]
+    pub extern "C" fn __trunctfbf2(a: f128) -> f16 {
+        f16::from_bits(trunc::<_, bf16>(a).to_bits())
+    }
+}
+
+#[cfg(all(
+    target_arch = "x86_64",
+    not(any(windows, target_os = "cygwin", target_os = "uefi")),
+    f128_enabled
+))]
+intrinsics! {
+    #[x87_ret_abi]
+    pub extern "C" fn __trunctfxf2(a: f128) -> f80 {
+        trunc(a)
+    }
+}
@@ -1,3 +1,4 @@
+use super::trap_on_overflow;
 use crate::support::{DInt, Int, MinInt};
 
 trait UAddSub: DInt + Int {
@@ -18,6 +19,8 @@ trait UAddSub: DInt + Int {
     }
 }
 
+impl UAddSub for u32 {}
+impl UAddSub for u64 {}
 impl UAddSub for u128 {}
 
 trait AddSub: Int
@@ -32,6 +35,8 @@ where
     }
 }
 
+impl AddSub for i32 {}
+impl AddSub for i64 {}
 impl AddSub for u128 {}
 impl AddSub for i128 {}
 
@@ -45,6 +50,8 @@ where
     }
 }
 
+impl Addo for i32 {}
+impl Addo for i64 {}
 impl Addo for i128 {}
 impl Addo for u128 {}
 
@@ -58,6 +65,8 @@ where
     }
 }
 
+impl Subo for i32 {}
+impl Subo for i64 {}
 impl Subo for i128 {}
 impl Subo for u128 {}
 
@@ -101,4 +110,52 @@ intrinsics! {
         *oflow = o.into();
         sub
     }
+
+    pub extern "C" fn __addvsi3(a: i32, b: i32) -> i32 {
+        trap_on_overflow(a.addo(b))
+    }
+
+    pub extern "C" fn __addvdi3(a: i64, b: i64) -> i64 {
+        trap_on_overflow(a.addo(b))
+    }
+
+    pub extern "C" fn __addvti3(a: i128, b: i128) -> i128 {
+        trap_on_overflow(a.addo(b))
+    }
+
+    pub extern "C" fn __subvsi3(a: i32, b: i32) -> i32 {
+        trap_on_overflow(a.subo(b))
+    }
+
+    pub extern "C" fn __subvdi3(a: i64, b: i64) -> i64 {
+        trap_on_overflow(a.subo(b))
+    }
+
+    pub extern "C" fn __subvti3(a: i128, b: i128) -> i128 {
+        trap_on_overflow(a.subo(b))
+    }
+
+    pub extern "C" fn __negvsi2(a: i32) -> i32 {
+        trap_on_overflow(0i32.subo(a))
+    }
+
+    pub extern "C" fn __negvdi2(a: i64) -> i64 {
+        trap_on_overflow(0i64.subo(a))
+    }
+
+    pub extern "C" fn __negvti2(a: i128) -> i128 {
+        trap_on_overflow(0i128.subo(a))
+    }
+
+    pub extern "C" fn __absvsi2(a: i32) -> i32 {
+        if a < 0 { __negvsi2(a) } else { a }
+    }
+
+    pub extern "C" fn __absvdi2(a: i64) -> i64 {
+        if a < 0 { __negvdi2(a) } else { a }
+    }
+
+    pub extern "C" fn __absvti2(a: i128) -> i128 {
+        if a < 0 { __negvti2(a) } else { a }
+    }
 }
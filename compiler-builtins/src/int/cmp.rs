@@ -0,0 +1,33 @@
+/// Three-way comparison in the libgcc convention: 0 if `a < b`, 1 if `a == b` and 2 if `a > b`.
+fn cmp<I: PartialOrd>(a: I, b: I) -> i32 {
+    if a < b {
+        0
+    } else if a == b {
+        1
+    } else {
+        2
+    }
+}
+
+intrinsics! {
+    /// Returns 0, 1 or 2 if `a` is less than, equal to or greater than `b` (64 bit signed version).
+    pub extern "C" fn __cmpdi2(a: i64, b: i64) -> i32 {
+        cmp(a, b)
+    }
+
+    /// Returns 0, 1 or 2 if `a` is less than, equal to or greater than `b` (128 bit signed version).
+    pub extern "C" fn __cmpti2(a: i128, b: i128) -> i32 {
+        cmp(a, b)
+    }
+
+    /// Returns 0, 1 or 2 if `a` is less than, equal to or greater than `b` (64 bit unsigned version).
+    pub extern "C" fn __ucmpdi2(a: u64, b: u64) -> i32 {
+        cmp(a, b)
+    }
+
+    /// Returns 0, 1 or 2 if `a` is less than, equal to or greater than `b` (128 bit unsigned
+    /// version).
+    pub extern "C" fn __ucmpti2(a: u128, b: u128) -> i32 {
+        cmp(a, b)
+    }
+}
@@ -2,9 +2,20 @@ mod specialized_div_rem;
 
 pub mod addsub;
 pub mod bswap;
+pub mod cmp;
 pub mod leading_zeros;
 pub mod mul;
+pub mod popcount;
 pub mod sdiv;
 pub mod shift;
 pub mod trailing_zeros;
 pub mod udiv;
+
+/// Unwrap the result of an operation that reports overflow, aborting if it overflowed. This is
+/// what the `v` intrinsics called by C code compiled with `-ftrapv` do.
+fn trap_on_overflow<T>((res, overflow): (T, bool)) -> T {
+    if overflow {
+        core::intrinsics::abort();
+    }
+    res
+}
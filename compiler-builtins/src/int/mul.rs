@@ -1,3 +1,4 @@
+use super::trap_on_overflow;
 use crate::support::{DInt, HInt, Int};
 
 trait Mul: DInt + Int
@@ -133,6 +134,7 @@ intrinsics! {
     }
 
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_mpyll]
     #[arm_aeabi_alias = __aeabi_lmul]
     pub extern "C" fn __muldi3(a: u64, b: u64) -> u64 {
         #[cfg(all(any(target_arch = "riscv32", target_arch = "riscv64"), not(target_feature = "m")))]
@@ -203,4 +205,256 @@ intrinsics! {
         mul
     }
 
+    pub extern "C" fn __mulvsi3(a: i32, b: i32) -> i32 {
+        trap_on_overflow(i32_overflowing_mul(a, b))
+    }
+
+    pub extern "C" fn __mulvdi3(a: i64, b: i64) -> i64 {
+        trap_on_overflow(i64_overflowing_mul(a, b))
+    }
+
+    pub extern "C" fn __mulvti3(a: i128, b: i128) -> i128 {
+        trap_on_overflow(i128_overflowing_mul(a, b))
+    }
+}
+
+// AVR has no multiplier on many devices, and only an 8-bit one on the others. avr-gcc calls these
+// with the [non-standard calling convention](https://gcc.gnu.org/wiki/avr-gcc#Exceptions_to_the_Calling_Convention)
+// of libgcc, which only lets them clobber the registers listed. The operands are in the same
+// registers as for a C call, except for `__umulhisi3`, so LLVM can call them too.
+//
+// They use shift-and-add, so they work without `mul`. R1 is always zero and R0 is always free. The
+// assembler doesn't see the CPU features of the device, so they only use instructions that every
+// AVR core has (no `sbiw`, `push` or `pop`).
+#[cfg(target_arch = "avr")]
+intrinsics! {
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mulqi3() {
+        // compute 8-bit `a * b`.
+        //
+        // Inputs:
+        //     R24: a
+        //     R22: b
+        // Outputs:
+        //     R24: product
+        // Clobbers:
+        //     R22
+        //     R0: product while it is computed
+        core::arch::naked_asm!(
+            // For each set bit of `a`, starting from the bottom, add `b` shifted to that bit.
+            "clr R0",       // product = 0
+            "1:",
+            "sbrc R24, 0",  // if a & 1
+            "add R0, R22",  //     product += b
+            "lsl R22",      // b <<= 1
+            "lsr R24",      // a >>= 1
+            "brne 1b",      // until a == 0
+            "mov R24, R0",
+            "ret",
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mulhi3() {
+        // compute 16-bit `a * b`.
+        //
+        // Inputs:
+        //     R24: a [low]
+        //     R25: a [high]
+        //     R22: b [low]
+        //     R23: b [high]
+        // Outputs:
+        //     R24: product [low]
+        //     R25: product [high]
+        // Clobbers:
+        //     R22, R23
+        //     R0, R21: product while it is computed
+        core::arch::naked_asm!(
+            // The same as `__mulqi3`, with 16-bit operations.
+            "clr R0",           // product = 0
+            "clr R21",
+            "1:",
+            "sbrs R24, 0",      // if a & 1
+            "rjmp 2f",
+            "add R0, R22",      //     product += b
+            "adc R21, R23",
+            "2:",
+            "lsl R22",          // b <<= 1
+            "rol R23",
+            "lsr R25",          // a >>= 1
+            "ror R24",
+            "cp R24, R1",       // until a == 0
+            "cpc R25, R1",
+            "brne 1b",
+            "mov R24, R0",
+            "mov R25, R21",
+            "ret",
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mulpsi3() {
+        // compute 24-bit `a * b`.
+        //
+        // Inputs:
+        //     R22: a [low]
+        //     R23: a [middle]
+        //     R24: a [high]
+        //     R18: b [low]
+        //     R19: b [middle]
+        //     R20: b [high]
+        // Outputs:
+        //     R22: product [low]
+        //     R23: product [middle]
+        //     R24: product [high]
+        // Clobbers:
+        //     R0: loop counter
+        //     R26, R27, R25: product while it is computed
+        core::arch::naked_asm!(
+            // `b` has to be preserved, so this goes from the top bit of `a` instead, doubling the
+            // product each time.
+            "ldi R26, 24",      // for each bit
+            "mov R0, R26",
+            "clr R26",          // product = 0
+            "clr R27",
+            "clr R25",
+            "1:",
+            "lsl R26",          //     product <<= 1
+            "rol R27",
+            "rol R25",
+            "lsl R22",          //     shift the top bit out of a
+            "rol R23",
+            "rol R24",
+            "brcc 2f",          //     if it was set
+            "add R26, R18",     //         product += b
+            "adc R27, R19",
+            "adc R25, R20",
+            "2:",
+            "dec R0",           // end loop
+            "brne 1b",
+            "mov R22, R26",
+            "mov R23, R27",
+            "mov R24, R25",
+            "ret",
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mulsi3() {
+        // compute 32-bit `a * b`.
+        //
+        // Inputs:
+        //     R22: a [low]
+        //     R23
+        //     R24
+        //     R25: a [high]
+        //     R18: b [low]
+        //     R19
+        //     R20
+        //     R21: b [high]
+        // Outputs:
+        //     R22: product [low]
+        //     R23
+        //     R24
+        //     R25: product [high]
+        // Clobbers:
+        //     R0: loop counter
+        //     R26, R27: `aH * bL + aL * bH`
+        core::arch::naked_asm!(
+            // With 16-bit halves, the product is `aL * bL + ((aH * bL + aL * bH) << 16)`. Only
+            // the low 16 bits of the cross terms matter, and `aL * bL` is computed in place, so
+            // this fits in the registers avr-gcc lets it clobber.
+            //
+            // The cross terms go from the top bit of `aH` and `aL`, like `__mulpsi3`. `aL` is
+            // rotated back to where it started, and `aH` ends up zero.
+            "ldi R26, 16",      // for each bit
+            "mov R0, R26",
+            "clr R26",          // cross = 0
+            "clr R27",
+            "1:",
+            "lsl R26",          //     cross <<= 1
+            "rol R27",
+            "lsl R24",          //     shift the top bit out of aH
+            "rol R25",
+            "brcc 2f",          //     if it was set
+            "add R26, R18",     //         cross += bL
+            "adc R27, R19",
+            "2:",
+            "lsl R22",          //     shift the top bit out of aL
+            "rol R23",
+            "brcc 3f",          //     if it was set
+            "inc R22",          //         put it back at the bottom
+            "add R26, R20",     //         cross += bH
+            "adc R27, R21",
+            "3:",
+            "dec R0",           // end loop
+            "brne 1b",
+            // `aL * bL` goes from the bottom bit of `aL`, adding `bL` to the high half of the
+            // product and shifting it right into the bits of `aL` that have been used.
+            "ldi R24, 16",      // for each bit
+            "mov R0, R24",
+            "clr R24",          // product [high] = 0
+            "lsr R23",          // shift the bottom bit out of aL
+            "ror R22",
+            "4:",
+            "brcc 5f",          //     if it was set
+            "add R24, R18",     //         product [high] += bL
+            "adc R25, R19",
+            "5:",
+            "ror R25",          //     product >>= 1, shifting the next bit out of aL
+            "ror R24",
+            "ror R23",
+            "ror R22",
+            "dec R0",           // end loop
+            "brne 4b",
+            "add R24, R26",     // product [high] += cross
+            "adc R25, R27",
+            "ret",
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __umulhisi3() {
+        // compute the 32-bit product of unsigned 16-bit `a` and `b`.
+        //
+        // Inputs:
+        //     R26: a [low]
+        //     R27: a [high]
+        //     R18: b [low]
+        //     R19: b [high]
+        // Outputs:
+        //     R22: product [low]
+        //     R23
+        //     R24
+        //     R25: product [high]
+        // Clobbers:
+        //     R0: loop counter
+        core::arch::naked_asm!(
+            // Like `__mulpsi3`, but the product is built up in the output registers, and `a` is
+            // rotated instead of shifted so that it is back where it started after 16 bits.
+            "ldi R22, 16",      // for each bit
+            "mov R0, R22",
+            "clr R22",          // product = 0
+            "clr R23",
+            "clr R24",
+            "clr R25",
+            "1:",
+            "lsl R22",          //     product <<= 1
+            "rol R23",
+            "rol R24",
+            "rol R25",
+            "lsl R26",          //     shift the top bit out of a
+            "rol R27",
+            "brcc 2f",          //     if it was set
+            "inc R26",          //         put it back at the bottom
+            "add R22, R18",     //         product += b
+            "adc R23, R19",
+            "adc R24, R1",
+            "adc R25, R1",
+            "2:",
+            "dec R0",           // end loop
+            "brne 1b",
+            "ret",
+        );
+    }
 }
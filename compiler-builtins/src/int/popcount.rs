@@ -0,0 +1,84 @@
+use crate::support::Int;
+
+// These must not use `count_ones`, which may be lowered to a call to these very functions on
+// targets without a population count instruction.
+
+/// Returns `I` with every byte set to `b`.
+fn splat<I: Int>(b: u8) -> I {
+    let mut x = I::cast_from(b);
+    let mut shift = 8;
+    while shift < I::BITS {
+        x |= x << shift;
+        shift *= 2;
+    }
+    x
+}
+
+/// Returns the number of ones in the binary representation of `x`.
+fn popcount<I: Int>(x: I) -> i32 {
+    let m1 = splat::<I>(0x55);
+    let m2 = splat::<I>(0x33);
+    let m4 = splat::<I>(0x0f);
+
+    // Count the ones in each pair of bits, then in each nibble and each byte.
+    let mut x = x - ((x >> 1) & m1);
+    x = (x & m2) + ((x >> 2) & m2);
+    x = (x + (x >> 4)) & m4;
+
+    // Add up the bytes. The total is at most 128 so it fits in the lowest one.
+    let mut shift = 8;
+    while shift < I::BITS {
+        x += x >> shift;
+        shift *= 2;
+    }
+
+    let count: u32 = (x & I::cast_from(0xffu8)).cast();
+    count as i32
+}
+
+/// Returns 1 if `x` has an odd number of ones in its binary representation, 0 otherwise.
+fn parity<I: Int>(x: I) -> i32 {
+    // Fold the value onto its lowest nibble, which keeps the parity.
+    let mut x = x;
+    let mut shift = I::BITS / 2;
+    while shift >= 4 {
+        x ^= x >> shift;
+        shift /= 2;
+    }
+
+    // `0x6996` has bit `n` set if `n` has an odd number of ones.
+    let nibble: u32 = (x & I::cast_from(0xfu8)).cast();
+    (0x6996 >> nibble) & 1
+}
+
+intrinsics! {
+    /// Returns the number of ones in the binary representation of `x` (32 bit version).
+    pub extern "C" fn __popcountsi2(x: u32) -> i32 {
+        popcount(x)
+    }
+
+    /// Returns the number of ones in the binary representation of `x` (64 bit version).
+    pub extern "C" fn __popcountdi2(x: u64) -> i32 {
+        popcount(x)
+    }
+
+    /// Returns the number of ones in the binary representation of `x` (128 bit version).
+    pub extern "C" fn __popcountti2(x: u128) -> i32 {
+        popcount(x)
+    }
+
+    /// Returns the parity of the number of ones in `x` (32 bit version).
+    pub extern "C" fn __paritysi2(x: u32) -> i32 {
+        parity(x)
+    }
+
+    /// Returns the parity of the number of ones in `x` (64 bit version).
+    pub extern "C" fn __paritydi2(x: u64) -> i32 {
+        parity(x)
+    }
+
+    /// Returns the parity of the number of ones in `x` (128 bit version).
+    pub extern "C" fn __parityti2(x: u128) -> i32 {
+        parity(x)
+    }
+}
@@ -155,11 +155,146 @@ intrinsics! {
 
         ((r as u32 as u64) << 32) | (t as u32 as u64)
     }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __divmodqi4() {
+        // compute signed 8-bit `n / d` and `n % d`.
+        //
+        // Note: GCC implements a [non-standard calling convention](https://gcc.gnu.org/wiki/avr-gcc#Exceptions_to_the_Calling_Convention) for this function.
+        // Inputs:
+        //     R24: dividend
+        //     R22: divisor
+        // Outputs:
+        //     R24: quotient  (dividend / divisor)
+        //     R25: remainder (dividend % divisor)
+        // Clobbers:
+        //     R22: divisor magnitude
+        //     R23: loop counter
+        //     R0: sign of the quotient
+        //     T: sign of the remainder
+        core::arch::naked_asm!(
+            // Divide the magnitudes, then fix up the signs. The remainder has the sign of the
+            // dividend, and the quotient is negative if the signs differ.
+            "bst R24, 7",
+            "mov R0, R24",
+            "eor R0, R22",
+            "sbrc R24, 7",  // dividend = |dividend|
+            "neg R24",
+            "sbrc R22, 7",  // divisor = |divisor|
+            "neg R22",
+
+            // The same long division as `__udivmodqi4`.
+            "clr R25",      // remainder = 0
+
+            "ldi R23, 8",   // for each bit
+            "1:",
+            "lsl R24",      //     shift the dividend MSb
+            "rol R25",      //     into the remainder LSb
+
+            "cp  R25, R22", //     if remainder >= divisor
+            "brlo 2f",
+            "sub R25, R22", //         remainder -= divisor
+            "sbr R24, 1",   //         quotient |= 1
+            "2:",
+
+            "dec R23",      // end loop
+            "brne 1b",
+
+            "brtc 3f",      // if the dividend was negative
+            "neg R25",      //     remainder = -remainder
+            "3:",
+            "sbrc R0, 7",   // if the signs differ
+            "neg R24",      //     quotient = -quotient
+            "ret",
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __divmodhi4() {
+        // compute signed 16-bit `n / d` and `n % d`.
+        //
+        // Note: GCC implements a [non-standard calling convention](https://gcc.gnu.org/wiki/avr-gcc#Exceptions_to_the_Calling_Convention) for this function.
+        // Inputs:
+        //     R24: dividend [low]
+        //     R25: dividend [high]
+        //     R22: divisor [low]
+        //     R23: divisor [high]
+        // Outputs:
+        //     R22: quotient [low]  (dividend / divisor)
+        //     R23: quotient [high]
+        //     R24: remainder [low] (dividend % divisor)
+        //     R25: remainder [high]
+        // Clobbers:
+        //     R21: loop counter
+        //     R26: divisor [low]
+        //     R27: divisor [high]
+        //     R0: sign of the quotient
+        //     T: sign of the remainder
+        core::arch::naked_asm!(
+            // The same as `__divmodqi4`. Negating a 16-bit value complements the high byte, and
+            // adds one to it if the low byte was zero.
+            "bst R25, 7",
+            "mov R0, R25",
+            "eor R0, R23",
+            "sbrs R25, 7",      // dividend = |dividend|
+            "rjmp 1f",
+            "com R25",
+            "neg R24",
+            "sbci R25, 0xff",
+            "1:",
+            "sbrs R23, 7",      // divisor = |divisor|
+            "rjmp 2f",
+            "com R23",
+            "neg R22",
+            "sbci R23, 0xff",
+            "2:",
+
+            // The same long division as `__udivmodhi4`.
+            "mov R26, R22",     // move divisor to make room for quotient
+            "mov R27, R23",
+            "mov R22, R24",     // move dividend to output location (becomes quotient)
+            "mov R23, R25",
+            "clr R24",          // remainder = 0
+            "clr R25",
+
+            "ldi R21, 16",      // for each bit
+            "3:",
+            "lsl R22",          //     shift the dividend MSb
+            "rol R23",
+            "rol R24",          //     into the remainder LSb
+            "rol R25",
+
+            "cp  R24, R26",     //     if remainder >= divisor
+            "cpc R25, R27",
+            "brlo 4f",
+            "sub R24, R26",     //         remainder -= divisor
+            "sbc R25, R27",
+            "sbr R22, 1",       //         quotient |= 1
+            "4:",
+
+            "dec R21",          // end loop
+            "brne 3b",
+
+            "brtc 5f",          // if the dividend was negative
+            "com R25",          //     remainder = -remainder
+            "neg R24",
+            "sbci R25, 0xff",
+            "5:",
+            "sbrs R0, 7",       // if the signs differ
+            "rjmp 6f",
+            "com R23",          //     quotient = -quotient
+            "neg R22",
+            "sbci R23, 0xff",
+            "6:",
+            "ret",
+        );
+    }
 }
 
 // The `#[arm_aeabi_alias = __aeabi_idiv]` attribute cannot be made to work with `intrinsics!` in macros
 intrinsics! {
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_divli]
     #[arm_aeabi_alias = __aeabi_idiv]
     /// Returns `n / d`
     pub extern "C" fn __divsi3(a: i32, b: i32) -> i32 {
@@ -203,3 +338,56 @@ sdivmod!(
 );
 sdiv!(__udivti3, __divti3, u128, i128,);
 smod!(__umodti3, __modti3, u128, i128,);
+
+/// Replaces the signed `_BitInt(bits)` value in `x` by its magnitude and returns whether it was
+/// negative.
+fn bitint_abs(x: &mut [u32], bits: u32) -> bool {
+    bitint_truncate(x, bits);
+    let negative = (x[x.len() - 1] >> ((bits - 1) % 32)) & 1 != 0;
+    if negative {
+        neg_words(x);
+        bitint_truncate(x, bits);
+    }
+    negative
+}
+
+intrinsics! {
+    /// Stores `a / b` in `quo`, for signed `_BitInt(bits)` values. `a` and `b` are clobbered.
+    ///
+    /// # Safety
+    ///
+    /// `quo`, `a` and `b` must be valid for reads and writes of `bits.div_ceil(32)` words, and
+    /// `bits` must be nonzero.
+    pub unsafe extern "C" fn __divei4(quo: *mut u32, a: *mut u32, b: *mut u32, bits: u32) {
+        let (quo, a, b) = unsafe {
+            (bitint_words(quo, bits), bitint_words(a, bits), bitint_words(b, bits))
+        };
+        let a_neg = bitint_abs(a, bits);
+        let b_neg = bitint_abs(b, bits);
+        words_div_rem(Some(quo), a, b);
+        if a_neg != b_neg {
+            neg_words(quo);
+        }
+        bitint_finish(quo);
+    }
+
+    /// Stores `a % b` in `rem`, for signed `_BitInt(bits)` values. `a` and `b` are clobbered.
+    ///
+    /// # Safety
+    ///
+    /// `rem`, `a` and `b` must be valid for reads and writes of `bits.div_ceil(32)` words, and
+    /// `bits` must be nonzero.
+    pub unsafe extern "C" fn __modei4(rem: *mut u32, a: *mut u32, b: *mut u32, bits: u32) {
+        let (rem, a, b) = unsafe {
+            (bitint_words(rem, bits), bitint_words(a, bits), bitint_words(b, bits))
+        };
+        let a_neg = bitint_abs(a, bits);
+        bitint_abs(b, bits);
+        words_div_rem(None, a, b);
+        rem.copy_from_slice(a);
+        if a_neg {
+            neg_words(rem);
+        }
+        bitint_finish(rem);
+    }
+}
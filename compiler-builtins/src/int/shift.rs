@@ -75,6 +75,7 @@ intrinsics! {
     }
 
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_sllll]
     #[arm_aeabi_alias = __aeabi_llsl]
     pub extern "C" fn __ashldi3(a: u64, b: core::ffi::c_uint) -> u64 {
         a.ashl(b as u32)
@@ -90,6 +91,7 @@ intrinsics! {
     }
 
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_srall]
     #[arm_aeabi_alias = __aeabi_lasr]
     pub extern "C" fn __ashrdi3(a: i64, b: core::ffi::c_uint) -> i64 {
         a.ashr(b as u32)
@@ -105,6 +107,7 @@ intrinsics! {
     }
 
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_srlll]
     #[arm_aeabi_alias = __aeabi_llsr]
     pub extern "C" fn __lshrdi3(a: u64, b: core::ffi::c_uint) -> u64 {
         a.lshr(b as u32)
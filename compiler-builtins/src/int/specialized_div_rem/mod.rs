@@ -67,6 +67,9 @@ mod trifecta;
 #[macro_use]
 mod asymmetric;
 
+mod word_slice;
+pub(crate) use self::word_slice::{neg_words, words_div_rem};
+
 /// The behavior of all divisions by zero is controlled by this function. This function should be
 /// impossible to reach by Rust users, unless `compiler-builtins` public division functions or
 /// `core/std::unchecked_div/rem` are directly used without a zero check in front.
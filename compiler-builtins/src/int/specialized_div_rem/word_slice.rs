@@ -0,0 +1,165 @@
+//! Division of integers of arbitrary width, stored as slices of `u32` words with the least
+//! significant word first. This is used for `_BitInt` and LLVM integers wider than 128 bits.
+//!
+//! The algorithm is Knuth's algorithm D (The Art of Computer Programming, volume 2, section
+//! 4.3.1), which is long division with a radix of `2^32`. Each quotient word is estimated from the
+//! leading words of the remainder and the divisor, after normalizing so the divisor has its most
+//! significant bit set. The estimate is then at most two too large and gets corrected.
+
+use super::{u32_normalization_shift, u64_by_u64_div_rem, zero_div_fn};
+
+/// Returns the number of words in `x` without the leading zero words.
+fn significant_len(x: &[u32]) -> usize {
+    let mut len = x.len();
+    while len > 0 && x[len - 1] == 0 {
+        len -= 1;
+    }
+    len
+}
+
+/// Shifts `x` left by `shl` bits, which must be less than 32, and returns the bits shifted out.
+fn shl_words(x: &mut [u32], shl: u32) -> u32 {
+    if shl == 0 {
+        return 0;
+    }
+    let mut carry = 0;
+    let mut i = 0;
+    while i < x.len() {
+        let word = x[i];
+        x[i] = (word << shl) | carry;
+        carry = word >> (32 - shl);
+        i += 1;
+    }
+    carry
+}
+
+/// Shifts `x` right by `shr` bits, which must be less than 32.
+fn shr_words(x: &mut [u32], shr: u32) {
+    if shr == 0 {
+        return;
+    }
+    let mut carry = 0;
+    let mut i = x.len();
+    while i > 0 {
+        i -= 1;
+        let word = x[i];
+        x[i] = (word >> shr) | carry;
+        carry = word << (32 - shr);
+    }
+}
+
+/// Negates `x` in two's complement.
+pub(crate) fn neg_words(x: &mut [u32]) {
+    let mut carry = true;
+    let mut i = 0;
+    while i < x.len() {
+        let (word, c) = (!x[i]).overflowing_add(carry as u32);
+        x[i] = word;
+        carry = c;
+        i += 1;
+    }
+}
+
+/// Divides `duo` by `div`, storing the quotient in `quo` if it is given. The remainder is left in
+/// `duo`.
+///
+/// `quo` must be at least as long as `duo`. `div` is clobbered.
+pub(crate) fn words_div_rem(mut quo: Option<&mut [u32]>, duo: &mut [u32], div: &mut [u32]) {
+    if let Some(quo) = quo.as_deref_mut() {
+        let mut i = 0;
+        while i < quo.len() {
+            quo[i] = 0;
+            i += 1;
+        }
+    }
+
+    let n = significant_len(div);
+    let duo_len = significant_len(duo);
+    if n == 0 {
+        zero_div_fn()
+    }
+    if duo_len < n {
+        return;
+    }
+
+    if n == 1 {
+        // Short division, one word of the dividend at a time.
+        let div = div[0] as u64;
+        let mut rem = 0;
+        let mut i = duo_len;
+        while i > 0 {
+            i -= 1;
+            let (q, r) = u64_by_u64_div_rem((rem << 32) | duo[i] as u64, div);
+            if let Some(quo) = quo.as_deref_mut() {
+                quo[i] = q as u32;
+            }
+            duo[i] = 0;
+            rem = r;
+        }
+        duo[0] = rem as u32;
+        return;
+    }
+
+    // The full normalization of `div[n - 1]` against `u32::MAX` is its number of leading zeros.
+    let shl = u32_normalization_shift(u32::MAX, div[n - 1], true) as u32;
+    let div = &mut div[..n];
+    shl_words(div, shl);
+    // The dividend gets an extra word from normalization, which is kept separately.
+    let mut top = shl_words(&mut duo[..duo_len], shl);
+
+    let div_hi = div[n - 1] as u64;
+    let div_lo = div[n - 2] as u64;
+
+    // Each step divides `top:duo[j..j + n]` by `div`, which leaves a remainder that fits in
+    // `duo[j..j + n]`.
+    let mut j = duo_len - n + 1;
+    while j > 0 {
+        j -= 1;
+
+        // `top <= div_hi` since the previous remainder is less than `div`, so the estimate is at
+        // most `2^32 + 1` and the products below do not overflow.
+        let (mut q, mut r) =
+            u64_by_u64_div_rem(((top as u64) << 32) | duo[j + n - 1] as u64, div_hi);
+        while q > u32::MAX as u64 || q * div_lo > ((r << 32) | duo[j + n - 2] as u64) {
+            q -= 1;
+            r += div_hi;
+            if r > u32::MAX as u64 {
+                break;
+            }
+        }
+
+        // Subtract `q * div`.
+        let mut borrow: i64 = 0;
+        let mut i = 0;
+        while i < n {
+            let p = q * div[i] as u64;
+            let t = duo[j + i] as i64 - borrow - (p & u32::MAX as u64) as i64;
+            duo[j + i] = t as u32;
+            borrow = (p >> 32) as i64 - (t >> 32);
+            i += 1;
+        }
+
+        // The estimate was one too large, add `div` back.
+        if (top as i64) < borrow {
+            q -= 1;
+            let mut carry = 0;
+            let mut i = 0;
+            while i < n {
+                let t = duo[j + i] as u64 + div[i] as u64 + carry;
+                duo[j + i] = t as u32;
+                carry = t >> 32;
+                i += 1;
+            }
+        }
+
+        if let Some(quo) = quo.as_deref_mut() {
+            quo[j] = q as u32;
+        }
+        if j + n < duo_len {
+            duo[j + n] = 0;
+        }
+        top = duo[j + n - 1];
+    }
+
+    shr_words(&mut duo[..n], shl);
+}
@@ -71,4 +71,22 @@ intrinsics! {
             __ctzdi2(lo)
         }
     }
+
+    /// Returns one plus the index of the least significant one in `x`, or zero if `x` is zero
+    /// (32 bit version).
+    pub extern "C" fn __ffssi2(x: u32) -> i32 {
+        if x == 0 { 0 } else { __ctzsi2(x) as i32 + 1 }
+    }
+
+    /// Returns one plus the index of the least significant one in `x`, or zero if `x` is zero
+    /// (64 bit version).
+    pub extern "C" fn __ffsdi2(x: u64) -> i32 {
+        if x == 0 { 0 } else { __ctzdi2(x) as i32 + 1 }
+    }
+
+    /// Returns one plus the index of the least significant one in `x`, or zero if `x` is zero
+    /// (128 bit version).
+    pub extern "C" fn __ffsti2(x: u128) -> i32 {
+        if x == 0 { 0 } else { __ctzti2(x) as i32 + 1 }
+    }
 }
@@ -5,6 +5,7 @@ pub use crate::int::specialized_div_rem::*;
 
 intrinsics! {
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_divul]
     #[arm_aeabi_alias = __aeabi_uidiv]
     /// Returns `n / d`
     pub extern "C" fn __udivsi3(n: u32, d: u32) -> u32 {
@@ -12,6 +13,7 @@ intrinsics! {
     }
 
     #[maybe_use_optimized_c_shim]
+    #[msp430_alias = __mspabi_remul]
     /// Returns `n % d`
     pub extern "C" fn __umodsi3(n: u32, d: u32) -> u32 {
         u32_div_rem(n, d).1
@@ -197,3 +199,66 @@ intrinsics! {
         }
     }
 }
+
+/// Returns the `_BitInt` argument at `x` as a slice with the least significant word first.
+///
+/// The `_BitInt` division intrinsics operate on integers of `bits` bits stored as arrays of
+/// `bits.div_ceil(32)` words, ordered by the target's endianness.
+///
+/// # Safety
+///
+/// `x` must be valid for reads and writes of `bits.div_ceil(32)` words.
+pub(crate) unsafe fn bitint_words<'a>(x: *mut u32, bits: u32) -> &'a mut [u32] {
+    let words = unsafe { core::slice::from_raw_parts_mut(x, bits.div_ceil(32) as usize) };
+    #[cfg(target_endian = "big")]
+    words.reverse();
+    words
+}
+
+/// Clears the bits of `x` above `bits`, which need not be zero or sign extended in the arguments.
+pub(crate) fn bitint_truncate(x: &mut [u32], bits: u32) {
+    if !bits.is_multiple_of(32) {
+        x[x.len() - 1] &= (1 << (bits % 32)) - 1;
+    }
+}
+
+/// Puts a result back in the target's word order.
+pub(crate) fn bitint_finish(_x: &mut [u32]) {
+    #[cfg(target_endian = "big")]
+    _x.reverse();
+}
+
+intrinsics! {
+    /// Stores `a / b` in `quo`, for unsigned `_BitInt(bits)` values. `a` and `b` are clobbered.
+    ///
+    /// # Safety
+    ///
+    /// `quo`, `a` and `b` must be valid for reads and writes of `bits.div_ceil(32)` words, and
+    /// `bits` must be nonzero.
+    pub unsafe extern "C" fn __udivei4(quo: *mut u32, a: *mut u32, b: *mut u32, bits: u32) {
+        let (quo, a, b) = unsafe {
+            (bitint_words(quo, bits), bitint_words(a, bits), bitint_words(b, bits))
+        };
+        bitint_truncate(a, bits);
+        bitint_truncate(b, bits);
+        words_div_rem(Some(quo), a, b);
+        bitint_finish(quo);
+    }
+
+    /// Stores `a % b` in `rem`, for unsigned `_BitInt(bits)` values. `a` and `b` are clobbered.
+    ///
+    /// # Safety
+    ///
+    /// `rem`, `a` and `b` must be valid for reads and writes of `bits.div_ceil(32)` words, and
+    /// `bits` must be nonzero.
+    pub unsafe extern "C" fn __umodei4(rem: *mut u32, a: *mut u32, b: *mut u32, bits: u32) {
+        let (rem, a, b) = unsafe {
+            (bitint_words(rem, bits), bitint_words(a, bits), bitint_words(b, bits))
+        };
+        bitint_truncate(a, bits);
+        bitint_truncate(b, bits);
+        words_div_rem(None, a, b);
+        rem.copy_from_slice(a);
+        bitint_finish(rem);
+    }
+}
@@ -15,6 +15,7 @@
 #![cfg_attr(f16_enabled, feature(f16))]
 #![cfg_attr(f128_enabled, feature(f128))]
 #![cfg_attr(all(target_family = "wasm"), feature(wasm_numeric_instr))]
+#![cfg_attr(target_has_atomic_load_store = "128", feature(integer_atomics))]
 //
 #![allow(unstable_name_collisions)] // FIXME(float_bits_const): remove when stable
 #![allow(unused_features)]
@@ -40,6 +41,7 @@ extern crate core;
 #[macro_use]
 mod macros;
 
+pub mod clear_cache;
 pub mod float;
 pub mod int;
 pub mod math;
@@ -75,6 +77,20 @@ pub mod avr;
 #[cfg(target_arch = "hexagon")]
 pub mod hexagon;
 
+// Also enabled with "unstable-public-internals" so the helpers written in Rust can be tested on
+// the host, but not exported there.
+#[cfg(any(
+    target_arch = "msp430",
+    all(
+        feature = "unstable-public-internals",
+        not(feature = "unmangled-names")
+    )
+))]
+pub mod msp430;
+
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+pub mod riscv;
+
 #[cfg(target_arch = "x86")]
 pub mod x86;
 
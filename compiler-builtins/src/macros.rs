@@ -43,9 +43,17 @@
 ///   ignored if an optimized C version was compiled.
 /// * `arm_aeabi_alias` - handles the "aliasing" of various intrinsics on ARM
 ///   their otherwise typical names to other prefixed ones.
+/// * `msp430_alias` - also exports the intrinsic under its `__mspabi_*` name
+///   on MSP430, which is what LLVM calls there.
 /// * `ppc_name` - changes the name of the symbol on PowerPC platforms without
 ///   changing any other behavior. This is mostly for `f128`, which is `tf` on
 ///   most platforms but `kf` on PowerPC.
+/// * `x87_arg_abi` / `x87_ret_abi` - for x86 and x86-64 builtins that take or
+///   return a `long double`, which Rust has no type for. The function is
+///   written with `f80` and the exported symbol moves the value between its
+///   x87 location and a Rust type.
+/// * `x87_complex_abi` - the same for builtins that take four `long double`s
+///   and return a `long double _Complex`.
 macro_rules! intrinsics {
     () => ();
 
@@ -317,6 +325,38 @@ macro_rules! intrinsics {
         intrinsics!($($rest)*);
     );
 
+    // LLVM calls the MSP430 EABI names (`__mspabi_*`) instead of the usual ones. Where the
+    // signature and calling convention match, the intrinsic is exported under both names.
+    //
+    // This must come before `arm_aeabi_alias`, which is passed on to the next expansion.
+    (
+        #[msp430_alias = $alias:ident]
+        $(#[$($attr:tt)*])*
+        pub extern $abi:tt fn $name:ident( $($argname:ident:  $ty:ty),* ) $(-> $ret:ty)? {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        intrinsics! {
+            $(#[$($attr)*])*
+            pub extern $abi fn $name( $($argname: $ty),* ) $(-> $ret)? {
+                $($body)*
+            }
+        }
+
+        #[cfg(all(target_arch = "msp430", feature = "unmangled-names"))]
+        mod $alias {
+            #[unsafe(no_mangle)]
+            #[cfg_attr(not(any(all(windows, target_env = "gnu"), target_os = "cygwin")), linkage = "weak")]
+            extern $abi fn $alias( $($argname: $ty),* ) $(-> $ret)? {
+                super::$name($($argname),*)
+            }
+        }
+
+        intrinsics!($($rest)*);
+    );
+
     // PowerPC usually uses `kf` rather than `tf` for `f128`. This is just an easy
     // way to change the name on those targets.
     (
@@ -347,6 +387,222 @@ macro_rules! intrinsics {
         intrinsics!($($rest)*);
     );
 
+    // On x86 and x86-64 (other than Windows and UEFI), `long double` is the x87 80-bit format.
+    // It is passed in memory on the stack and returned in `st(0)`, which can't be expressed in
+    // Rust. The builtin is defined with `f80` and the exported symbol converts to or from the x87
+    // layout and calling convention. That is a naked shim, except for i386 arguments, which are
+    // the same as a 12-byte struct's.
+    (
+        #[x87_arg_abi]
+        $(#[$($attr:tt)*])*
+        pub extern $abi:tt fn $name:ident( $argname:ident: f80 ) -> $ret:ty {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$($attr)*])*
+        pub extern $abi fn $name( $argname: $crate::support::f80 ) -> $ret {
+            $($body)*
+        }
+
+        $(#[$($attr)*])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            extern "C" fn from_x87_bits(bits: u128) -> $ret {
+                super::$name($crate::support::f80::from_x87_bits(bits))
+            }
+
+            /// # Safety
+            ///
+            /// The caller must pass the `long double` argument on the stack.
+            #[cfg(target_arch = "x86_64")]
+            #[unsafe(naked)]
+            #[cfg_attr(feature = "unmangled-names", unsafe(no_mangle))]
+            #[linkage = "weak"]
+            pub unsafe extern "C" fn $name() {
+                core::arch::naked_asm!(
+                    // The argument is the first 16 bytes above the return address. Pass it on in
+                    // `rdi:rsi`, the bits above the first 80 are ignored.
+                    "mov rdi, [rsp + 8]",
+                    "mov rsi, [rsp + 16]",
+                    "jmp {f}",
+                    f = sym from_x87_bits,
+                );
+            }
+
+            #[cfg(target_arch = "x86")]
+            #[cfg_attr(feature = "unmangled-names", unsafe(no_mangle))]
+            #[linkage = "weak"]
+            pub extern "C" fn $name($argname: $crate::float::X87LongDouble) -> $ret {
+                super::$name($argname.to_f80())
+            }
+        }
+
+        intrinsics!($($rest)*);
+    );
+    (
+        #[x87_ret_abi]
+        $(#[$($attr:tt)*])*
+        pub extern $abi:tt fn $name:ident( $($argname:ident:  $ty:ty),* ) -> f80 {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$($attr)*])*
+        pub extern $abi fn $name( $($argname: $ty),* ) -> $crate::support::f80 {
+            $($body)*
+        }
+
+        $(#[$($attr)*])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            extern "C" fn to_x87_bits( $($argname: $ty),* ) -> u128 {
+                super::$name($($argname),*).to_x87_bits()
+            }
+
+            /// # Safety
+            ///
+            /// The caller must expect the `long double` result in `st(0)`.
+            #[cfg(target_arch = "x86_64")]
+            #[unsafe(naked)]
+            #[cfg_attr(feature = "unmangled-names", unsafe(no_mangle))]
+            #[linkage = "weak"]
+            pub unsafe extern "C" fn $name() {
+                core::arch::naked_asm!(
+                    // Arguments are still in place. With the return address this realigns the
+                    // stack and leaves room to spill the result from `rax:rdx`.
+                    "sub rsp, 24",
+                    "call {f}",
+                    "mov [rsp], rax",
+                    "mov [rsp + 8], rdx",
+                    "fld tbyte ptr [rsp]",
+                    "add rsp, 24",
+                    "ret",
+                    f = sym to_x87_bits,
+                );
+            }
+
+            /// The arguments, laid out as they are on the stack.
+            #[cfg(target_arch = "x86")]
+            #[repr(C)]
+            struct Args($($ty),*);
+
+            // Nothing on the stack is more than 4-byte aligned.
+            #[cfg(target_arch = "x86")]
+            const _: () = assert!(core::mem::align_of::<Args>() <= 4);
+
+            #[cfg(target_arch = "x86")]
+            extern "C" fn to_x87_bits(args: &Args, out: &mut u128) {
+                let Args($($argname),*) = *args;
+                *out = super::$name($($argname),*).to_x87_bits();
+            }
+
+            /// # Safety
+            ///
+            /// The caller must expect the `long double` result in `st(0)`.
+            #[cfg(target_arch = "x86")]
+            #[unsafe(naked)]
+            #[cfg_attr(feature = "unmangled-names", unsafe(no_mangle))]
+            #[linkage = "weak"]
+            pub unsafe extern "C" fn $name() {
+                core::arch::naked_asm!(
+                    // Pass a pointer to the arguments and one to an aligned slot for the result,
+                    // keeping the stack aligned at the call.
+                    "lea eax, [esp + 4]",
+                    "sub esp, 44",
+                    "lea ecx, [esp + 16]",
+                    "mov [esp], eax",
+                    "mov [esp + 4], ecx",
+                    "call {f}",
+                    "fld tbyte ptr [esp + 16]",
+                    "add esp, 44",
+                    "ret",
+                    f = sym to_x87_bits,
+                );
+            }
+        }
+
+        intrinsics!($($rest)*);
+    );
+    // `long double _Complex` takes four `long double` arguments. x86-64 returns the real part in
+    // `st(0)` and the imaginary part in `st(1)`, and i386 returns it in memory like a struct.
+    (
+        #[x87_complex_abi]
+        $(#[$($attr:tt)*])*
+        pub extern $abi:tt fn $name:ident(
+            $a:ident: f80, $b:ident: f80, $c:ident: f80, $d:ident: f80
+        ) -> Complex<f80> {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$($attr)*])*
+        pub extern $abi fn $name(
+            $a: $crate::support::f80,
+            $b: $crate::support::f80,
+            $c: $crate::support::f80,
+            $d: $crate::support::f80,
+        ) -> $crate::float::complex::Complex<$crate::support::f80> {
+            $($body)*
+        }
+
+        $(#[$($attr)*])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            extern "C" fn from_x87_bits(args: &[u128; 4], out: &mut [u128; 2]) {
+                let [$a, $b, $c, $d] = args.map($crate::support::f80::from_x87_bits);
+                let res = super::$name($a, $b, $c, $d);
+                *out = [res.re.to_x87_bits(), res.im.to_x87_bits()];
+            }
+
+            /// # Safety
+            ///
+            /// The caller must pass the `long double` arguments on the stack and expect the
+            /// result in `st(0)` and `st(1)`.
+            #[cfg(target_arch = "x86_64")]
+            #[unsafe(naked)]
+            #[cfg_attr(feature = "unmangled-names", unsafe(no_mangle))]
+            #[linkage = "weak"]
+            pub unsafe extern "C" fn $name() {
+                core::arch::naked_asm!(
+                    // The arguments are 16 bytes each above the return address. The result is
+                    // spilled below them, then the imaginary part is loaded first so that the
+                    // real part ends up on top.
+                    "lea rdi, [rsp + 8]",
+                    "sub rsp, 40",
+                    "mov rsi, rsp",
+                    "call {f}",
+                    "fld tbyte ptr [rsp + 16]",
+                    "fld tbyte ptr [rsp]",
+                    "add rsp, 40",
+                    "ret",
+                    f = sym from_x87_bits,
+                );
+            }
+
+            #[cfg(target_arch = "x86")]
+            #[cfg_attr(feature = "unmangled-names", unsafe(no_mangle))]
+            #[linkage = "weak"]
+            pub extern "C" fn $name(
+                $a: $crate::float::X87LongDouble,
+                $b: $crate::float::X87LongDouble,
+                $c: $crate::float::X87LongDouble,
+                $d: $crate::float::X87LongDouble,
+            ) -> $crate::float::complex::Complex<$crate::float::X87LongDouble> {
+                let res = super::$name($a.to_f80(), $b.to_f80(), $c.to_f80(), $d.to_f80());
+                $crate::float::complex::Complex {
+                    re: $crate::float::X87LongDouble::from_f80(res.re),
+                    im: $crate::float::X87LongDouble::from_f80(res.im),
+                }
+            }
+        }
+
+        intrinsics!($($rest)*);
+    );
+
     // C mem* functions are only exposed via `no_mangle` when the "mem" feature is enabled.
     (
         #[mem_builtin]
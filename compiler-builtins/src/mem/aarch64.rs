@@ -0,0 +1,369 @@
+// Copies and fills use `ldp`/`stp` on general purpose registers, which are
+// available on all AArch64 targets including those without NEON (e.g. kernels).
+// This follows the approach of Arm's optimized-routines: lengths up to 64 bytes
+// are handled without loops by loading from both ends of the buffer, so the
+// accesses overlap rather than branching on the exact length. Longer lengths
+// load the ends first, then loop over 64-byte chunks with the destination
+// aligned to 16 bytes and store the ends last.
+//
+// Everything is loaded before it is stored over, so the same routines work for
+// `memmove` in the direction they copy in.
+//
+// Unaligned accesses are fine on normal memory, as for the generic version with
+// `mem_unaligned`.
+
+use core::arch::asm;
+use core::ffi::c_int;
+
+// Routines without an assembly version use the generic implementations.
+#[allow(dead_code)]
+#[path = "impls.rs"]
+mod generic;
+
+pub use generic::{
+    c_string_length, c_string_length_bounded, compare_c_strings, compare_c_strings_bounded,
+    find_byte, find_byte_reverse,
+};
+
+/// Lengths at or above this that zero memory use `dc zva`, if it is allowed.
+const ZVA_THRESHOLD: usize = 256;
+
+/// `dc zva` faults on Device memory, which is all memory while the MMU is off, so it is only used
+/// where an OS has mapped the memory as Normal.
+const ZVA_ALLOWED: bool = cfg!(any(target_os = "linux", target_os = "android"));
+
+#[inline(always)]
+unsafe fn load16(p: *const u8) -> (u64, u64) {
+    let (a, b);
+    asm!(
+        "ldp {a}, {b}, [{p}]",
+        p = in(reg) p,
+        a = out(reg) a,
+        b = lateout(reg) b,
+        options(nostack, preserves_flags, readonly)
+    );
+    (a, b)
+}
+
+#[inline(always)]
+unsafe fn store16(p: *mut u8, (a, b): (u64, u64)) {
+    asm!(
+        "stp {a}, {b}, [{p}]",
+        p = in(reg) p,
+        a = in(reg) a,
+        b = in(reg) b,
+        options(nostack, preserves_flags)
+    );
+}
+
+#[inline(always)]
+unsafe fn load8(p: *const u8) -> u64 {
+    let a;
+    asm!(
+        "ldr {a}, [{p}]",
+        p = in(reg) p,
+        a = lateout(reg) a,
+        options(nostack, preserves_flags, readonly)
+    );
+    a
+}
+
+#[inline(always)]
+unsafe fn store8(p: *mut u8, a: u64) {
+    asm!(
+        "str {a}, [{p}]",
+        p = in(reg) p,
+        a = in(reg) a,
+        options(nostack, preserves_flags)
+    );
+}
+
+#[inline(always)]
+unsafe fn load4(p: *const u8) -> u32 {
+    let a;
+    asm!(
+        "ldr {a:w}, [{p}]",
+        p = in(reg) p,
+        a = lateout(reg) a,
+        options(nostack, preserves_flags, readonly)
+    );
+    a
+}
+
+#[inline(always)]
+unsafe fn store4(p: *mut u8, a: u32) {
+    asm!(
+        "str {a:w}, [{p}]",
+        p = in(reg) p,
+        a = in(reg) a,
+        options(nostack, preserves_flags)
+    );
+}
+
+/// Copy up to 64 bytes. Everything is loaded before storing, so this works for overlapping
+/// buffers in either direction.
+#[inline(always)]
+unsafe fn copy_small(dest: *mut u8, src: *const u8, n: usize) {
+    let src_end = src.wrapping_add(n);
+    let dest_end = dest.wrapping_add(n);
+    if n > 32 {
+        let a = load16(src);
+        let b = load16(src.wrapping_add(16));
+        let c = load16(src_end.wrapping_sub(32));
+        let d = load16(src_end.wrapping_sub(16));
+        store16(dest, a);
+        store16(dest.wrapping_add(16), b);
+        store16(dest_end.wrapping_sub(32), c);
+        store16(dest_end.wrapping_sub(16), d);
+    } else if n >= 16 {
+        let a = load16(src);
+        let b = load16(src_end.wrapping_sub(16));
+        store16(dest, a);
+        store16(dest_end.wrapping_sub(16), b);
+    } else if n >= 8 {
+        let a = load8(src);
+        let b = load8(src_end.wrapping_sub(8));
+        store8(dest, a);
+        store8(dest_end.wrapping_sub(8), b);
+    } else if n >= 4 {
+        let a = load4(src);
+        let b = load4(src_end.wrapping_sub(4));
+        store4(dest, a);
+        store4(dest_end.wrapping_sub(4), b);
+    } else if n > 0 {
+        // The first, middle and last bytes cover all lengths from 1 to 3.
+        let a = *src;
+        let b = *src.wrapping_add(n / 2);
+        let c = *src_end.wrapping_sub(1);
+        *dest = a;
+        *dest.wrapping_add(n / 2) = b;
+        *dest_end.wrapping_sub(1) = c;
+    }
+}
+
+#[inline(always)]
+pub unsafe fn copy_forward(dest: *mut u8, src: *const u8, n: usize) {
+    if n <= 64 {
+        return copy_small(dest, src, n);
+    }
+
+    let head = load16(src);
+    let src_end = src.wrapping_add(n);
+    let tail0 = load16(src_end.wrapping_sub(64));
+    let tail1 = load16(src_end.wrapping_sub(48));
+    let tail2 = load16(src_end.wrapping_sub(32));
+    let tail3 = load16(src_end.wrapping_sub(16));
+
+    // Start the loop at the first 16-byte boundary after `dest`; the head covers the bytes before.
+    let skip = 16 - (dest.addr() & 15);
+    let mut d = dest.wrapping_add(skip);
+    let mut s = src.wrapping_add(skip);
+    let mut rem = n - skip;
+    while rem > 64 {
+        let a = load16(s);
+        let b = load16(s.wrapping_add(16));
+        let c = load16(s.wrapping_add(32));
+        let e = load16(s.wrapping_add(48));
+        store16(d, a);
+        store16(d.wrapping_add(16), b);
+        store16(d.wrapping_add(32), c);
+        store16(d.wrapping_add(48), e);
+        d = d.wrapping_add(64);
+        s = s.wrapping_add(64);
+        rem -= 64;
+    }
+
+    let dest_end = dest.wrapping_add(n);
+    store16(dest, head);
+    store16(dest_end.wrapping_sub(64), tail0);
+    store16(dest_end.wrapping_sub(48), tail1);
+    store16(dest_end.wrapping_sub(32), tail2);
+    store16(dest_end.wrapping_sub(16), tail3);
+}
+
+#[inline(always)]
+pub unsafe fn copy_backward(dest: *mut u8, src: *const u8, n: usize) {
+    if n <= 64 {
+        return copy_small(dest, src, n);
+    }
+
+    let src_end = src.wrapping_add(n);
+    let tail = load16(src_end.wrapping_sub(16));
+    let head0 = load16(src);
+    let head1 = load16(src.wrapping_add(16));
+    let head2 = load16(src.wrapping_add(32));
+    let head3 = load16(src.wrapping_add(48));
+
+    // Start the loop at the last 16-byte boundary before the end of `dest`; the tail covers the
+    // bytes after.
+    let dest_end = dest.wrapping_add(n);
+    let skip = ((dest_end.addr() - 1) & 15) + 1;
+    let mut d = dest_end.wrapping_sub(skip);
+    let mut s = src_end.wrapping_sub(skip);
+    let mut rem = n - skip;
+    while rem > 64 {
+        d = d.wrapping_sub(64);
+        s = s.wrapping_sub(64);
+        let a = load16(s.wrapping_add(48));
+        let b = load16(s.wrapping_add(32));
+        let c = load16(s.wrapping_add(16));
+        let e = load16(s);
+        store16(d.wrapping_add(48), a);
+        store16(d.wrapping_add(32), b);
+        store16(d.wrapping_add(16), c);
+        store16(d, e);
+        rem -= 64;
+    }
+
+    store16(dest_end.wrapping_sub(16), tail);
+    store16(dest, head0);
+    store16(dest.wrapping_add(16), head1);
+    store16(dest.wrapping_add(32), head2);
+    store16(dest.wrapping_add(48), head3);
+}
+
+/// Returns whether `dc zva` is allowed and zeroes 64-byte blocks, which is the case on
+/// practically all implementations.
+#[inline(always)]
+fn zva_64() -> bool {
+    let dczid: u64;
+    // SAFETY: `DCZID_EL0` is readable at all exception levels.
+    unsafe {
+        asm!(
+            "mrs {}, dczid_el0",
+            out(reg) dczid,
+            options(nomem, nostack, preserves_flags)
+        )
+    };
+    // Bit 4 is set if `dc zva` is prohibited, and bits 0..4 are the log2 of the block size in
+    // words.
+    dczid & 0x1f == 4
+}
+
+#[inline(always)]
+pub unsafe fn set_bytes(dest: *mut u8, c: u8, n: usize) {
+    let c8 = c as u64 * 0x0101_0101_0101_0101;
+    let dest_end = dest.wrapping_add(n);
+    if n <= 64 {
+        if n > 32 {
+            store16(dest, (c8, c8));
+            store16(dest.wrapping_add(16), (c8, c8));
+            store16(dest_end.wrapping_sub(32), (c8, c8));
+            store16(dest_end.wrapping_sub(16), (c8, c8));
+        } else if n >= 16 {
+            store16(dest, (c8, c8));
+            store16(dest_end.wrapping_sub(16), (c8, c8));
+        } else if n >= 8 {
+            store8(dest, c8);
+            store8(dest_end.wrapping_sub(8), c8);
+        } else if n >= 4 {
+            store4(dest, c8 as u32);
+            store4(dest_end.wrapping_sub(4), c8 as u32);
+        } else if n > 0 {
+            *dest = c;
+            *dest.wrapping_add(n / 2) = c;
+            *dest_end.wrapping_sub(1) = c;
+        }
+        return;
+    }
+
+    // Fill the first 64 bytes, then continue from a 16-byte boundary (or a 64-byte one to use
+    // `dc zva`).
+    store16(dest, (c8, c8));
+    store16(dest.wrapping_add(16), (c8, c8));
+    store16(dest.wrapping_add(32), (c8, c8));
+    store16(dest.wrapping_add(48), (c8, c8));
+
+    let mut d;
+    if ZVA_ALLOWED && c == 0 && n >= ZVA_THRESHOLD && zva_64() {
+        d = dest.wrapping_add(64 - (dest.addr() & 63));
+        while dest_end.addr() - d.addr() >= 64 {
+            asm!(
+                "dc zva, {}",
+                in(reg) d,
+                options(nostack, preserves_flags)
+            );
+            d = d.wrapping_add(64);
+        }
+    } else {
+        d = dest.wrapping_add(64 - (dest.addr() & 15));
+        while dest_end.addr() - d.addr() > 64 {
+            store16(d, (c8, c8));
+            store16(d.wrapping_add(16), (c8, c8));
+            store16(d.wrapping_add(32), (c8, c8));
+            store16(d.wrapping_add(48), (c8, c8));
+            d = d.wrapping_add(64);
+        }
+    }
+
+    store16(dest_end.wrapping_sub(64), (c8, c8));
+    store16(dest_end.wrapping_sub(48), (c8, c8));
+    store16(dest_end.wrapping_sub(32), (c8, c8));
+    store16(dest_end.wrapping_sub(16), (c8, c8));
+}
+
+/// Given words loaded from the same offset of two buffers that differ, returns the difference of
+/// the first bytes that differ.
+#[inline(always)]
+fn first_difference(a: u64, b: u64) -> c_int {
+    // Put the first byte in memory at the bottom.
+    let (a, b) = (u64::from_le(a), u64::from_le(b));
+    let shift = (a ^ b).trailing_zeros() & !7;
+    c_int::from((a >> shift) as u8) - c_int::from((b >> shift) as u8)
+}
+
+#[inline(always)]
+pub unsafe fn compare_bytes(s1: *const u8, s2: *const u8, n: usize) -> c_int {
+    if n < 16 {
+        if n >= 8 {
+            let (a, b) = (load8(s1), load8(s2));
+            if a != b {
+                return first_difference(a, b);
+            }
+            let (a, b) = (load8(s1.wrapping_add(n - 8)), load8(s2.wrapping_add(n - 8)));
+            if a != b {
+                return first_difference(a, b);
+            }
+        } else if n >= 4 {
+            let (a, b) = (load4(s1) as u64, load4(s2) as u64);
+            if a != b {
+                return first_difference(a, b);
+            }
+            let (a, b) = (load4(s1.wrapping_add(n - 4)), load4(s2.wrapping_add(n - 4)));
+            if a != b {
+                return first_difference(a as u64, b as u64);
+            }
+        } else {
+            let mut i = 0;
+            while i < n {
+                let (a, b) = (*s1.wrapping_add(i), *s2.wrapping_add(i));
+                if a != b {
+                    return c_int::from(a) - c_int::from(b);
+                }
+                i += 1;
+            }
+        }
+        return 0;
+    }
+
+    // Compare 16 bytes at a time, with the last chunk overlapping the previous one unless `n` is
+    // a multiple of 16.
+    let mut i = 0;
+    loop {
+        if i > n - 16 {
+            i = n - 16;
+        }
+        let (a0, a1) = load16(s1.wrapping_add(i));
+        let (b0, b1) = load16(s2.wrapping_add(i));
+        if a0 != b0 {
+            return first_difference(a0, b0);
+        }
+        if a1 != b1 {
+            return first_difference(a1, b1);
+        }
+        if i == n - 16 {
+            return 0;
+        }
+        i += 16;
+    }
+}
@@ -412,3 +412,143 @@ pub unsafe fn c_string_length(mut s: *const core::ffi::c_char) -> usize {
     }
     n
 }
+
+/// Returns a word with every byte set to `b`.
+#[inline(always)]
+fn broadcast_word(b: u8) -> usize {
+    usize::MAX / 0xff * b as usize
+}
+
+/// Returns whether any byte of `x` is zero.
+#[inline(always)]
+fn has_zero_byte(x: usize) -> bool {
+    const LO: usize = usize::MAX / 0xff;
+    const HI: usize = LO << 7;
+    x.wrapping_sub(LO) & !x & HI != 0
+}
+
+/// Returns a pointer to the first `c` in the `n` bytes at `s`, or null.
+#[inline(always)]
+pub unsafe fn find_byte(s: *const u8, c: u8, n: usize) -> *const u8 {
+    let mut i = 0;
+    if n >= WORD_COPY_THRESHOLD {
+        // Check bytes until aligned, then whole words until one contains `c`.
+        let misalignment = (s as usize).wrapping_neg() & WORD_MASK;
+        while i < misalignment {
+            if *s.wrapping_add(i) == c {
+                return s.wrapping_add(i);
+            }
+            i += 1;
+        }
+
+        let pattern = broadcast_word(c);
+        while n - i >= WORD_SIZE {
+            if has_zero_byte(*(s.wrapping_add(i) as *const usize) ^ pattern) {
+                break;
+            }
+            i += WORD_SIZE;
+        }
+    }
+
+    while i < n {
+        if *s.wrapping_add(i) == c {
+            return s.wrapping_add(i);
+        }
+        i += 1;
+    }
+    core::ptr::null()
+}
+
+/// Returns a pointer to the last `c` in the `n` bytes at `s`, or null.
+#[inline(always)]
+pub unsafe fn find_byte_reverse(s: *const u8, c: u8, n: usize) -> *const u8 {
+    let mut i = n;
+    if n >= WORD_COPY_THRESHOLD {
+        // Check bytes until the end is aligned, then whole words until one contains `c`.
+        let misalignment = s.wrapping_add(n) as usize & WORD_MASK;
+        while i > n - misalignment {
+            i -= 1;
+            if *s.wrapping_add(i) == c {
+                return s.wrapping_add(i);
+            }
+        }
+
+        let pattern = broadcast_word(c);
+        while i >= WORD_SIZE {
+            let word = *(s.wrapping_add(i - WORD_SIZE) as *const usize);
+            if has_zero_byte(word ^ pattern) {
+                break;
+            }
+            i -= WORD_SIZE;
+        }
+    }
+
+    while i > 0 {
+        i -= 1;
+        if *s.wrapping_add(i) == c {
+            return s.wrapping_add(i);
+        }
+    }
+    core::ptr::null()
+}
+
+#[inline(always)]
+pub unsafe fn c_string_length_bounded(s: *const core::ffi::c_char, n: usize) -> usize {
+    // `find_byte` stops at the aligned word with the NUL, so it doesn't read into another page.
+    let nul = find_byte(s.cast(), 0, n);
+    if nul.is_null() {
+        n
+    } else {
+        nul as usize - s as usize
+    }
+}
+
+#[inline(always)]
+pub unsafe fn compare_c_strings(
+    s1: *const core::ffi::c_char,
+    s2: *const core::ffi::c_char,
+) -> c_int {
+    compare_c_strings_bounded(s1, s2, usize::MAX)
+}
+
+#[inline(always)]
+pub unsafe fn compare_c_strings_bounded(
+    s1: *const core::ffi::c_char,
+    s2: *const core::ffi::c_char,
+    n: usize,
+) -> c_int {
+    // The characters are compared as `unsigned char`.
+    let (s1, s2) = (s1.cast::<u8>(), s2.cast::<u8>());
+    let mut i = 0;
+    if (s1 as usize ^ s2 as usize) & WORD_MASK == 0 {
+        // Both strings are at the same offset in a word. Check bytes until aligned, then whole
+        // words until they differ or contain a NUL. An aligned word never crosses into another
+        // page, so reading past the NUL within it can't fault.
+        let misalignment = (s1 as usize).wrapping_neg() & WORD_MASK;
+        while i < misalignment.min(n) {
+            let (a, b) = (*s1.wrapping_add(i), *s2.wrapping_add(i));
+            if a != b || a == 0 {
+                return c_int::from(a) - c_int::from(b);
+            }
+            i += 1;
+        }
+
+        while n - i >= WORD_SIZE {
+            let a = *(s1.wrapping_add(i) as *const usize);
+            let b = *(s2.wrapping_add(i) as *const usize);
+            if a != b || has_zero_byte(a) {
+                break;
+            }
+            i += WORD_SIZE;
+        }
+    }
+
+    while i < n {
+        let (a, b) = (*s1.wrapping_add(i), *s2.wrapping_add(i));
+        if a != b || a == 0 {
+            return c_int::from(a) - c_int::from(b);
+        }
+        i += 1;
+    }
+    0
+}
@@ -7,8 +7,127 @@
 
 // memcpy/memmove/memset have optimized implementations on some architectures
 #[cfg_attr(all(feature = "arch", target_arch = "x86_64"), path = "x86_64.rs")]
+#[cfg_attr(all(feature = "arch", target_arch = "aarch64"), path = "aarch64.rs")]
 mod impls;
 
+/// The x86-64 implementations that are selected between at runtime, for benchmarking.
+#[cfg(all(
+    feature = "unstable-public-internals",
+    feature = "arch",
+    target_arch = "x86_64",
+    target_feature = "sse2"
+))]
+pub mod x86_64 {
+    pub use super::impls::{
+        copy_backward_avx2, copy_backward_rep_movsq, copy_forward_avx2, copy_forward_ermsb_avx2,
+        copy_forward_rep_movsb, copy_forward_rep_movsq, set_bytes_avx2, set_bytes_ermsb_avx2,
+        set_bytes_rep_stosb, set_bytes_rep_stosq,
+    };
+}
+
+#[cfg_attr(not(target_has_atomic_load_store = "8"), allow(unused_imports))]
+use core::sync::atomic::Ordering;
+
+/// An element of the `__llvm_*_element_unordered_atomic_*` intrinsics, which must be loaded and
+/// stored as a whole.
+///
+/// LLVM only requires unordered accesses, the weakest atomic ordering, which Rust doesn't expose.
+/// Relaxed is the next strongest and compiles to the same plain loads and stores.
+trait AtomicElement: Copy {
+    /// # Safety
+    ///
+    /// `p` must be aligned and valid for reads.
+    unsafe fn load(p: *const Self) -> Self;
+
+    /// # Safety
+    ///
+    /// `p` must be aligned and valid for writes.
+    unsafe fn store(p: *mut Self, val: Self);
+
+    /// Returns an element with every byte set to `c`.
+    fn splat(c: u8) -> Self;
+}
+
+macro_rules! impl_atomic_element {
+    ($($ty:ty, $atomic:ident, $width:literal;)*) => {$(
+        #[cfg(target_has_atomic_load_store = $width)]
+        impl AtomicElement for $ty {
+            unsafe fn load(p: *const Self) -> Self {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(p.cast_mut()) };
+                a.load(Ordering::Relaxed)
+            }
+
+            unsafe fn store(p: *mut Self, val: Self) {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(p) };
+                a.store(val, Ordering::Relaxed);
+            }
+
+            fn splat(c: u8) -> Self {
+                Self::from_ne_bytes([c; core::mem::size_of::<Self>()])
+            }
+        }
+    )*};
+}
+
+impl_atomic_element! {
+    u8, AtomicU8, "8";
+    u16, AtomicU16, "16";
+    u32, AtomicU32, "32";
+    u64, AtomicU64, "64";
+    u128, AtomicU128, "128";
+}
+
+/// Returns the number of `T` elements in `bytes`, which must be a multiple of their size.
+#[cfg_attr(not(target_has_atomic_load_store = "8"), allow(dead_code))]
+fn element_count<T>(bytes: usize) -> usize {
+    debug_assert!(bytes.is_multiple_of(core::mem::size_of::<T>()));
+    bytes / core::mem::size_of::<T>()
+}
+
+#[cfg_attr(not(target_has_atomic_load_store = "8"), allow(dead_code))]
+unsafe fn memcpy_element_unordered_atomic<T: AtomicElement>(
+    dest: *mut T,
+    src: *const T,
+    bytes: usize,
+) {
+    let n = element_count::<T>(bytes);
+    let mut i = 0;
+    while i < n {
+        T::store(dest.wrapping_add(i), T::load(src.wrapping_add(i)));
+        i += 1;
+    }
+}
+
+#[cfg_attr(not(target_has_atomic_load_store = "8"), allow(dead_code))]
+unsafe fn memmove_element_unordered_atomic<T: AtomicElement>(
+    dest: *mut T,
+    src: *const T,
+    bytes: usize,
+) {
+    let n = element_count::<T>(bytes);
+    if (src as usize) < (dest as usize) {
+        // Copy from the end so overlapping elements are read before they are overwritten.
+        let mut i = n;
+        while i != 0 {
+            i -= 1;
+            T::store(dest.wrapping_add(i), T::load(src.wrapping_add(i)));
+        }
+    } else {
+        memcpy_element_unordered_atomic(dest, src, bytes);
+    }
+}
+
+#[cfg_attr(not(target_has_atomic_load_store = "8"), allow(dead_code))]
+unsafe fn memset_element_unordered_atomic<T: AtomicElement>(s: *mut T, c: u8, bytes: usize) {
+    let n = element_count::<T>(bytes);
+    let x = T::splat(c);
+    let mut i = 0;
+    while i < n {
+        T::store(s.wrapping_add(i), x);
+        i += 1;
+    }
+}
+
 intrinsics! {
     #[mem_builtin]
     #[allow(suspicious_runtime_symbol_definitions)]
@@ -54,4 +173,143 @@ intrinsics! {
     pub unsafe extern "C" fn strlen(s: *const core::ffi::c_char) -> usize {
         impls::c_string_length(s)
     }
+
+    #[mem_builtin]
+    pub unsafe extern "C" fn memchr(s: *const u8, c: core::ffi::c_int, n: usize) -> *mut u8 {
+        impls::find_byte(s, c as u8, n).cast_mut()
+    }
+
+    #[mem_builtin]
+    pub unsafe extern "C" fn memrchr(s: *const u8, c: core::ffi::c_int, n: usize) -> *mut u8 {
+        impls::find_byte_reverse(s, c as u8, n).cast_mut()
+    }
+
+    #[mem_builtin]
+    pub unsafe extern "C" fn strnlen(s: *const core::ffi::c_char, n: usize) -> usize {
+        impls::c_string_length_bounded(s, n)
+    }
+
+    #[mem_builtin]
+    pub unsafe extern "C" fn strcmp(
+        s1: *const core::ffi::c_char, s2: *const core::ffi::c_char
+    ) -> core::ffi::c_int {
+        impls::compare_c_strings(s1, s2)
+    }
+
+    #[mem_builtin]
+    pub unsafe extern "C" fn strncmp(
+        s1: *const core::ffi::c_char, s2: *const core::ffi::c_char, n: usize
+    ) -> core::ffi::c_int {
+        impls::compare_c_strings_bounded(s1, s2, n)
+    }
+}
+
+// Element-wise atomic versions of `memcpy`, `memmove` and `memset`, emitted by LLVM for the
+// `llvm.*.element.unordered.atomic` intrinsics. `bytes` is a multiple of the element size and the
+// pointers are aligned to it.
+intrinsics! {
+    #[cfg(target_has_atomic_load_store = "8")]
+    pub unsafe extern "C" fn __llvm_memcpy_element_unordered_atomic_1(
+        dest: *mut u8, src: *const u8, bytes: usize
+    ) {
+        memcpy_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "16")]
+    pub unsafe extern "C" fn __llvm_memcpy_element_unordered_atomic_2(
+        dest: *mut u16, src: *const u16, bytes: usize
+    ) {
+        memcpy_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "32")]
+    pub unsafe extern "C" fn __llvm_memcpy_element_unordered_atomic_4(
+        dest: *mut u32, src: *const u32, bytes: usize
+    ) {
+        memcpy_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "64")]
+    pub unsafe extern "C" fn __llvm_memcpy_element_unordered_atomic_8(
+        dest: *mut u64, src: *const u64, bytes: usize
+    ) {
+        memcpy_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "128")]
+    pub unsafe extern "C" fn __llvm_memcpy_element_unordered_atomic_16(
+        dest: *mut u128, src: *const u128, bytes: usize
+    ) {
+        memcpy_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "8")]
+    pub unsafe extern "C" fn __llvm_memmove_element_unordered_atomic_1(
+        dest: *mut u8, src: *const u8, bytes: usize
+    ) {
+        memmove_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "16")]
+    pub unsafe extern "C" fn __llvm_memmove_element_unordered_atomic_2(
+        dest: *mut u16, src: *const u16, bytes: usize
+    ) {
+        memmove_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "32")]
+    pub unsafe extern "C" fn __llvm_memmove_element_unordered_atomic_4(
+        dest: *mut u32, src: *const u32, bytes: usize
+    ) {
+        memmove_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "64")]
+    pub unsafe extern "C" fn __llvm_memmove_element_unordered_atomic_8(
+        dest: *mut u64, src: *const u64, bytes: usize
+    ) {
+        memmove_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "128")]
+    pub unsafe extern "C" fn __llvm_memmove_element_unordered_atomic_16(
+        dest: *mut u128, src: *const u128, bytes: usize
+    ) {
+        memmove_element_unordered_atomic(dest, src, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "8")]
+    pub unsafe extern "C" fn __llvm_memset_element_unordered_atomic_1(
+        s: *mut u8, c: u8, bytes: usize
+    ) {
+        memset_element_unordered_atomic(s, c, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "16")]
+    pub unsafe extern "C" fn __llvm_memset_element_unordered_atomic_2(
+        s: *mut u16, c: u8, bytes: usize
+    ) {
+        memset_element_unordered_atomic(s, c, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "32")]
+    pub unsafe extern "C" fn __llvm_memset_element_unordered_atomic_4(
+        s: *mut u32, c: u8, bytes: usize
+    ) {
+        memset_element_unordered_atomic(s, c, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "64")]
+    pub unsafe extern "C" fn __llvm_memset_element_unordered_atomic_8(
+        s: *mut u64, c: u8, bytes: usize
+    ) {
+        memset_element_unordered_atomic(s, c, bytes);
+    }
+
+    #[cfg(target_has_atomic_load_store = "128")]
+    pub unsafe extern "C" fn __llvm_memset_element_unordered_atomic_16(
+        s: *mut u128, c: u8, bytes: usize
+    ) {
+        memset_element_unordered_atomic(s, c, bytes);
+    }
 }
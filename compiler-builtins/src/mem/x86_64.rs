@@ -12,16 +12,69 @@
 //  - Fast Zero-Length MOVSB (On no current hardware)
 //  - Fast Short STOSB (On no current hardware)
 //
-// To simplify things, we switch to using the byte-based variants if the "ermsb"
-// feature is present at compile-time. We don't bother detecting other features.
-// Note that ERMSB does not enhance the backwards (DF=1) "rep movsb".
+// Binaries are usually built for a baseline CPU, so when SSE2 is available we
+// pick an implementation once at runtime using the feature detection from
+// `libm`:
+//  - With FSRM, "rep movsb" is used for copies of any length.
+//  - With ERMSB, "rep movsb" and "rep stosb" are used for long lengths. Shorter
+//    lengths use AVX2 loops if available, since the startup cost of the string
+//    instructions dominates there.
+//  - Otherwise, AVX2 loops or the "rep movsq" and "rep stosq" versions.
+// Note that ERMSB does not enhance the backwards (DF=1) "rep movsb", so only
+// AVX2 is checked for `copy_backward`.
+//
+// Without SSE2 (e.g. in kernels), the byte-based variants are used if the
+// "ermsb" feature is present at compile-time.
 
 use core::arch::asm;
 use core::{intrinsics, mem};
 
+#[cfg(target_feature = "sse2")]
+use crate::math::libm_math::arch::x86::detect::{cpu_flags, get_cpu_features};
+#[cfg(target_feature = "sse2")]
+use crate::support::feature_detect::select_once;
+
+/// With ERMSB but not FSRM, lengths below this use the AVX2 loops rather than the string
+/// instructions.
+#[cfg(target_feature = "sse2")]
+const ERMSB_THRESHOLD: usize = 2048;
+
+#[inline(always)]
+#[cfg(target_feature = "sse2")]
+pub unsafe fn copy_forward(dest: *mut u8, src: *const u8, count: usize) {
+    select_once! {
+        sig: fn(dest: *mut u8, src: *const u8, count: usize) -> (),
+        init: || -> Func {
+            let features = get_cpu_features();
+            let avx2 = features.contains(cpu_flags::AVX2);
+            if features.contains(cpu_flags::FSRM) {
+                copy_forward_rep_movsb
+            } else if features.contains(cpu_flags::ERMSB) {
+                if avx2 { copy_forward_ermsb_avx2 } else { copy_forward_rep_movsb }
+            } else if avx2 {
+                copy_forward_avx2
+            } else {
+                copy_forward_rep_movsq
+            }
+        },
+        // SAFETY: the caller upholds the preconditions, and the selected function only uses
+        // features that were detected.
+        call: |f: Func| unsafe { f(dest, src, count) },
+    }
+}
+
 #[inline(always)]
-#[cfg(target_feature = "ermsb")]
+#[cfg(not(target_feature = "sse2"))]
 pub unsafe fn copy_forward(dest: *mut u8, src: *const u8, count: usize) {
+    if cfg!(target_feature = "ermsb") {
+        copy_forward_rep_movsb(dest, src, count)
+    } else {
+        copy_forward_rep_movsq(dest, src, count)
+    }
+}
+
+#[inline(always)]
+pub unsafe fn copy_forward_rep_movsb(dest: *mut u8, src: *const u8, count: usize) {
     asm!(
         "rep movsb [rdi], [rsi]",
         inout("rcx") count => _,
@@ -32,8 +85,7 @@ pub unsafe fn copy_forward(dest: *mut u8, src: *const u8, count: usize) {
 }
 
 #[inline(always)]
-#[cfg(not(target_feature = "ermsb"))]
-pub unsafe fn copy_forward(mut dest: *mut u8, mut src: *const u8, count: usize) {
+pub unsafe fn copy_forward_rep_movsq(mut dest: *mut u8, mut src: *const u8, count: usize) {
     let (pre_byte_count, qword_count, byte_count) = rep_param(dest, count);
     // Separating the blocks gives the compiler more freedom to reorder instructions.
     asm!(
@@ -59,8 +111,75 @@ pub unsafe fn copy_forward(mut dest: *mut u8, mut src: *const u8, count: usize)
     );
 }
 
+// The AVX2 loops load the vector at the far end before starting so that it can
+// be stored last, covering the remainder that isn't a multiple of 32 bytes.
+// Loading it first keeps this correct for overlapping `memmove`s. LLVM inserts
+// `vzeroupper` on return since the assembly uses ymm registers.
+
+#[cfg(target_feature = "sse2")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn copy_forward_avx2(dest: *mut u8, src: *const u8, count: usize) {
+    if count < 32 {
+        return copy_forward_rep_movsb(dest, src, count);
+    }
+    asm!(
+        "vmovdqu {tail}, ymmword ptr [{src} + {count} - 32]",
+        "lea {end}, [{dest} + {count} - 32]",
+        "2:",
+        "vmovdqu {v}, ymmword ptr [{src}]",
+        "vmovdqu ymmword ptr [{dest}], {v}",
+        "add {src}, 32",
+        "add {dest}, 32",
+        "sub {count}, 32",
+        "cmp {count}, 32",
+        "ja 2b",
+        "vmovdqu ymmword ptr [{end}], {tail}",
+        dest = inout(reg) dest => _,
+        src = inout(reg) src => _,
+        count = inout(reg) count => _,
+        end = out(reg) _,
+        v = out(ymm_reg) _,
+        tail = out(ymm_reg) _,
+        options(nostack)
+    );
+}
+
+#[cfg(target_feature = "sse2")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn copy_forward_ermsb_avx2(dest: *mut u8, src: *const u8, count: usize) {
+    if count < ERMSB_THRESHOLD {
+        copy_forward_avx2(dest, src, count)
+    } else {
+        copy_forward_rep_movsb(dest, src, count)
+    }
+}
+
 #[inline(always)]
+#[cfg(target_feature = "sse2")]
 pub unsafe fn copy_backward(dest: *mut u8, src: *const u8, count: usize) {
+    select_once! {
+        sig: fn(dest: *mut u8, src: *const u8, count: usize) -> (),
+        init: || -> Func {
+            if get_cpu_features().contains(cpu_flags::AVX2) {
+                copy_backward_avx2
+            } else {
+                copy_backward_rep_movsq
+            }
+        },
+        // SAFETY: the caller upholds the preconditions, and the selected function only uses
+        // features that were detected.
+        call: |f: Func| unsafe { f(dest, src, count) },
+    }
+}
+
+#[inline(always)]
+#[cfg(not(target_feature = "sse2"))]
+pub unsafe fn copy_backward(dest: *mut u8, src: *const u8, count: usize) {
+    copy_backward_rep_movsq(dest, src, count)
+}
+
+#[inline(always)]
+pub unsafe fn copy_backward_rep_movsq(dest: *mut u8, src: *const u8, count: usize) {
     let (pre_byte_count, qword_count, byte_count) = rep_param(dest, count);
     // We can't separate this block due to std/cld
     asm!(
@@ -84,9 +203,64 @@ pub unsafe fn copy_backward(dest: *mut u8, src: *const u8, count: usize) {
     );
 }
 
+#[cfg(target_feature = "sse2")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn copy_backward_avx2(dest: *mut u8, src: *const u8, count: usize) {
+    if count < 32 {
+        return copy_backward_rep_movsq(dest, src, count);
+    }
+    asm!(
+        "vmovdqu {head}, ymmword ptr [{src}]",
+        "2:",
+        "sub {count}, 32",
+        "vmovdqu {v}, ymmword ptr [{src} + {count}]",
+        "vmovdqu ymmword ptr [{dest} + {count}], {v}",
+        "cmp {count}, 32",
+        "ja 2b",
+        "vmovdqu ymmword ptr [{dest}], {head}",
+        dest = in(reg) dest,
+        src = in(reg) src,
+        count = inout(reg) count => _,
+        v = out(ymm_reg) _,
+        head = out(ymm_reg) _,
+        options(nostack)
+    );
+}
+
+#[inline(always)]
+#[cfg(target_feature = "sse2")]
+pub unsafe fn set_bytes(dest: *mut u8, c: u8, count: usize) {
+    select_once! {
+        sig: fn(dest: *mut u8, c: u8, count: usize) -> (),
+        init: || -> Func {
+            let features = get_cpu_features();
+            let avx2 = features.contains(cpu_flags::AVX2);
+            if features.contains(cpu_flags::ERMSB) {
+                if avx2 { set_bytes_ermsb_avx2 } else { set_bytes_rep_stosb }
+            } else if avx2 {
+                set_bytes_avx2
+            } else {
+                set_bytes_rep_stosq
+            }
+        },
+        // SAFETY: the caller upholds the preconditions, and the selected function only uses
+        // features that were detected.
+        call: |f: Func| unsafe { f(dest, c, count) },
+    }
+}
+
 #[inline(always)]
-#[cfg(target_feature = "ermsb")]
+#[cfg(not(target_feature = "sse2"))]
 pub unsafe fn set_bytes(dest: *mut u8, c: u8, count: usize) {
+    if cfg!(target_feature = "ermsb") {
+        set_bytes_rep_stosb(dest, c, count)
+    } else {
+        set_bytes_rep_stosq(dest, c, count)
+    }
+}
+
+#[inline(always)]
+pub unsafe fn set_bytes_rep_stosb(dest: *mut u8, c: u8, count: usize) {
     asm!(
         "rep stosb [rdi], al",
         inout("rcx") count => _,
@@ -97,8 +271,7 @@ pub unsafe fn set_bytes(dest: *mut u8, c: u8, count: usize) {
 }
 
 #[inline(always)]
-#[cfg(not(target_feature = "ermsb"))]
-pub unsafe fn set_bytes(mut dest: *mut u8, c: u8, count: usize) {
+pub unsafe fn set_bytes_rep_stosq(mut dest: *mut u8, c: u8, count: usize) {
     let c = c as u64 * 0x0101_0101_0101_0101;
     let (pre_byte_count, qword_count, byte_count) = rep_param(dest, count);
     // Separating the blocks gives the compiler more freedom to reorder instructions.
@@ -125,6 +298,40 @@ pub unsafe fn set_bytes(mut dest: *mut u8, c: u8, count: usize) {
     );
 }
 
+#[cfg(target_feature = "sse2")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn set_bytes_avx2(dest: *mut u8, c: u8, count: usize) {
+    if count < 32 {
+        return set_bytes_rep_stosb(dest, c, count);
+    }
+    asm!(
+        "vmovd {v:x}, {c:e}",
+        "vpbroadcastb {v}, {v:x}",
+        "vmovdqu ymmword ptr [{dest} + {count} - 32], {v}",
+        "2:",
+        "vmovdqu ymmword ptr [{dest}], {v}",
+        "add {dest}, 32",
+        "sub {count}, 32",
+        "cmp {count}, 32",
+        "ja 2b",
+        c = in(reg) c as u32,
+        dest = inout(reg) dest => _,
+        count = inout(reg) count => _,
+        v = out(ymm_reg) _,
+        options(nostack)
+    );
+}
+
+#[cfg(target_feature = "sse2")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn set_bytes_ermsb_avx2(dest: *mut u8, c: u8, count: usize) {
+    if count < ERMSB_THRESHOLD {
+        set_bytes_avx2(dest, c, count)
+    } else {
+        set_bytes_rep_stosb(dest, c, count)
+    }
+}
+
 #[inline(always)]
 pub unsafe fn compare_bytes(a: *const u8, b: *const u8, n: usize) -> i32 {
     #[inline(always)]
@@ -298,6 +505,186 @@ pub unsafe fn c_string_length(mut s: *const core::ffi::c_char) -> usize {
     }
 }
 
+// The routines below use the same approach as `c_string_length`: aligned loads
+// never cross a page boundary, so reading a whole block around the bytes of
+// interest is fine. `compare_c_strings` can't align both strings, so it checks
+// for a page boundary before each unaligned load instead. Targets without SSE2
+// use the generic versions.
+
+#[allow(dead_code)]
+#[path = "impls.rs"]
+mod generic;
+
+#[cfg(not(target_feature = "sse2"))]
+pub use generic::{
+    c_string_length_bounded, compare_c_strings, compare_c_strings_bounded, find_byte,
+    find_byte_reverse,
+};
+
+/// Loads the 16-byte block at `p`, which must be 16-byte aligned but may extend past the object
+/// being read.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+unsafe fn load_block(p: *const u8) -> core::arch::x86_64::__m128i {
+    let r;
+    asm!(
+        "movdqa {dest}, [{addr:r}]",
+        addr = in(reg) p,
+        dest = out(xmm_reg) r,
+        options(nostack, preserves_flags, readonly),
+    );
+    r
+}
+
+/// Returns a mask with bit `i` set if byte `i` of the 16-byte block at `p` is `c`.
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+unsafe fn block_matches(p: *const u8, c: u8) -> u32 {
+    use core::arch::x86_64::{_mm_cmpeq_epi8, _mm_movemask_epi8, _mm_set1_epi8};
+
+    _mm_movemask_epi8(_mm_cmpeq_epi8(load_block(p), _mm_set1_epi8(c as i8))) as u32
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+pub unsafe fn find_byte(s: *const u8, c: u8, n: usize) -> *const u8 {
+    if n == 0 {
+        return core::ptr::null();
+    }
+
+    // Start at the block containing `s`, ignoring the bytes before it. `i` is the offset from `s`
+    // of the current block.
+    let align = s as usize & 15;
+    let mut i = align.wrapping_neg();
+    let mut mask = block_matches(s.wrapping_sub(align), c) >> align << align;
+    loop {
+        if mask != 0 {
+            let found = i.wrapping_add(mask.trailing_zeros() as usize);
+            return if found < n {
+                s.wrapping_add(found)
+            } else {
+                core::ptr::null()
+            };
+        }
+        i = i.wrapping_add(16);
+        if i >= n {
+            return core::ptr::null();
+        }
+        mask = block_matches(s.wrapping_add(i), c);
+    }
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+pub unsafe fn find_byte_reverse(s: *const u8, c: u8, n: usize) -> *const u8 {
+    if n == 0 {
+        return core::ptr::null();
+    }
+
+    // Start at the block containing the last byte, ignoring the bytes after it. `i` is the offset
+    // from `s` of the current block, which may be before `s` for the last one.
+    let last = s.wrapping_add(n - 1);
+    let align = last as usize & 15;
+    let mut i = (n - 1).wrapping_sub(align);
+    let mut mask = block_matches(last.wrapping_sub(align), c) & (u32::MAX >> (31 - align));
+    loop {
+        if mask != 0 {
+            let found = i.wrapping_add(31 - mask.leading_zeros() as usize);
+            // Matches before `s` show up as large offsets.
+            return if found < n {
+                s.wrapping_add(found)
+            } else {
+                core::ptr::null()
+            };
+        }
+        if i == 0 || i > n {
+            return core::ptr::null();
+        }
+        i = i.wrapping_sub(16);
+        mask = block_matches(s.wrapping_add(i), c);
+    }
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+pub unsafe fn c_string_length_bounded(s: *const core::ffi::c_char, n: usize) -> usize {
+    let nul = find_byte(s.cast(), 0, n);
+    if nul.is_null() {
+        n
+    } else {
+        nul as usize - s as usize
+    }
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+pub unsafe fn compare_c_strings(
+    s1: *const core::ffi::c_char,
+    s2: *const core::ffi::c_char,
+) -> core::ffi::c_int {
+    compare_c_strings_bounded(s1, s2, usize::MAX)
+}
+
+#[cfg(target_feature = "sse2")]
+#[inline(always)]
+pub unsafe fn compare_c_strings_bounded(
+    s1: *const core::ffi::c_char,
+    s2: *const core::ffi::c_char,
+    n: usize,
+) -> core::ffi::c_int {
+    use core::arch::x86_64::{__m128i, _mm_cmpeq_epi8, _mm_movemask_epi8, _mm_setzero_si128};
+
+    /// Whether a 16-byte load at `p` would cross into the next page.
+    fn crosses_page(p: *const core::ffi::c_char) -> bool {
+        p as usize & 0xfff > 0x1000 - 16
+    }
+
+    #[inline(always)]
+    unsafe fn load_unaligned(p: *const core::ffi::c_char) -> __m128i {
+        let r;
+        asm!(
+            "movdqu {dest}, [{addr:r}]",
+            addr = in(reg) p,
+            dest = out(xmm_reg) r,
+            options(nostack, preserves_flags, readonly),
+        );
+        r
+    }
+
+    let mut i = 0;
+    while i < n {
+        let p1 = s1.wrapping_add(i);
+        let p2 = s2.wrapping_add(i);
+        if crosses_page(p1) || crosses_page(p2) {
+            // Step over the page boundary a byte at a time.
+            let a = *p1 as u8;
+            let b = *p2 as u8;
+            if a != b || a == 0 {
+                return core::ffi::c_int::from(a) - core::ffi::c_int::from(b);
+            }
+            i += 1;
+            continue;
+        }
+
+        let a = load_unaligned(p1);
+        let b = load_unaligned(p2);
+        let equal = _mm_movemask_epi8(_mm_cmpeq_epi8(a, b)) as u32;
+        let nul = _mm_movemask_epi8(_mm_cmpeq_epi8(a, _mm_setzero_si128())) as u32;
+        let stop = (!equal & 0xffff) | nul;
+        if stop != 0 {
+            let j = i + stop.trailing_zeros() as usize;
+            if j >= n {
+                return 0;
+            }
+            let a = *s1.wrapping_add(j) as u8;
+            let b = *s2.wrapping_add(j) as u8;
+            return core::ffi::c_int::from(a) - core::ffi::c_int::from(b);
+        }
+        i += 16;
+    }
+    0
+}
+
 /// Determine optimal parameters for a `rep` instruction.
 fn rep_param(dest: *mut u8, mut count: usize) -> (usize, usize, usize) {
     // Unaligned writes are still slow on modern processors, so align the destination address.
@@ -0,0 +1,242 @@
+//! The helper functions from the MSP430 EABI. LLVM calls these rather than the usual libgcc names
+//! for multiplication, division and 32-bit shifts, and GCC also calls the shifts by a constant to
+//! keep code small.
+//!
+//! Most are wrappers around the generic intrinsics. The ones with the same signature are exported
+//! from where they are defined, through `#[msp430_alias]`.
+//!
+//! Everything here assumes there is no hardware multiplier, so multiplication can't use `*`.
+
+#[cfg(target_arch = "msp430")]
+use core::arch::global_asm;
+
+use crate::int::sdiv::{__divsi3, __modsi3};
+use crate::int::shift::{__ashlsi3, __ashrsi3, __lshrsi3};
+use crate::int::udiv::{__udivsi3, __umodsi3};
+use crate::support::Int;
+
+// Interfaces used by naked trampolines.
+// SAFETY: these are defined in compiler-builtins
+#[cfg(target_arch = "msp430")]
+unsafe extern "C" {
+    fn __udivdi3(a: u64, b: u64) -> u64;
+    fn __umoddi3(a: u64, b: u64) -> u64;
+    fn __divdi3(a: i64, b: i64) -> i64;
+    fn __moddi3(a: i64, b: i64) -> i64;
+}
+
+/// Multiplication by shift-and-add, since `*` would call back into `__mspabi_mpyi` and
+/// `__mspabi_mpyl`.
+fn mul<T: Int>(mut a: T, mut b: T) -> T {
+    let mut product = T::ZERO;
+    while a != T::ZERO {
+        if a & T::ONE != T::ZERO {
+            product = product.wrapping_add(b);
+        }
+        a >>= 1u32;
+        b <<= 1u32;
+    }
+    product
+}
+
+intrinsics! {
+    pub extern "C" fn __mspabi_mpyi(a: u16, b: u16) -> u16 {
+        mul(a, b)
+    }
+
+    pub extern "C" fn __mspabi_mpyl(a: u32, b: u32) -> u32 {
+        mul(a, b)
+    }
+
+    // The 16-bit results of the 32-bit operations are exact, including the wrapping of
+    // `i16::MIN / -1`.
+
+    pub extern "C" fn __mspabi_divi(a: i16, b: i16) -> i16 {
+        __divsi3(a.into(), b.into()) as i16
+    }
+
+    pub extern "C" fn __mspabi_divu(a: u16, b: u16) -> u16 {
+        __udivsi3(a.into(), b.into()) as u16
+    }
+
+    pub extern "C" fn __mspabi_remi(a: i16, b: i16) -> i16 {
+        __modsi3(a.into(), b.into()) as i16
+    }
+
+    pub extern "C" fn __mspabi_remu(a: u16, b: u16) -> u16 {
+        __umodsi3(a.into(), b.into()) as u16
+    }
+
+    // `__modsi3` is defined by a macro that can't take the alias.
+    pub extern "C" fn __mspabi_remli(a: i32, b: i32) -> i32 {
+        __modsi3(a, b)
+    }
+
+    // LLVM shifts 16-bit values inline, so these don't call back into themselves.
+
+    pub extern "C" fn __mspabi_slli(a: u16, b: u16) -> u16 {
+        a.wrapping_shl(b.into())
+    }
+
+    pub extern "C" fn __mspabi_srai(a: i16, b: u16) -> i16 {
+        a.wrapping_shr(b.into())
+    }
+
+    pub extern "C" fn __mspabi_srli(a: u16, b: u16) -> u16 {
+        a.wrapping_shr(b.into())
+    }
+
+    // The 32-bit shifts take a 16-bit amount, unlike `__ashlsi3` and friends.
+
+    pub extern "C" fn __mspabi_slll(a: u32, b: u16) -> u32 {
+        __ashlsi3(a, b.into())
+    }
+
+    pub extern "C" fn __mspabi_sral(a: i32, b: u16) -> i32 {
+        __ashrsi3(a, b.into())
+    }
+
+    pub extern "C" fn __mspabi_srll(a: u32, b: u16) -> u32 {
+        __lshrsi3(a, b.into())
+    }
+}
+
+#[cfg(target_arch = "msp430")]
+intrinsics! {
+    // LLVM calls the 64-bit divisions with a special convention: the dividend is in R8-R11, the
+    // divisor in R12-R15 and the result is returned in R12-R15. These move the divisor to the
+    // stack and the dividend to R12-R15, where a C call expects them. R8-R10 are preserved by
+    // the C function.
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mspabi_divull() {
+        core::arch::naked_asm!(
+            "push r15",
+            "push r14",
+            "push r13",
+            "push r12",
+            "mov r8, r12",
+            "mov r9, r13",
+            "mov r10, r14",
+            "mov r11, r15",
+            "call #{trampoline}",
+            "add #8, r1",
+            "ret",
+            trampoline = sym crate::msp430::__udivdi3
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mspabi_remull() {
+        core::arch::naked_asm!(
+            "push r15",
+            "push r14",
+            "push r13",
+            "push r12",
+            "mov r8, r12",
+            "mov r9, r13",
+            "mov r10, r14",
+            "mov r11, r15",
+            "call #{trampoline}",
+            "add #8, r1",
+            "ret",
+            trampoline = sym crate::msp430::__umoddi3
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mspabi_divlli() {
+        core::arch::naked_asm!(
+            "push r15",
+            "push r14",
+            "push r13",
+            "push r12",
+            "mov r8, r12",
+            "mov r9, r13",
+            "mov r10, r14",
+            "mov r11, r15",
+            "call #{trampoline}",
+            "add #8, r1",
+            "ret",
+            trampoline = sym crate::msp430::__divdi3
+        );
+    }
+
+    #[unsafe(naked)]
+    pub unsafe extern "custom" fn __mspabi_remlli() {
+        core::arch::naked_asm!(
+            "push r15",
+            "push r14",
+            "push r13",
+            "push r12",
+            "mov r8, r12",
+            "mov r9, r13",
+            "mov r10, r14",
+            "mov r11, r15",
+            "call #{trampoline}",
+            "add #8, r1",
+            "ret",
+            trampoline = sym crate::msp430::__moddi3
+        );
+    }
+}
+
+// Shifts by a constant `N`, of the value in R12 (or R13:R12 for the 32-bit ones). Each entry point
+// shifts by one bit and falls through to the one for `N - 1`, so a family shares one copy of the
+// code. These only use the registers that hold the value.
+#[cfg(target_arch = "msp430")]
+global_asm!(
+    ".text",
+    ".p2align 1",
+    ".irp i, 15,14,13,12,11,10,9,8,7,6,5,4,3,2,1",
+    ".weak __mspabi_slli_\\i",
+    ".type __mspabi_slli_\\i, @function",
+    "__mspabi_slli_\\i:",
+    "rla r12",
+    ".endr",
+    "ret",
+    "",
+    ".irp i, 15,14,13,12,11,10,9,8,7,6,5,4,3,2,1",
+    ".weak __mspabi_srai_\\i",
+    ".type __mspabi_srai_\\i, @function",
+    "__mspabi_srai_\\i:",
+    "rra r12",
+    ".endr",
+    "ret",
+    "",
+    ".irp i, 15,14,13,12,11,10,9,8,7,6,5,4,3,2,1",
+    ".weak __mspabi_srli_\\i",
+    ".type __mspabi_srli_\\i, @function",
+    "__mspabi_srli_\\i:",
+    "clrc",
+    "rrc r12",
+    ".endr",
+    "ret",
+    "",
+    ".irp i, 15,14,13,12,11,10,9,8,7,6,5,4,3,2,1",
+    ".weak __mspabi_slll_\\i",
+    ".type __mspabi_slll_\\i, @function",
+    "__mspabi_slll_\\i:",
+    "rla r12",
+    "rlc r13",
+    ".endr",
+    "ret",
+    "",
+    ".irp i, 15,14,13,12,11,10,9,8,7,6,5,4,3,2,1",
+    ".weak __mspabi_sral_\\i",
+    ".type __mspabi_sral_\\i, @function",
+    "__mspabi_sral_\\i:",
+    "rra r13",
+    "rrc r12",
+    ".endr",
+    "ret",
+    "",
+    ".irp i, 15,14,13,12,11,10,9,8,7,6,5,4,3,2,1",
+    ".weak __mspabi_srll_\\i",
+    ".type __mspabi_srll_\\i, @function",
+    "__mspabi_srll_\\i:",
+    "clrc",
+    "rrc r13",
+    "rrc r12",
+    ".endr",
+    "ret",
+);
@@ -36,16 +36,30 @@
 //! Note that `#[naked]` is typically used here for the stack probe because the
 //! ABI corresponds to no actual ABI.
 //!
-//! Finally it's worth noting that at the time of this writing LLVM only has
-//! support for stack probes on x86 and x86_64. There's no support for stack
-//! probes on any other architecture like ARM or PowerPC64. LLVM I'm sure would
-//! be more than welcome to accept such a change!
+//! Finally it's worth noting that at the time of this writing LLVM only calls
+//! `__rust_probestack` on x86 and x86_64. Other architectures use inline stack
+//! probes, but some kernels and custom targets still want an out-of-line probe.
+//! For AArch64, RISC-V, LoongArch and PowerPC64 no compiler emits calls to
+//! `__rust_probestack` and there's no ABI to follow, so the routines here take
+//! the frame size in bytes in a scratch register and preserve every other
+//! general purpose register, like on x86. The callers are hand-written
+//! prologues that follow the convention documented on each.
 
-#![cfg(feature = "unmangled-names")]
+// Also built with mangled names for `unstable-public-internals` so it can be tested.
+#![cfg(any(feature = "unmangled-names", feature = "unstable-public-internals"))]
 // Windows and Cygwin already has builtins to do this.
 #![cfg(not(any(windows, target_os = "cygwin")))]
 // We only define stack probing for these architectures today.
-#![cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+#![cfg(any(
+    target_arch = "x86_64",
+    target_arch = "x86",
+    target_arch = "aarch64",
+    target_arch = "riscv64",
+    target_arch = "loongarch64",
+    all(target_arch = "powerpc64", target_abi = "elfv2"),
+))]
+// These are only meant to be called by code the compiler generates, with their own conventions.
+#![allow(clippy::missing_safety_doc)]
 
 // Our goal here is to touch each page between `rsp+8` and `rsp+8-rax`,
 // ensuring that if any pages are unmapped we'll make a page fault.
@@ -54,7 +68,7 @@
 // return we're not supposed to modify `rsp` or `rax`.
 #[cfg(target_arch = "x86_64")]
 #[unsafe(naked)]
-#[rustc_std_internal_symbol]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
 pub unsafe extern "custom" fn __rust_probestack() {
     core::arch::naked_asm!(
         "
@@ -129,7 +143,7 @@ pub unsafe extern "custom" fn __rust_probestack() {
 //
 // The ABI here is the same as x86_64, except everything is 32-bits large.
 #[unsafe(naked)]
-#[rustc_std_internal_symbol]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
 pub unsafe extern "custom" fn __rust_probestack() {
     core::arch::naked_asm!(
         "
@@ -178,7 +192,7 @@ pub unsafe extern "custom" fn __rust_probestack() {
 //   MSVC x64's __chkstk and cygwin/mingw's ___chkstk_ms do not adjust `rsp`
 //   themselves.
 #[unsafe(naked)]
-#[rustc_std_internal_symbol]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
 pub unsafe extern "custom" fn __rust_probestack() {
     core::arch::naked_asm!(
         "
@@ -219,3 +233,157 @@ pub unsafe extern "custom" fn __rust_probestack() {
         ",
     )
 }
+
+// Touch each page between `sp` and `sp - x15`, where `x15` is the frame size in bytes. LLVM
+// probes inline on AArch64, so nothing calls this with a compiler-defined convention. `x15` was
+// picked because Windows' `__chkstk` takes its argument there, though in 16-byte units. `x13` and
+// `x14` are saved below `sp` with `stp x13, x14, [sp, #-16]!` and restored before returning,
+// which keeps `sp` 16-byte aligned. The flags (NZCV) are clobbered.
+#[cfg(target_arch = "aarch64")]
+#[unsafe(naked)]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
+pub unsafe extern "custom" fn __rust_probestack() {
+    core::arch::naked_asm!(
+        "
+            .cfi_startproc
+            stp     x13, x14, [sp, #-16]!
+            .cfi_def_cfa_offset 16
+            .cfi_offset x13, -16
+            .cfi_offset x14, -8
+
+            // `x13` walks down from the caller's stack pointer a page at a time until it passes
+            // the lowest address of the new frame, in `x14`.
+            add     x13, sp, #16
+            sub     x14, x13, x15
+        2:
+            sub     x13, x13, #0x1000
+            cmp     x13, x14
+            b.ls    3f
+            ldrb    wzr, [x13]
+            b       2b
+
+        3:
+            ldrb    wzr, [x14]
+            ldp     x13, x14, [sp], #16
+            .cfi_def_cfa_offset 0
+            .cfi_restore x13
+            .cfi_restore x14
+            ret
+            .cfi_endproc
+        ",
+    )
+}
+
+// The same as AArch64, with the frame size in `t1`, saving `t2` and `t3` below `sp`. No compiler
+// calls this either, and nothing else is clobbered. Loads to `zero` still fault.
+#[cfg(target_arch = "riscv64")]
+#[unsafe(naked)]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
+pub unsafe extern "custom" fn __rust_probestack() {
+    core::arch::naked_asm!(
+        "
+            .cfi_startproc
+            addi    sp, sp, -16
+            .cfi_def_cfa_offset 16
+            sd      t2, 0(sp)
+            sd      t3, 8(sp)
+            .cfi_offset t2, -16
+            .cfi_offset t3, -8
+
+            addi    t2, sp, 16
+            sub     t3, t2, t1
+        2:
+            // A page doesn't fit in an immediate.
+            addi    t2, t2, -2048
+            addi    t2, t2, -2048
+            bleu    t2, t3, 3f
+            lb      zero, 0(t2)
+            j       2b
+
+        3:
+            lb      zero, 0(t3)
+            ld      t2, 0(sp)
+            ld      t3, 8(sp)
+            .cfi_restore t2
+            .cfi_restore t3
+            addi    sp, sp, 16
+            .cfi_def_cfa_offset 0
+            ret
+            .cfi_endproc
+        ",
+    )
+}
+
+// The same as RISC-V, with the frame size in `$t0`, saving `$t1` and `$t2` below `$sp`. No
+// compiler calls this either, and nothing else is clobbered. The CFI uses the DWARF numbers of
+// `$t1` and `$t2`.
+#[cfg(target_arch = "loongarch64")]
+#[unsafe(naked)]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
+pub unsafe extern "custom" fn __rust_probestack() {
+    core::arch::naked_asm!(
+        "
+            .cfi_startproc
+            addi.d  $sp, $sp, -16
+            .cfi_def_cfa_offset 16
+            st.d    $t1, $sp, 0
+            st.d    $t2, $sp, 8
+            .cfi_offset 13, -16
+            .cfi_offset 14, -8
+
+            addi.d  $t1, $sp, 16
+            sub.d   $t2, $t1, $t0
+        2:
+            addi.d  $t1, $t1, -2048
+            addi.d  $t1, $t1, -2048
+            bgeu    $t2, $t1, 3f
+            ld.b    $zero, $t1, 0
+            b       2b
+
+        3:
+            ld.b    $zero, $t2, 0
+            ld.d    $t1, $sp, 0
+            ld.d    $t2, $sp, 8
+            .cfi_restore 13
+            .cfi_restore 14
+            addi.d  $sp, $sp, 16
+            .cfi_def_cfa_offset 0
+            ret
+            .cfi_endproc
+        ",
+    )
+}
+
+// The frame size in bytes is in `r0`. No compiler calls this either. There is no zero register
+// to load into, so this needs three scratch registers. They are saved in the red zone below `r1`,
+// which leaves `r1` alone and needs no CFI. `cr0` is clobbered by the comparison.
+#[cfg(all(target_arch = "powerpc64", target_abi = "elfv2"))]
+#[unsafe(naked)]
+#[cfg_attr(feature = "unmangled-names", rustc_std_internal_symbol)]
+pub unsafe extern "custom" fn __rust_probestack() {
+    core::arch::naked_asm!(
+        "
+            .cfi_startproc
+            std     10, -8(1)
+            std     11, -16(1)
+            std     12, -24(1)
+
+            mr      11, 1
+            subf    12, 0, 1
+        2:
+            addi    11, 11, -4096
+            cmpld   11, 12
+            ble     3f
+            lbz     10, 0(11)
+            b       2b
+
+        3:
+            lbz     10, 0(12)
+            ld      10, -8(1)
+            ld      11, -16(1)
+            ld      12, -24(1)
+            blr
+            .cfi_endproc
+        ",
+    )
+}
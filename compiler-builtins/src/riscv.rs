@@ -0,0 +1,267 @@
+//! The millicode routines that `-msave-restore` calls in place of register spills, which makes
+//! functions smaller at the cost of a few extra instructions.
+//!
+//! A prologue calls `__riscv_save_N` with `jal t0`. It pushes `ra` and `s0` to `s{N-1}`, and
+//! returns through `t0`. The epilogue tail calls the matching `__riscv_restore_N`, which pops them
+//! and returns to the original caller. `t1` is the only other register used.
+//!
+//! The frame layout has to match what the compiler assumes in its CFI and frame offsets: `ra` is
+//! just below the caller's stack pointer, followed by `s0`, `s1` and so on. The frame is rounded
+//! up to the stack alignment, so several `N` share a routine that saves a few extra registers.
+//! Restores load from the top of the frame down, so each one falls through to the next smaller
+//! one. This matches `save.S` and `restore.S` in compiler-rt.
+
+use core::arch::global_asm;
+
+/// Directives to declare each of the names as a weak function, followed by their labels.
+macro_rules! entry {
+    ($($name:literal),*) => {
+        concat!($(".weak ", $name, "\n.type ", $name, ", @function\n", $name, ":\n"),*)
+    };
+}
+
+// The 16-byte aligned stack fits four registers per step.
+#[cfg(all(target_arch = "riscv32", not(target_feature = "e")))]
+global_asm!(
+    ".text",
+    ".p2align 2",
+    entry!("__riscv_save_12"),
+    "addi   sp, sp, -64",
+    "mv     t1, zero",
+    "sw     s11, 12(sp)",
+    "j      1f",
+    entry!(
+        "__riscv_save_11",
+        "__riscv_save_10",
+        "__riscv_save_9",
+        "__riscv_save_8"
+    ),
+    "addi   sp, sp, -64",
+    "li     t1, 16",
+    "1:",
+    "sw     s10, 16(sp)",
+    "sw     s9, 20(sp)",
+    "sw     s8, 24(sp)",
+    "sw     s7, 28(sp)",
+    "j      2f",
+    entry!(
+        "__riscv_save_7",
+        "__riscv_save_6",
+        "__riscv_save_5",
+        "__riscv_save_4"
+    ),
+    "addi   sp, sp, -64",
+    "li     t1, 32",
+    "2:",
+    "sw     s6, 32(sp)",
+    "sw     s5, 36(sp)",
+    "sw     s4, 40(sp)",
+    "sw     s3, 44(sp)",
+    "sw     s2, 48(sp)",
+    "sw     s1, 52(sp)",
+    "sw     s0, 56(sp)",
+    "sw     ra, 60(sp)",
+    // Give back the part of the frame this `N` doesn't use.
+    "add    sp, sp, t1",
+    "jr     t0",
+    entry!(
+        "__riscv_save_3",
+        "__riscv_save_2",
+        "__riscv_save_1",
+        "__riscv_save_0"
+    ),
+    "addi   sp, sp, -16",
+    "sw     s2, 0(sp)",
+    "sw     s1, 4(sp)",
+    "sw     s0, 8(sp)",
+    "sw     ra, 12(sp)",
+    "jr     t0",
+    "",
+    entry!("__riscv_restore_12"),
+    "lw     s11, 12(sp)",
+    "addi   sp, sp, 16",
+    entry!(
+        "__riscv_restore_11",
+        "__riscv_restore_10",
+        "__riscv_restore_9",
+        "__riscv_restore_8"
+    ),
+    "lw     s10, 0(sp)",
+    "lw     s9, 4(sp)",
+    "lw     s8, 8(sp)",
+    "lw     s7, 12(sp)",
+    "addi   sp, sp, 16",
+    entry!(
+        "__riscv_restore_7",
+        "__riscv_restore_6",
+        "__riscv_restore_5",
+        "__riscv_restore_4"
+    ),
+    "lw     s6, 0(sp)",
+    "lw     s5, 4(sp)",
+    "lw     s4, 8(sp)",
+    "lw     s3, 12(sp)",
+    "addi   sp, sp, 16",
+    entry!(
+        "__riscv_restore_3",
+        "__riscv_restore_2",
+        "__riscv_restore_1",
+        "__riscv_restore_0"
+    ),
+    "lw     s2, 0(sp)",
+    "lw     s1, 4(sp)",
+    "lw     s0, 8(sp)",
+    "lw     ra, 12(sp)",
+    "addi   sp, sp, 16",
+    "ret",
+);
+
+// The same with two registers per 16 bytes.
+#[cfg(all(target_arch = "riscv64", not(target_feature = "e")))]
+global_asm!(
+    ".text",
+    ".p2align 2",
+    entry!("__riscv_save_12"),
+    "addi   sp, sp, -112",
+    "mv     t1, zero",
+    "sd     s11, 8(sp)",
+    "j      1f",
+    entry!("__riscv_save_11", "__riscv_save_10"),
+    "addi   sp, sp, -112",
+    "li     t1, 16",
+    "1:",
+    "sd     s10, 16(sp)",
+    "sd     s9, 24(sp)",
+    "j      2f",
+    entry!("__riscv_save_9", "__riscv_save_8"),
+    "addi   sp, sp, -112",
+    "li     t1, 32",
+    "2:",
+    "sd     s8, 32(sp)",
+    "sd     s7, 40(sp)",
+    "j      3f",
+    entry!("__riscv_save_7", "__riscv_save_6"),
+    "addi   sp, sp, -112",
+    "li     t1, 48",
+    "3:",
+    "sd     s6, 48(sp)",
+    "sd     s5, 56(sp)",
+    "j      4f",
+    entry!("__riscv_save_5", "__riscv_save_4"),
+    "addi   sp, sp, -112",
+    "li     t1, 64",
+    "4:",
+    "sd     s4, 64(sp)",
+    "sd     s3, 72(sp)",
+    "j      5f",
+    entry!("__riscv_save_3", "__riscv_save_2"),
+    "addi   sp, sp, -112",
+    "li     t1, 80",
+    "5:",
+    "sd     s2, 80(sp)",
+    "sd     s1, 88(sp)",
+    "sd     s0, 96(sp)",
+    "sd     ra, 104(sp)",
+    "add    sp, sp, t1",
+    "jr     t0",
+    entry!("__riscv_save_1", "__riscv_save_0"),
+    "addi   sp, sp, -16",
+    "sd     s0, 0(sp)",
+    "sd     ra, 8(sp)",
+    "jr     t0",
+    "",
+    entry!("__riscv_restore_12"),
+    "ld     s11, 8(sp)",
+    "addi   sp, sp, 16",
+    entry!("__riscv_restore_11", "__riscv_restore_10"),
+    "ld     s10, 0(sp)",
+    "ld     s9, 8(sp)",
+    "addi   sp, sp, 16",
+    entry!("__riscv_restore_9", "__riscv_restore_8"),
+    "ld     s8, 0(sp)",
+    "ld     s7, 8(sp)",
+    "addi   sp, sp, 16",
+    entry!("__riscv_restore_7", "__riscv_restore_6"),
+    "ld     s6, 0(sp)",
+    "ld     s5, 8(sp)",
+    "addi   sp, sp, 16",
+    entry!("__riscv_restore_5", "__riscv_restore_4"),
+    "ld     s4, 0(sp)",
+    "ld     s3, 8(sp)",
+    "addi   sp, sp, 16",
+    entry!("__riscv_restore_3", "__riscv_restore_2"),
+    "ld     s2, 0(sp)",
+    "ld     s1, 8(sp)",
+    "addi   sp, sp, 16",
+    entry!("__riscv_restore_1", "__riscv_restore_0"),
+    "ld     s0, 0(sp)",
+    "ld     ra, 8(sp)",
+    "addi   sp, sp, 16",
+    "ret",
+);
+
+// RVE only has `s0` and `s1`, and its stack is only aligned to the register size, so every `N`
+// has its own frame size.
+#[cfg(all(target_arch = "riscv32", target_feature = "e"))]
+global_asm!(
+    ".text",
+    ".p2align 2",
+    entry!("__riscv_save_2"),
+    "addi   sp, sp, -12",
+    "sw     s1, 0(sp)",
+    "sw     s0, 4(sp)",
+    "sw     ra, 8(sp)",
+    "jr     t0",
+    entry!("__riscv_save_1"),
+    "addi   sp, sp, -8",
+    "sw     s0, 0(sp)",
+    "sw     ra, 4(sp)",
+    "jr     t0",
+    entry!("__riscv_save_0"),
+    "addi   sp, sp, -4",
+    "sw     ra, 0(sp)",
+    "jr     t0",
+    "",
+    entry!("__riscv_restore_2"),
+    "lw     s1, 0(sp)",
+    "addi   sp, sp, 4",
+    entry!("__riscv_restore_1"),
+    "lw     s0, 0(sp)",
+    "addi   sp, sp, 4",
+    entry!("__riscv_restore_0"),
+    "lw     ra, 0(sp)",
+    "addi   sp, sp, 4",
+    "ret",
+);
+
+#[cfg(all(target_arch = "riscv64", target_feature = "e"))]
+global_asm!(
+    ".text",
+    ".p2align 2",
+    entry!("__riscv_save_2"),
+    "addi   sp, sp, -24",
+    "sd     s1, 0(sp)",
+    "sd     s0, 8(sp)",
+    "sd     ra, 16(sp)",
+    "jr     t0",
+    entry!("__riscv_save_1"),
+    "addi   sp, sp, -16",
+    "sd     s0, 0(sp)",
+    "sd     ra, 8(sp)",
+    "jr     t0",
+    entry!("__riscv_save_0"),
+    "addi   sp, sp, -8",
+    "sd     ra, 0(sp)",
+    "jr     t0",
+    "",
+    entry!("__riscv_restore_2"),
+    "ld     s1, 0(sp)",
+    "addi   sp, sp, 8",
+    entry!("__riscv_restore_1"),
+    "ld     s0, 0(sp)",
+    "addi   sp, sp, 8",
+    entry!("__riscv_restore_0"),
+    "ld     ra, 0(sp)",
+    "addi   sp, sp, 8",
+    "ret",
+);
@@ -0,0 +1,380 @@
+//! The `__atomic_*` library calls, which LLVM emits for atomic operations that are wider than the
+//! target supports natively, or not aligned to their size.
+//!
+//! As in compiler-rt, sizes that the target supports lock-free use native atomics, including from
+//! the generic calls when the object is aligned, so that they stay atomic with respect to inline
+//! atomic instructions on the same object. Every other operation holds a lock while it accesses
+//! memory. With native CAS the locks are a table of spinlocks selected by address. Without it,
+//! operations instead run with interrupts disabled, which is only sound on single-core systems.
+//! Either way the locked atomics are only atomic with respect to other calls into this module, so
+//! all accesses to an object must go through it. This is what LLVM does when it decides that an
+//! atomic size is not lock-free.
+//!
+//! See <https://llvm.org/docs/Atomics.html#libcalls-atomic> and
+//! <https://gcc.gnu.org/wiki/Atomic/GCCMM/LIbrary> for the interface.
+
+use core::ffi::c_int;
+use core::ptr;
+use core::sync::atomic::Ordering;
+
+use super::lock::atomically;
+
+/// The ordering for the C memory model `model`, for an operation that both loads and stores.
+/// Unknown models are treated as `__ATOMIC_SEQ_CST`.
+#[cfg_attr(not(target_has_atomic = "8"), allow(dead_code))]
+fn ordering(model: c_int) -> Ordering {
+    match model {
+        0 => Ordering::Relaxed,
+        // `__ATOMIC_CONSUME` is strengthened to acquire, as compilers do.
+        1 | 2 => Ordering::Acquire,
+        3 => Ordering::Release,
+        4 => Ordering::AcqRel,
+        _ => Ordering::SeqCst,
+    }
+}
+
+/// Like `ordering`, for an operation that only loads.
+#[cfg_attr(not(target_has_atomic = "8"), allow(dead_code))]
+fn load_ordering(model: c_int) -> Ordering {
+    match ordering(model) {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        o => o,
+    }
+}
+
+/// Like `ordering`, for an operation that only stores.
+#[cfg_attr(not(target_has_atomic = "8"), allow(dead_code))]
+fn store_ordering(model: c_int) -> Ordering {
+    match ordering(model) {
+        Ordering::Acquire => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Release,
+        o => o,
+    }
+}
+
+/// Returns `$call` from the enclosing function if `$size` is a lock-free size and `$addr` is
+/// aligned to it. In `$call`, `$ty` is the unsigned integer of that size and `$f` is the sized
+/// operation whose name starts with `$prefix`.
+macro_rules! forward_lock_free {
+    ($size:expr, $addr:expr, $prefix:ident, |$ty:ident, $f:ident| $call:expr) => {
+        forward_lock_free!(@size "8", 1, u8, $size, $addr, $prefix, |$ty, $f| $call);
+        forward_lock_free!(@size "16", 2, u16, $size, $addr, $prefix, |$ty, $f| $call);
+        forward_lock_free!(@size "32", 4, u32, $size, $addr, $prefix, |$ty, $f| $call);
+        forward_lock_free!(@size "64", 8, u64, $size, $addr, $prefix, |$ty, $f| $call);
+        forward_lock_free!(@size "128", 16, u128, $size, $addr, $prefix, |$ty, $f| $call);
+    };
+    (
+        @size $width:literal, $bytes:literal, $int:ty,
+        $size:expr, $addr:expr, $prefix:ident, |$ty:ident, $f:ident| $call:expr
+    ) => {
+        #[cfg(target_has_atomic = $width)]
+        if $size == $bytes && $addr.is_multiple_of($bytes) {
+            type $ty = $int;
+            let $f = ${concat($prefix, $bytes)};
+            return $call;
+        }
+    };
+}
+
+intrinsics! {
+    /// Atomically copies `size` bytes from `src` to `dest`.
+    ///
+    /// # Safety
+    ///
+    /// `src` must be valid for atomic reads and `dest` for writes of `size` bytes.
+    pub unsafe extern "C" fn __atomic_load(
+        size: usize, src: *const u8, dest: *mut u8, model: core::ffi::c_int
+    ) {
+        forward_lock_free!(size, src.addr(), __atomic_load_, |T, f| unsafe {
+            dest.cast::<T>().write_unaligned(f(src.cast(), model))
+        });
+        atomically(src.addr(), model, || unsafe {
+            ptr::copy_nonoverlapping(src, dest, size)
+        });
+    }
+
+    /// Atomically copies `size` bytes from `src` to `dest`.
+    ///
+    /// # Safety
+    ///
+    /// `src` must be valid for reads and `dest` for atomic writes of `size` bytes.
+    pub unsafe extern "C" fn __atomic_store(
+        size: usize, dest: *mut u8, src: *const u8, model: core::ffi::c_int
+    ) {
+        forward_lock_free!(size, dest.addr(), __atomic_store_, |T, f| unsafe {
+            f(dest.cast(), src.cast::<T>().read_unaligned(), model)
+        });
+        atomically(dest.addr(), model, || unsafe {
+            ptr::copy_nonoverlapping(src, dest, size)
+        });
+    }
+
+    /// Atomically replaces the `size` bytes at `ptr` with those at `val`, storing the previous
+    /// ones to `old`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for atomic reads and writes of `size` bytes. `val` must be valid for
+    /// reads and `old` for writes of `size` bytes, and `old` may be the same as `val`.
+    pub unsafe extern "C" fn __atomic_exchange(
+        size: usize, ptr: *mut u8, val: *const u8, old: *mut u8, model: core::ffi::c_int
+    ) {
+        forward_lock_free!(size, ptr.addr(), __atomic_exchange_, |T, f| unsafe {
+            let prev = f(ptr.cast(), val.cast::<T>().read_unaligned(), model);
+            old.cast::<T>().write_unaligned(prev)
+        });
+        atomically(ptr.addr(), model, || unsafe {
+            // Swap a byte at a time since `old` and `val` may be the same.
+            let mut i = 0;
+            while i < size {
+                let x = val.add(i).read();
+                old.add(i).write(ptr.add(i).read());
+                ptr.add(i).write(x);
+                i += 1;
+            }
+        });
+    }
+
+    /// If the `size` bytes at `ptr` are equal to those at `expected`, atomically replaces them
+    /// with those at `desired` and returns true. Otherwise copies them to `expected` and returns
+    /// false.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for atomic reads and writes of `size` bytes, `expected` for reads and
+    /// writes and `desired` for reads.
+    pub unsafe extern "C" fn __atomic_compare_exchange(
+        size: usize,
+        ptr: *mut u8,
+        expected: *mut u8,
+        desired: *const u8,
+        success: core::ffi::c_int,
+        failure: core::ffi::c_int
+    ) -> bool {
+        forward_lock_free!(size, ptr.addr(), __atomic_compare_exchange_, |T, f| unsafe {
+            let mut current = expected.cast::<T>().read_unaligned();
+            let desired = desired.cast::<T>().read_unaligned();
+            let res = f(ptr.cast(), &mut current, desired, success, failure);
+            expected.cast::<T>().write_unaligned(current);
+            res
+        });
+        // The failure ordering can't be stronger than the success one.
+        let _ = failure;
+        atomically(ptr.addr(), success, || unsafe {
+            let mut i = 0;
+            while i < size {
+                if ptr.add(i).read() != expected.add(i).read() {
+                    ptr::copy_nonoverlapping(ptr, expected, size);
+                    return false;
+                }
+                i += 1;
+            }
+            ptr::copy_nonoverlapping(desired, ptr, size);
+            true
+        })
+    }
+}
+
+/// The sized operations on `$ty`, with names ending in `$bytes`. They use `$atomic` if the target
+/// has atomics of `$width` bits, and otherwise take the lock.
+macro_rules! atomic_sized {
+    ($ty:ty, $atomic:ident, $bytes:literal, $width:literal) => {
+        #[cfg(target_has_atomic = $width)]
+        intrinsics! {
+            /// Atomically loads the value at `ptr`.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads.
+            pub unsafe extern "C" fn ${concat(__atomic_load_, $bytes)}(
+                ptr: *const $ty, model: core::ffi::c_int
+            ) -> $ty {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(ptr.cast_mut()) };
+                a.load(crate::sync::libatomic::load_ordering(model))
+            }
+
+            /// Atomically stores `val` to `ptr`.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic writes.
+            pub unsafe extern "C" fn ${concat(__atomic_store_, $bytes)}(
+                ptr: *mut $ty, val: $ty, model: core::ffi::c_int
+            ) {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                a.store(val, crate::sync::libatomic::store_ordering(model));
+            }
+
+            /// Atomically replaces the value at `ptr` with `val`, returning the previous value.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads and writes.
+            pub unsafe extern "C" fn ${concat(__atomic_exchange_, $bytes)}(
+                ptr: *mut $ty, val: $ty, model: core::ffi::c_int
+            ) -> $ty {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                a.swap(val, crate::sync::libatomic::ordering(model))
+            }
+
+            /// If the value at `ptr` is `*expected`, atomically replaces it with `desired` and
+            /// returns true. Otherwise stores it to `expected` and returns false.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads and writes. `expected` must be
+            /// valid for reads and writes.
+            pub unsafe extern "C" fn ${concat(__atomic_compare_exchange_, $bytes)}(
+                ptr: *mut $ty,
+                expected: *mut $ty,
+                desired: $ty,
+                success: core::ffi::c_int,
+                failure: core::ffi::c_int
+            ) -> bool {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                let res = a.compare_exchange(
+                    unsafe { expected.read() },
+                    desired,
+                    crate::sync::libatomic::ordering(success),
+                    crate::sync::libatomic::load_ordering(failure),
+                );
+                match res {
+                    Ok(_) => true,
+                    Err(current) => {
+                        unsafe { expected.write(current) };
+                        false
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(target_has_atomic = $width))]
+        intrinsics! {
+            /// Atomically loads the value at `ptr`.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads.
+            pub unsafe extern "C" fn ${concat(__atomic_load_, $bytes)}(
+                ptr: *const $ty, model: core::ffi::c_int
+            ) -> $ty {
+                atomically(ptr.addr(), model, || unsafe { ptr.read() })
+            }
+
+            /// Atomically stores `val` to `ptr`.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic writes.
+            pub unsafe extern "C" fn ${concat(__atomic_store_, $bytes)}(
+                ptr: *mut $ty, val: $ty, model: core::ffi::c_int
+            ) {
+                atomically(ptr.addr(), model, || unsafe { ptr.write(val) });
+            }
+
+            /// Atomically replaces the value at `ptr` with `val`, returning the previous value.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads and writes.
+            pub unsafe extern "C" fn ${concat(__atomic_exchange_, $bytes)}(
+                ptr: *mut $ty, val: $ty, model: core::ffi::c_int
+            ) -> $ty {
+                atomically(ptr.addr(), model, || unsafe { ptr.replace(val) })
+            }
+
+            /// If the value at `ptr` is `*expected`, atomically replaces it with `desired` and
+            /// returns true. Otherwise stores it to `expected` and returns false.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads and writes. `expected` must be
+            /// valid for reads and writes.
+            pub unsafe extern "C" fn ${concat(__atomic_compare_exchange_, $bytes)}(
+                ptr: *mut $ty,
+                expected: *mut $ty,
+                desired: $ty,
+                success: core::ffi::c_int,
+                failure: core::ffi::c_int
+            ) -> bool {
+                let _ = failure;
+                atomically(ptr.addr(), success, || unsafe {
+                    let current = ptr.read();
+                    if current == expected.read() {
+                        ptr.write(desired);
+                        true
+                    } else {
+                        expected.write(current);
+                        false
+                    }
+                })
+            }
+        }
+
+        atomic_rmw! {
+            $ty, $atomic, $width;
+            ${concat(__atomic_fetch_add_, $bytes)}, fetch_add, |a: $ty, b: $ty| a.wrapping_add(b);
+            ${concat(__atomic_fetch_sub_, $bytes)}, fetch_sub, |a: $ty, b: $ty| a.wrapping_sub(b);
+            ${concat(__atomic_fetch_and_, $bytes)}, fetch_and, |a: $ty, b: $ty| a & b;
+            ${concat(__atomic_fetch_or_, $bytes)}, fetch_or, |a: $ty, b: $ty| a | b;
+            ${concat(__atomic_fetch_xor_, $bytes)}, fetch_xor, |a: $ty, b: $ty| a ^ b;
+            ${concat(__atomic_fetch_nand_, $bytes)}, fetch_nand, |a: $ty, b: $ty| !(a & b);
+        }
+    };
+}
+
+/// Operations that replace the value at `ptr` with `$op(old, val)` and return the old value. They
+/// use `$atomic::$method` if the target has atomics of `$width` bits, and otherwise take the lock.
+macro_rules! atomic_rmw {
+    ($ty:ty, $atomic:ident, $width:literal; $($name:ident, $method:ident, $op:expr;)*) => {
+        #[cfg(target_has_atomic = $width)]
+        intrinsics! {$(
+            /// Atomically applies the operation to the value at `ptr` and `val`, returning the
+            /// previous value.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads and writes.
+            pub unsafe extern "C" fn $name(
+                ptr: *mut $ty, val: $ty, model: core::ffi::c_int
+            ) -> $ty {
+                let a = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                a.$method(val, crate::sync::libatomic::ordering(model))
+            }
+        )*}
+
+        #[cfg(not(target_has_atomic = $width))]
+        intrinsics! {$(
+            /// Atomically applies the operation to the value at `ptr` and `val`, returning the
+            /// previous value.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be aligned and valid for atomic reads and writes.
+            pub unsafe extern "C" fn $name(
+                ptr: *mut $ty, val: $ty, model: core::ffi::c_int
+            ) -> $ty {
+                atomically(ptr.addr(), model, || unsafe {
+                    let old = ptr.read();
+                    ptr.write($op(old, val));
+                    old
+                })
+            }
+        )*}
+    };
+}
+
+atomic_sized!(u8, AtomicU8, 1, "8");
+atomic_sized!(u16, AtomicU16, 2, "16");
+atomic_sized!(u32, AtomicU32, 4, "32");
+atomic_sized!(u64, AtomicU64, 8, "64");
+// x86_64 has its own versions, which use `cmpxchg16b` if the CPU has it.
+#[cfg(not(target_arch = "x86_64"))]
+atomic_sized!(u128, AtomicU128, 16, "128");
+#[cfg(target_arch = "x86_64")]
+pub use super::x86_64::{
+    __atomic_compare_exchange_16, __atomic_exchange_16, __atomic_fetch_add_16,
+    __atomic_fetch_and_16, __atomic_fetch_nand_16, __atomic_fetch_or_16, __atomic_fetch_sub_16,
+    __atomic_fetch_xor_16, __atomic_load_16, __atomic_store_16,
+};
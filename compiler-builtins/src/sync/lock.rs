@@ -0,0 +1,90 @@
+//! Locking for atomics that can't be done with native instructions. See `libatomic.rs`.
+
+use core::ffi::c_int;
+use core::sync::atomic::{Ordering, fence};
+
+/// `__ATOMIC_SEQ_CST`. The other memory models need no more than the lock itself provides.
+const SEQ_CST: c_int = 5;
+
+#[cfg(target_has_atomic = "8")]
+mod imp {
+    use core::hint::spin_loop;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    const LOCK_COUNT: usize = 64;
+
+    /// Addresses in the same granule share a lock, so objects of up to this size are usually
+    /// covered by one lock whatever address they are accessed through.
+    const GRANULE: usize = 16;
+
+    static LOCKS: [AtomicBool; LOCK_COUNT] = [const { AtomicBool::new(false) }; LOCK_COUNT];
+
+    /// Runs `f` while holding the lock for `addr`.
+    #[inline]
+    pub(super) fn with_lock<R>(addr: usize, f: impl FnOnce() -> R) -> R {
+        let lock = &LOCKS[addr / GRANULE % LOCK_COUNT];
+        while lock.swap(true, Ordering::Acquire) {
+            // Wait without writing so the cache line isn't contended.
+            while lock.load(Ordering::Relaxed) {
+                spin_loop();
+            }
+        }
+        let res = f();
+        lock.store(false, Ordering::Release);
+        res
+    }
+}
+
+#[cfg(not(target_has_atomic = "8"))]
+mod imp {
+    use core::arch::asm;
+
+    /// Runs `f` with interrupts disabled. The asm blocks are not `nomem`, so they also keep the
+    /// compiler from moving memory accesses out of the critical section.
+    #[cfg(all(target_arch = "arm", target_feature = "mclass"))]
+    #[inline]
+    pub(super) fn with_lock<R>(_addr: usize, f: impl FnOnce() -> R) -> R {
+        let primask: u32;
+        // SAFETY: only masks interrupts, which are restored below.
+        unsafe { asm!("mrs {}, PRIMASK", "cpsid i", out(reg) primask, options(nostack)) };
+        let res = f();
+        // Only enable interrupts if they were enabled before.
+        if primask & 1 == 0 {
+            // SAFETY: restores the state from before.
+            unsafe { asm!("cpsie i", options(nostack)) };
+        }
+        res
+    }
+
+    /// Runs `f` with interrupts disabled, which requires running in machine mode.
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    #[inline]
+    pub(super) fn with_lock<R>(_addr: usize, f: impl FnOnce() -> R) -> R {
+        // `mstatus.MIE`
+        const MIE: usize = 1 << 3;
+        let mstatus: usize;
+        // SAFETY: only clears `mstatus.MIE`, which is restored below.
+        unsafe { asm!("csrrci {}, mstatus, 8", out(reg) mstatus, options(nostack)) };
+        let res = f();
+        if mstatus & MIE != 0 {
+            // SAFETY: restores the state from before.
+            unsafe { asm!("csrsi mstatus, 8", options(nostack)) };
+        }
+        res
+    }
+}
+
+/// Runs `f` atomically with respect to the other operations on `addr`.
+#[inline]
+pub(crate) fn atomically<R>(addr: usize, model: c_int, f: impl FnOnce() -> R) -> R {
+    // The lock orders the operations on the same address, but sequentially consistent operations
+    // also need to be ordered with those on other addresses.
+    if model == SEQ_CST {
+        fence(Ordering::SeqCst);
+    }
+    let res = imp::with_lock(addr, f);
+    if model == SEQ_CST {
+        fence(Ordering::SeqCst);
+    }
+    res
+}
@@ -18,3 +18,35 @@ pub mod arm_linux;
     not(target_feature = "v6t2"),
 ))]
 pub mod thumbv6k;
+
+// Used by `libatomic` and as the fallback for 16-byte atomics on x86_64.
+#[cfg(any(
+    target_arch = "x86_64",
+    all(
+        any(target_os = "none", feature = "unstable-public-internals"),
+        any(
+            target_has_atomic = "8",
+            all(target_arch = "arm", target_feature = "mclass"),
+            target_arch = "riscv32",
+            target_arch = "riscv64",
+        )
+    )
+))]
+mod lock;
+
+// Elsewhere libatomic provides these, and its lock table has to be the one shared by everything in
+// the process. The module is also enabled for `unstable-public-internals` so that it gets tested.
+#[cfg(all(
+    any(target_os = "none", feature = "unstable-public-internals"),
+    any(
+        target_has_atomic = "8",
+        all(target_arch = "arm", target_feature = "mclass"),
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+    )
+))]
+pub mod libatomic;
+
+// `__atomic_*_16`, which use `cmpxchg16b` if the CPU has it.
+#[cfg(target_arch = "x86_64")]
+pub mod x86_64;
@@ -0,0 +1,223 @@
+//! 16-byte atomics for x86_64 CPUs that may not have `cmpxchg16b`, which LLVM turns into
+//! `__atomic_*_16` calls. The first x86_64 CPUs lacked the instruction, so it isn't part of the
+//! baseline target.
+//!
+//! If the CPU has `cmpxchg16b`, every operation is done with it, which is compatible with inline
+//! 16-byte atomics in code built with `+cmpxchg16b`. Otherwise operations fall back to the lock
+//! table. `lock cmpxchg16b` is a full barrier, so the memory model only matters for the fallback.
+
+use core::arch::asm;
+use core::arch::x86_64::__cpuid;
+use core::ffi::c_int;
+use core::sync::atomic::AtomicU32;
+#[cfg(feature = "unstable-public-internals")]
+use core::sync::atomic::Ordering;
+
+use super::lock::atomically;
+use crate::support::feature_detect::{Flags, get_or_init_flags_cache};
+
+/// Cached result of checking for `cmpxchg16b`.
+static CPU_FLAGS: AtomicU32 = AtomicU32::new(0);
+
+/// Set in `CPU_FLAGS` if `cmpxchg16b` is available.
+const CMPXCHG16B: u32 = 1;
+
+/// Check the CPU for `cmpxchg16b`.
+// FIXME(msrv): Remove unsafe block around __cpuid once https://github.com/rust-lang/stdarch/pull/1935 is available in MSRV.
+#[allow(unused_unsafe)]
+fn detect() -> Flags {
+    let mut flags = Flags::empty();
+    if cfg!(target_feature = "cmpxchg16b") {
+        flags.insert(CMPXCHG16B);
+    } else if !cfg!(target_env = "sgx") {
+        // Leaf 1 is always available on x86_64. `CMPXCHG16B` is bit 13 of ECX. SGX is skipped
+        // since `cpuid` is untrusted there.
+        let ecx = unsafe { __cpuid(0x0000_0001_u32) }.ecx;
+        if Flags::from_bits(ecx).test_nth(13) {
+            flags.insert(CMPXCHG16B);
+        }
+    }
+    flags
+}
+
+/// Whether to use `cmpxchg16b`, checking the CPU the first time.
+fn have_cmpxchg16b() -> bool {
+    get_or_init_flags_cache(&CPU_FLAGS, detect).contains(CMPXCHG16B)
+}
+
+/// Function to enable/disable `cmpxchg16b`. To be used only for testing purposes.
+///
+/// # Safety
+///
+/// `cmpxchg16b` must not be enabled unless the CPU supports it, and no other 16-byte atomic
+/// operations may run at the same time.
+#[cfg(feature = "unstable-public-internals")]
+pub unsafe fn set_have_cmpxchg16b(has_cmpxchg16b: bool) {
+    // Check first so the detection doesn't overwrite this later.
+    let mut flags = get_or_init_flags_cache(&CPU_FLAGS, detect);
+    if has_cmpxchg16b {
+        flags.insert(CMPXCHG16B);
+    } else {
+        flags = Flags::from_bits(flags.bits() & !CMPXCHG16B);
+    }
+    CPU_FLAGS.store(flags.bits(), Ordering::Relaxed);
+}
+
+/// Function to obtain whether `cmpxchg16b` is used or not. To be used only for testing purposes.
+#[cfg(feature = "unstable-public-internals")]
+pub fn get_have_cmpxchg16b() -> bool {
+    have_cmpxchg16b()
+}
+
+/// If the value at `ptr` is `old`, replaces it with `new`. Returns the previous value.
+///
+/// # Safety
+///
+/// The CPU must support `cmpxchg16b`. `ptr` must be 16-byte aligned and valid for reads and
+/// writes.
+#[inline]
+unsafe fn cmpxchg16b(ptr: *mut u128, old: u128, new: u128) -> u128 {
+    let prev_lo: u64;
+    let prev_hi: u64;
+    // SAFETY: the caller guarantees the CPU has the instruction and `ptr` is valid. `rbx` can't
+    // be an operand since LLVM reserves it, so the low half of `new` is swapped into it and the
+    // original value restored afterwards.
+    unsafe {
+        asm!(
+            "xchg {new_lo}, rbx",
+            "lock cmpxchg16b xmmword ptr [{ptr}]",
+            "mov rbx, {new_lo}",
+            ptr = in(reg) ptr,
+            new_lo = inout(reg) new as u64 => _,
+            in("rcx") (new >> 64) as u64,
+            inout("rax") old as u64 => prev_lo,
+            inout("rdx") (old >> 64) as u64 => prev_hi,
+            options(nostack),
+        );
+    }
+    (u128::from(prev_hi) << 64) | u128::from(prev_lo)
+}
+
+/// Replaces the value at `ptr` with `f(old)`, returning `old`.
+///
+/// # Safety
+///
+/// `ptr` must be 16-byte aligned and valid for atomic reads and writes.
+#[inline]
+unsafe fn atomic_rmw(ptr: *mut u128, model: c_int, f: impl Fn(u128) -> u128) -> u128 {
+    if !have_cmpxchg16b() {
+        return atomically(ptr.addr(), model, || unsafe {
+            let old = ptr.read();
+            ptr.write(f(old));
+            old
+        });
+    }
+
+    // Guess zero, the first compare-exchange returns the actual value if that is wrong.
+    let mut old = 0;
+    loop {
+        // SAFETY: checked for `cmpxchg16b` above, and the caller guarantees `ptr` is valid.
+        let prev = unsafe { cmpxchg16b(ptr, old, f(old)) };
+        if prev == old {
+            return old;
+        }
+        old = prev;
+    }
+}
+
+intrinsics! {
+    /// Atomically loads the value at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be 16-byte aligned and valid for atomic reads. It must also be writable, since
+    /// `cmpxchg16b` always writes.
+    pub unsafe extern "C" fn __atomic_load_16(ptr: *const u128, model: core::ffi::c_int) -> u128 {
+        if have_cmpxchg16b() {
+            // Storing the value that is already there doesn't change anything.
+            unsafe { cmpxchg16b(ptr.cast_mut(), 0, 0) }
+        } else {
+            atomically(ptr.addr(), model, || unsafe { ptr.read() })
+        }
+    }
+
+    /// Atomically stores `val` to `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be 16-byte aligned and valid for atomic writes.
+    pub unsafe extern "C" fn __atomic_store_16(ptr: *mut u128, val: u128, model: core::ffi::c_int) {
+        unsafe { atomic_rmw(ptr, model, |_| val) };
+    }
+
+    /// Atomically replaces the value at `ptr` with `val`, returning the previous value.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be 16-byte aligned and valid for atomic reads and writes.
+    pub unsafe extern "C" fn __atomic_exchange_16(
+        ptr: *mut u128, val: u128, model: core::ffi::c_int
+    ) -> u128 {
+        unsafe { atomic_rmw(ptr, model, |_| val) }
+    }
+
+    /// If the value at `ptr` is `*expected`, atomically replaces it with `desired` and returns
+    /// true. Otherwise stores it to `expected` and returns false.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be 16-byte aligned and valid for atomic reads and writes. `expected` must be
+    /// valid for reads and writes.
+    pub unsafe extern "C" fn __atomic_compare_exchange_16(
+        ptr: *mut u128,
+        expected: *mut u128,
+        desired: u128,
+        success: core::ffi::c_int,
+        failure: core::ffi::c_int
+    ) -> bool {
+        let _ = failure;
+        let old = unsafe { expected.read() };
+        let prev = if have_cmpxchg16b() {
+            unsafe { cmpxchg16b(ptr, old, desired) }
+        } else {
+            atomically(ptr.addr(), success, || unsafe {
+                let prev = ptr.read();
+                if prev == old {
+                    ptr.write(desired);
+                }
+                prev
+            })
+        };
+        if prev == old {
+            true
+        } else {
+            unsafe { expected.write(prev) };
+            false
+        }
+    }
+}
+
+macro_rules! atomic_rmw {
+    ($name:ident, $op:expr) => {
+        intrinsics! {
+            /// Atomically applies the operation to the value at `ptr` and `val`, returning the
+            /// previous value.
+            ///
+            /// # Safety
+            ///
+            /// `ptr` must be 16-byte aligned and valid for atomic reads and writes.
+            pub unsafe extern "C" fn $name(
+                ptr: *mut u128, val: u128, model: core::ffi::c_int
+            ) -> u128 {
+                unsafe { atomic_rmw(ptr, model, |old| $op(old, val)) }
+            }
+        }
+    };
+}
+
+atomic_rmw!(__atomic_fetch_add_16, |a: u128, b: u128| a.wrapping_add(b));
+atomic_rmw!(__atomic_fetch_sub_16, |a: u128, b: u128| a.wrapping_sub(b));
+atomic_rmw!(__atomic_fetch_and_16, |a: u128, b: u128| a & b);
+atomic_rmw!(__atomic_fetch_or_16, |a: u128, b: u128| a | b);
+atomic_rmw!(__atomic_fetch_xor_16, |a: u128, b: u128| a ^ b);
+atomic_rmw!(__atomic_fetch_nand_16, |a: u128, b: u128| !(a & b));
@@ -52,7 +52,7 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
             returns: &[Ty::F16],
         },
         c_sig: None,
-        fn_list: &["addf16", "mulf16", "subf16"],
+        fn_list: &["addf16", "divf16", "mulf16", "subf16"],
         scope: OpScope::BuiltinsPublic,
     },
     NestedOp {
@@ -82,6 +82,15 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["addf128", "divf128", "mulf128", "subf128"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16, Ty::I32],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["powif16"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F32, Ty::I32],
@@ -161,6 +170,15 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         scope: OpScope::BuiltinsPublic,
     },
     /* conversion */
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::BF16],
+            returns: &[Ty::F32],
+        },
+        c_sig: None,
+        fn_list: &["extend_bf16_f32"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F16],
@@ -215,6 +233,15 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["extend_f64_f128"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F32],
+            returns: &[Ty::BF16],
+        },
+        c_sig: None,
+        fn_list: &["narrow_f32_bf16"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F32],
@@ -224,6 +251,15 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["narrow_f32_f16"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F64],
+            returns: &[Ty::BF16],
+        },
+        c_sig: None,
+        fn_list: &["narrow_f64_bf16"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F64],
@@ -233,6 +269,15 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["narrow_f64_f16"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F128],
+            returns: &[Ty::BF16],
+        },
+        c_sig: None,
+        fn_list: &["narrow_f128_bf16"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F128],
@@ -269,6 +314,33 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["narrow_f128_f64"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["ftoi_f16_i32"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16],
+            returns: &[Ty::I64],
+        },
+        c_sig: None,
+        fn_list: &["ftoi_f16_i64"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16],
+            returns: &[Ty::I128],
+        },
+        c_sig: None,
+        fn_list: &["ftoi_f16_i128"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F32],
@@ -350,6 +422,33 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["ftoi_f128_i128"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16],
+            returns: &[Ty::U32],
+        },
+        c_sig: None,
+        fn_list: &["ftoi_f16_u32"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16],
+            returns: &[Ty::U64],
+        },
+        c_sig: None,
+        fn_list: &["ftoi_f16_u64"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::F16],
+            returns: &[Ty::U128],
+        },
+        c_sig: None,
+        fn_list: &["ftoi_f16_u128"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::F32],
@@ -431,6 +530,33 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["ftoi_f128_u128"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::I32],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["itof_i32_f16"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::I64],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["itof_i64_f16"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::I128],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["itof_i128_f16"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::I32],
@@ -512,6 +638,33 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["itof_i128_f128"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U32],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["itof_u32_f16"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U64],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["itof_u64_f16"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U128],
+            returns: &[Ty::F16],
+        },
+        c_sig: None,
+        fn_list: &["itof_u128_f16"],
+        scope: OpScope::BuiltinsPublic,
+    },
     NestedOp {
         rust_sig: Signature {
             args: &[Ty::U32],
@@ -854,6 +1007,69 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
         fn_list: &["trailing_zeros_u128"],
         scope: OpScope::BuiltinsPublic,
     },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U32],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["ffs_u32", "parity_u32", "popcount_u32"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U64],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["ffs_u64", "parity_u64", "popcount_u64"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U128],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["ffs_u128", "parity_u128", "popcount_u128"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::I64, Ty::I64],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["icmp_i64"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U64, Ty::U64],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["icmp_u64"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::I128, Ty::I128],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["icmp_i128"],
+        scope: OpScope::BuiltinsPublic,
+    },
+    NestedOp {
+        rust_sig: Signature {
+            args: &[Ty::U128, Ty::U128],
+            returns: &[Ty::I32],
+        },
+        c_sig: None,
+        fn_list: &["icmp_u128"],
+        scope: OpScope::BuiltinsPublic,
+    },
     /*******************
      * libm operations *
      *******************/
@@ -1356,6 +1572,7 @@ const ALL_OPERATIONS_NESTED: &[NestedOp] = &[
 /// A type used in a function signature.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Ty {
+    BF16,
     F16,
     F32,
     F64,
@@ -1383,7 +1600,7 @@ impl Ty {
     pub fn effective_bits(self) -> u32 {
         match self {
             Ty::Bool => 1,
-            Ty::F16 | Ty::MutF16 => 16,
+            Ty::BF16 | Ty::F16 | Ty::MutF16 => 16,
             Ty::F32 | Ty::I32 | Ty::U32 | Ty::MutF32 | Ty::MutI32 => 32,
             Ty::F64 | Ty::I64 | Ty::U64 | Ty::MutF64 => 64,
             Ty::F128 | Ty::I128 | Ty::U128 | Ty::MutF128 => 128,
@@ -1396,6 +1613,7 @@ impl Ty {
     /// How to group functions that mostly have this kind of input.
     fn group(self) -> Group {
         match self {
+            Ty::BF16 => Group::BF16,
             Ty::F16 | Ty::MutF16 => Group::F16,
             Ty::F32 | Ty::MutF32 => Group::F32,
             Ty::F64 | Ty::MutF64 => Group::F64,
@@ -1416,7 +1634,8 @@ impl Ty {
 
     fn is_float(self) -> bool {
         match self {
-            Ty::F16
+            Ty::BF16
+            | Ty::F16
             | Ty::F32
             | Ty::F64
             | Ty::F128
@@ -1442,6 +1661,7 @@ impl Ty {
 /// How a function should get grouped for things like extensive tests.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Group {
+    BF16,
     F16,
     F32,
     F64,
@@ -1452,6 +1672,7 @@ pub enum Group {
 impl fmt::Display for Ty {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
+            Ty::BF16 => "bf16",
             Ty::F16 => "f16",
             Ty::F32 => "f32",
             Ty::F64 => "f64",
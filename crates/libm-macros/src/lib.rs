@@ -141,6 +141,7 @@ fn validate(input: &mut StructuredInput) -> syn::Result<Vec<&'static MathOpInfo>
     // Replace magic mappers with a list of relevant functions.
     if let Some(map) = &mut input.fn_extra {
         for (name, group) in [
+            ("ALL_BF16", Group::BF16),
             ("ALL_F16", Group::F16),
             ("ALL_F32", Group::F32),
             ("ALL_F64", Group::F64),
@@ -229,9 +230,9 @@ fn validate(input: &mut StructuredInput) -> syn::Result<Vec<&'static MathOpInfo>
             continue;
         }
 
-        // Omit f16 and f128 functions if requested
+        // Omit f16 and f128 functions if requested. `bf16` builtins also need `f16`.
         if input.skip_f16_f128 {
-            if matches!(func.group, Group::F16 | Group::F128) {
+            if matches!(func.group, Group::BF16 | Group::F16 | Group::F128) {
                 continue;
             }
 
@@ -240,7 +241,7 @@ fn validate(input: &mut StructuredInput) -> syn::Result<Vec<&'static MathOpInfo>
                 .args
                 .iter()
                 .chain(func.rust_sig.returns.iter())
-                .any(|ty| matches!(ty, Ty::F16 | Ty::F128))
+                .any(|ty| matches!(ty, Ty::BF16 | Ty::F16 | Ty::F128))
             {
                 continue;
             }
@@ -339,8 +340,15 @@ fn expand(input: StructuredInput, fn_list: &[&MathOpInfo]) -> syn::Result<pm2::T
             meta_fields.extend(meta_iter);
         }
 
-        // Always emit f16 and f128 meta so this doesn't need to be repeated everywhere
-        if func.rust_sig.args.contains(&Ty::F16) || func.rust_sig.returns.contains(&Ty::F16) {
+        // Always emit f16 and f128 meta so this doesn't need to be repeated everywhere. `bf16`
+        // builtins use `f16` as their ABI type so they need the same gate.
+        if func
+            .rust_sig
+            .args
+            .iter()
+            .chain(func.rust_sig.returns)
+            .any(|ty| matches!(ty, Ty::BF16 | Ty::F16))
+        {
             let ts = quote! { cfg(f16_enabled) };
             meta_fields.push(ts);
         }
@@ -498,18 +506,25 @@ fn base_name(name: &str) -> &str {
     // Attempt to strip unambiguous suffixes first. This is repeated so e.g.
     // `extend_f32_f64` turns into `extend`.
     let strip = [
-        "_f16", "_f32", "_f64", "_f128", "_i32", "_i64", "_i128", "_u32", "_u64", "_u128", "f16",
-        "f32", "f64", "f128",
+        "_bf16", "_f16", "_f32", "_f64", "_f128", "_i32", "_i64", "_i128", "_u32", "_u64", "_u128",
     ];
 
     let mut any_found = false;
     let mut ret = name;
 
-    for sfx in strip {
-        if let Some(stripped) = ret.strip_suffix(sfx) {
-            ret = stripped;
-            any_found = true;
-        }
+    while let Some(stripped) = strip.iter().find_map(|sfx| ret.strip_suffix(sfx)) {
+        ret = stripped;
+        any_found = true;
+    }
+
+    // Otherwise try a float type suffix without the underscore, e.g. `sqrtf128`.
+    if !any_found
+        && let Some(stripped) = ["f16", "f32", "f64", "f128"]
+            .iter()
+            .find_map(|sfx| ret.strip_suffix(sfx))
+    {
+        ret = stripped;
+        any_found = true;
     }
 
     // Only if no suffix was stripped, try stripping the C-style float suffix.
@@ -522,6 +537,8 @@ fn base_name(name: &str) -> &str {
 
 fn ty_to_tokens(ty: Ty) -> pm2::TokenStream {
     match ty {
+        // Not a primitive, so this needs to be in scope where it is used.
+        Ty::BF16 => quote! { bf16 },
         Ty::F16 => quote! { f16 },
         Ty::F32 => quote! { f32 },
         Ty::F64 => quote! { f64 },
@@ -23,7 +23,12 @@ ETC_DIR = SELF_PATH.parent
 ROOT_DIR = ETC_DIR.parent
 
 # These files do not trigger a retest.
-IGNORED_SOURCES = ["libm/src/libm_helper.rs", "libm/src/math/support/float_traits.rs"]
+IGNORED_SOURCES = [
+    "libm/src/libm_helper.rs",
+    "libm/src/math/support/bfloat16.rs",
+    "libm/src/math/support/float80.rs",
+    "libm/src/math/support/float_traits.rs",
+]
 
 IndexTy: TypeAlias = dict[str, dict[str, Any]]
 """Type of the `index` item in rustdoc's JSON output"""
@@ -93,6 +93,7 @@ library_benchmark_group!(
         icount_bench_cosh,
         icount_bench_coshf,
         icount_bench_divf128,
+        icount_bench_divf16,
         icount_bench_divf32,
         icount_bench_divf64,
         icount_bench_eqf128,
@@ -111,6 +112,7 @@ library_benchmark_group!(
         icount_bench_expf,
         icount_bench_expm1,
         icount_bench_expm1f,
+        icount_bench_extend_bf16_f32,
         icount_bench_extend_f16_f128,
         icount_bench_extend_f16_f32,
         icount_bench_extend_f16_f64,
@@ -125,6 +127,9 @@ library_benchmark_group!(
         icount_bench_fdimf,
         icount_bench_fdimf128,
         icount_bench_fdimf16,
+        icount_bench_ffs_u128,
+        icount_bench_ffs_u32,
+        icount_bench_ffs_u64,
         icount_bench_floor,
         icount_bench_floorf,
         icount_bench_floorf128,
@@ -171,6 +176,12 @@ library_benchmark_group!(
         icount_bench_ftoi_f128_u128,
         icount_bench_ftoi_f128_u32,
         icount_bench_ftoi_f128_u64,
+        icount_bench_ftoi_f16_i128,
+        icount_bench_ftoi_f16_i32,
+        icount_bench_ftoi_f16_i64,
+        icount_bench_ftoi_f16_u128,
+        icount_bench_ftoi_f16_u32,
+        icount_bench_ftoi_f16_u64,
         icount_bench_ftoi_f32_i128,
         icount_bench_ftoi_f32_i32,
         icount_bench_ftoi_f32_i64,
@@ -197,6 +208,10 @@ library_benchmark_group!(
         icount_bench_iadd_u128,
         icount_bench_iaddo_i128,
         icount_bench_iaddo_u128,
+        icount_bench_icmp_i128,
+        icount_bench_icmp_i64,
+        icount_bench_icmp_u128,
+        icount_bench_icmp_u64,
         icount_bench_idiv_i128,
         icount_bench_idiv_i32,
         icount_bench_idiv_i64,
@@ -230,21 +245,27 @@ library_benchmark_group!(
         icount_bench_isubo_i128,
         icount_bench_isubo_u128,
         icount_bench_itof_i128_f128,
+        icount_bench_itof_i128_f16,
         icount_bench_itof_i128_f32,
         icount_bench_itof_i128_f64,
         icount_bench_itof_i32_f128,
+        icount_bench_itof_i32_f16,
         icount_bench_itof_i32_f32,
         icount_bench_itof_i32_f64,
         icount_bench_itof_i64_f128,
+        icount_bench_itof_i64_f16,
         icount_bench_itof_i64_f32,
         icount_bench_itof_i64_f64,
         icount_bench_itof_u128_f128,
+        icount_bench_itof_u128_f16,
         icount_bench_itof_u128_f32,
         icount_bench_itof_u128_f64,
         icount_bench_itof_u32_f128,
+        icount_bench_itof_u32_f16,
         icount_bench_itof_u32_f32,
         icount_bench_itof_u32_f64,
         icount_bench_itof_u64_f128,
+        icount_bench_itof_u64_f16,
         icount_bench_itof_u64_f32,
         icount_bench_itof_u64_f64,
         icount_bench_j0,
@@ -289,10 +310,13 @@ library_benchmark_group!(
         icount_bench_mulf16,
         icount_bench_mulf32,
         icount_bench_mulf64,
+        icount_bench_narrow_f128_bf16,
         icount_bench_narrow_f128_f16,
         icount_bench_narrow_f128_f32,
         icount_bench_narrow_f128_f64,
+        icount_bench_narrow_f32_bf16,
         icount_bench_narrow_f32_f16,
+        icount_bench_narrow_f64_bf16,
         icount_bench_narrow_f64_f16,
         icount_bench_narrow_f64_f32,
         icount_bench_nef128,
@@ -301,9 +325,16 @@ library_benchmark_group!(
         icount_bench_nef64,
         icount_bench_nextafter,
         icount_bench_nextafterf,
+        icount_bench_parity_u128,
+        icount_bench_parity_u32,
+        icount_bench_parity_u64,
+        icount_bench_popcount_u128,
+        icount_bench_popcount_u32,
+        icount_bench_popcount_u64,
         icount_bench_pow,
         icount_bench_powf,
         icount_bench_powif128,
+        icount_bench_powif16,
         icount_bench_powif32,
         icount_bench_powif64,
         icount_bench_remainder,
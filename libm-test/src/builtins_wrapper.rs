@@ -4,6 +4,8 @@
 //! ABI (doesn't work with libm-test traits because that changes the type signature). Wrap these
 //! to make them a bit more similar to the rest of the libm functions.
 
+use crate::bf16;
+
 macro_rules! cb_op {
     // Fully generic version
     ($mod:ident, $cb_name:ident, $new_name:ident, ($($arg:ident: $ArgTy:ty),*) -> $RetTy:ty) => {
@@ -11,6 +13,17 @@ macro_rules! cb_op {
             compiler_builtins::float::$mod::$cb_name($($arg),*)
         }
     };
+    // `bf16` builtins use `f16` as the ABI type
+    (@to_bf16 $mod:ident, $cb_name:ident, $new_name:ident, ($arg:ident: $ArgTy:ty)) => {
+        pub fn $new_name($arg: $ArgTy) -> bf16 {
+            bf16::from_bits(compiler_builtins::float::$mod::$cb_name($arg).to_bits())
+        }
+    };
+    (@from_bf16 $mod:ident, $cb_name:ident, $new_name:ident, -> $RetTy:ty) => {
+        pub fn $new_name(a: bf16) -> $RetTy {
+            compiler_builtins::float::$mod::$cb_name(f16::from_bits(a.to_bits()))
+        }
+    };
     (@int $mod:ident, $cb_name:ident, $new_name:ident, ($($arg:ident: $ArgTy:ty),*) -> $RetTy:ty) => {
         pub fn $new_name($($arg: $ArgTy),*) -> $RetTy {
             compiler_builtins::int::$mod::$cb_name($($arg),*)
@@ -118,11 +131,15 @@ cb_op!(@binop f64, mul, __muldf3, mulf64);
 #[cfg(f128_enabled)]
 cb_op!(@binop f128, mul, __multf3, mulf128);
 
+#[cfg(f16_enabled)]
+cb_op!(@binop f16, div, __divhf3, divf16);
 cb_op!(@binop f32, div, __divsf3, divf32);
 cb_op!(@binop f64, div, __divdf3, divf64);
 #[cfg(f128_enabled)]
 cb_op!(@binop f128, div, __divtf3, divf128);
 
+#[cfg(f16_enabled)]
+cb_op!(pow, __powihf2, powif16, (a: f16, b: i32) -> f16);
 cb_op!(pow, __powisf2, powif32, (a: f32, b: i32) -> f32);
 cb_op!(pow, __powidf2, powif64, (a: f64, b: i32) -> f64);
 #[cfg(f128_enabled)]
@@ -177,6 +194,8 @@ cb_op!(@cmp_unord f64, cmp, __unorddf2, unordf64);
 #[cfg(f128_enabled)]
 cb_op!(@cmp_unord f128, cmp, __unordtf2, unordf128);
 
+#[cfg(f16_enabled)]
+cb_op!(@from_bf16 extend, __extendbfsf2, extend_bf16_f32, -> f32);
 #[cfg(f16_enabled)]
 cb_op!(extend, __extendhfsf2, extend_f16_f32, (a: f16) -> f32);
 #[cfg(f16_enabled)]
@@ -192,6 +211,13 @@ cb_op!(extend, __extenddftf2, extend_f64_f128, (a: f64) -> f128);
 
 // Note that these are renamed from trunc to narrow to avoid collision with libm `trunc`.
 #[cfg(f16_enabled)]
+cb_op!(@to_bf16 trunc, __truncsfbf2, narrow_f32_bf16, (a: f32));
+#[cfg(f16_enabled)]
+cb_op!(@to_bf16 trunc, __truncdfbf2, narrow_f64_bf16, (a: f64));
+#[cfg(f16_enabled)]
+#[cfg(f128_enabled)]
+cb_op!(@to_bf16 trunc, __trunctfbf2, narrow_f128_bf16, (a: f128));
+#[cfg(f16_enabled)]
 cb_op!(trunc, __truncsfhf2, narrow_f32_f16, (a: f32) -> f16);
 #[cfg(f16_enabled)]
 cb_op!(trunc, __truncdfhf2, narrow_f64_f16, (a: f64) -> f16);
@@ -204,6 +230,12 @@ cb_op!(trunc, __trunctfsf2, narrow_f128_f32, (a: f128) -> f32);
 #[cfg(f128_enabled)]
 cb_op!(trunc, __trunctfdf2, narrow_f128_f64, (a: f128) -> f64);
 
+#[cfg(f16_enabled)]
+cb_op!(conv, __fixhfsi, ftoi_f16_i32, (a: f16) -> i32);
+#[cfg(f16_enabled)]
+cb_op!(conv, __fixhfdi, ftoi_f16_i64, (a: f16) -> i64);
+#[cfg(f16_enabled)]
+cb_op!(conv, __fixhfti, ftoi_f16_i128, (a: f16) -> i128);
 cb_op!(conv, __fixsfsi, ftoi_f32_i32, (a: f32) -> i32);
 cb_op!(conv, __fixsfdi, ftoi_f32_i64, (a: f32) -> i64);
 cb_op!(conv, __fixsfti, ftoi_f32_i128, (a: f32) -> i128);
@@ -216,6 +248,12 @@ cb_op!(conv, __fixtfsi, ftoi_f128_i32, (a: f128) -> i32);
 cb_op!(conv, __fixtfdi, ftoi_f128_i64, (a: f128) -> i64);
 #[cfg(f128_enabled)]
 cb_op!(conv, __fixtfti, ftoi_f128_i128, (a: f128) -> i128);
+#[cfg(f16_enabled)]
+cb_op!(conv, __fixunshfsi, ftoi_f16_u32, (a: f16) -> u32);
+#[cfg(f16_enabled)]
+cb_op!(conv, __fixunshfdi, ftoi_f16_u64, (a: f16) -> u64);
+#[cfg(f16_enabled)]
+cb_op!(conv, __fixunshfti, ftoi_f16_u128, (a: f16) -> u128);
 cb_op!(conv, __fixunssfsi, ftoi_f32_u32, (a: f32) -> u32);
 cb_op!(conv, __fixunssfdi, ftoi_f32_u64, (a: f32) -> u64);
 cb_op!(conv, __fixunssfti, ftoi_f32_u128, (a: f32) -> u128);
@@ -229,6 +267,12 @@ cb_op!(conv, __fixunstfdi, ftoi_f128_u64, (a: f128) -> u64);
 #[cfg(f128_enabled)]
 cb_op!(conv, __fixunstfti, ftoi_f128_u128, (a: f128) -> u128);
 
+#[cfg(f16_enabled)]
+cb_op!(conv, __floatsihf, itof_i32_f16, (a: i32) -> f16);
+#[cfg(f16_enabled)]
+cb_op!(conv, __floatdihf, itof_i64_f16, (a: i64) -> f16);
+#[cfg(f16_enabled)]
+cb_op!(conv, __floattihf, itof_i128_f16, (a: i128) -> f16);
 cb_op!(conv, __floatsisf, itof_i32_f32, (a: i32) -> f32);
 cb_op!(conv, __floatdisf, itof_i64_f32, (a: i64) -> f32);
 cb_op!(conv, __floattisf, itof_i128_f32, (a: i128) -> f32);
@@ -241,6 +285,12 @@ cb_op!(conv, __floatsitf, itof_i32_f128, (a: i32) -> f128);
 cb_op!(conv, __floatditf, itof_i64_f128, (a: i64) -> f128);
 #[cfg(f128_enabled)]
 cb_op!(conv, __floattitf, itof_i128_f128, (a: i128) -> f128);
+#[cfg(f16_enabled)]
+cb_op!(conv, __floatunsihf, itof_u32_f16, (a: u32) -> f16);
+#[cfg(f16_enabled)]
+cb_op!(conv, __floatundihf, itof_u64_f16, (a: u64) -> f16);
+#[cfg(f16_enabled)]
+cb_op!(conv, __floatuntihf, itof_u128_f16, (a: u128) -> f16);
 cb_op!(conv, __floatunsisf, itof_u32_f32, (a: u32) -> f32);
 cb_op!(conv, __floatundisf, itof_u64_f32, (a: u64) -> f32);
 cb_op!(conv, __floatuntisf, itof_u128_f32, (a: u128) -> f32);
@@ -265,6 +315,11 @@ cb_op!(@int_binop_oflow i128, addsub, __rust_i128_subo, isubo_i128);
 cb_op!(@int_binop_oflow u128, addsub, __rust_u128_addo, iaddo_u128);
 cb_op!(@int_binop_oflow u128, addsub, __rust_u128_subo, isubo_u128);
 
+cb_op!(@int cmp, __cmpdi2, icmp_i64, (a: i64, b: i64) -> i32);
+cb_op!(@int cmp, __cmpti2, icmp_i128, (a: i128, b: i128) -> i32);
+cb_op!(@int cmp, __ucmpdi2, icmp_u64, (a: u64, b: u64) -> i32);
+cb_op!(@int cmp, __ucmpti2, icmp_u128, (a: u128, b: u128) -> i32);
+
 cb_op!(@int mul, __muldi3, imul_u64, (a: u64, b: u64) -> u64);
 cb_op!(@int mul, __multi3, imul_i128, (a: i128, b: i128) -> i128);
 cb_op!(@int_binop_oflow i32, mul, __mulosi4, imulo_i32);
@@ -308,3 +363,12 @@ cb_op!(@int leading_zeros, __clzti2, leading_zeros_u128, (a: u128) -> usize);
 cb_op!(@int trailing_zeros, __ctzsi2, trailing_zeros_u32, (a: u32) -> usize);
 cb_op!(@int trailing_zeros, __ctzdi2, trailing_zeros_u64, (a: u64) -> usize);
 cb_op!(@int trailing_zeros, __ctzti2, trailing_zeros_u128, (a: u128) -> usize);
+cb_op!(@int trailing_zeros, __ffssi2, ffs_u32, (a: u32) -> i32);
+cb_op!(@int trailing_zeros, __ffsdi2, ffs_u64, (a: u64) -> i32);
+cb_op!(@int trailing_zeros, __ffsti2, ffs_u128, (a: u128) -> i32);
+cb_op!(@int popcount, __popcountsi2, popcount_u32, (a: u32) -> i32);
+cb_op!(@int popcount, __popcountdi2, popcount_u64, (a: u64) -> i32);
+cb_op!(@int popcount, __popcountti2, popcount_u128, (a: u128) -> i32);
+cb_op!(@int popcount, __paritysi2, parity_u32, (a: u32) -> i32);
+cb_op!(@int popcount, __paritydi2, parity_u64, (a: u64) -> i32);
+cb_op!(@int popcount, __parityti2, parity_u128, (a: u128) -> i32);
@@ -263,6 +263,10 @@ pub fn get_domain<F: Float, I: Int>(
         BaseName::Lshr => &EitherPrim::UNBOUNDED2[..],
         BaseName::LeadingZeros => &EitherPrim::UNBOUNDED1[..],
         BaseName::TrailingZeros => &EitherPrim::UNBOUNDED1[..],
+        BaseName::Ffs => &EitherPrim::UNBOUNDED1[..],
+        BaseName::Popcount => &EitherPrim::UNBOUNDED1[..],
+        BaseName::Parity => &EitherPrim::UNBOUNDED1[..],
+        BaseName::Icmp => &EitherPrim::UNBOUNDED2[..],
 
         // Math functions
         BaseName::Acos => &EitherPrim::INVERSE_TRIG_PERIODIC[..],
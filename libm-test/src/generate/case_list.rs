@@ -123,6 +123,11 @@ fn mulf128_cases() -> Vec<TestCase<op::mulf128::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn divf16_cases() -> Vec<TestCase<op::divf16::Routine>> {
+    cases![]
+}
+
 fn divf32_cases() -> Vec<TestCase<op::divf32::Routine>> {
     cases![]
 }
@@ -136,13 +141,18 @@ fn divf128_cases() -> Vec<TestCase<op::divf128::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn powif16_cases() -> Vec<TestCase<op::powif16::Routine>> {
+    cases![]
+}
+
 fn powif32_cases() -> Vec<TestCase<op::powif32::Routine>> {
     cases![]
 }
 
 fn powif64_cases() -> Vec<TestCase<op::powif64::Routine>> {
     cases![
-        // High error
+        // Large exponent, error accumulates with naive repeated squaring
         ((0.9999497584118668, -5858518), 6.823250355352412e127),
     ]
 }
@@ -282,6 +292,11 @@ fn unordf128_cases() -> Vec<TestCase<op::unordf128::Routine>> {
 
 /* conversion */
 
+#[cfg(f16_enabled)]
+fn extend_bf16_f32_cases() -> Vec<TestCase<op::extend_bf16_f32::Routine>> {
+    cases![]
+}
+
 #[cfg(f16_enabled)]
 fn extend_f16_f32_cases() -> Vec<TestCase<op::extend_f16_f32::Routine>> {
     cases![]
@@ -312,6 +327,22 @@ fn extend_f64_f128_cases() -> Vec<TestCase<op::extend_f64_f128::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn narrow_f32_bf16_cases() -> Vec<TestCase<op::narrow_f32_bf16::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn narrow_f64_bf16_cases() -> Vec<TestCase<op::narrow_f64_bf16::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+#[cfg(f128_enabled)]
+fn narrow_f128_bf16_cases() -> Vec<TestCase<op::narrow_f128_bf16::Routine>> {
+    cases![]
+}
+
 #[cfg(f16_enabled)]
 fn narrow_f32_f16_cases() -> Vec<TestCase<op::narrow_f32_f16::Routine>> {
     cases![]
@@ -342,6 +373,21 @@ fn narrow_f128_f64_cases() -> Vec<TestCase<op::narrow_f128_f64::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn ftoi_f16_i32_cases() -> Vec<TestCase<op::ftoi_f16_i32::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn ftoi_f16_i64_cases() -> Vec<TestCase<op::ftoi_f16_i64::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn ftoi_f16_i128_cases() -> Vec<TestCase<op::ftoi_f16_i128::Routine>> {
+    cases![]
+}
+
 fn ftoi_f32_i32_cases() -> Vec<TestCase<op::ftoi_f32_i32::Routine>> {
     cases![]
 }
@@ -381,6 +427,21 @@ fn ftoi_f128_i128_cases() -> Vec<TestCase<op::ftoi_f128_i128::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn ftoi_f16_u32_cases() -> Vec<TestCase<op::ftoi_f16_u32::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn ftoi_f16_u64_cases() -> Vec<TestCase<op::ftoi_f16_u64::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn ftoi_f16_u128_cases() -> Vec<TestCase<op::ftoi_f16_u128::Routine>> {
+    cases![]
+}
+
 fn ftoi_f32_u32_cases() -> Vec<TestCase<op::ftoi_f32_u32::Routine>> {
     cases![]
 }
@@ -420,6 +481,21 @@ fn ftoi_f128_u128_cases() -> Vec<TestCase<op::ftoi_f128_u128::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn itof_i32_f16_cases() -> Vec<TestCase<op::itof_i32_f16::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn itof_i64_f16_cases() -> Vec<TestCase<op::itof_i64_f16::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn itof_i128_f16_cases() -> Vec<TestCase<op::itof_i128_f16::Routine>> {
+    cases![]
+}
+
 fn itof_i32_f32_cases() -> Vec<TestCase<op::itof_i32_f32::Routine>> {
     cases![]
 }
@@ -459,6 +535,21 @@ fn itof_i128_f128_cases() -> Vec<TestCase<op::itof_i128_f128::Routine>> {
     cases![]
 }
 
+#[cfg(f16_enabled)]
+fn itof_u32_f16_cases() -> Vec<TestCase<op::itof_u32_f16::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn itof_u64_f16_cases() -> Vec<TestCase<op::itof_u64_f16::Routine>> {
+    cases![]
+}
+
+#[cfg(f16_enabled)]
+fn itof_u128_f16_cases() -> Vec<TestCase<op::itof_u128_f16::Routine>> {
+    cases![]
+}
+
 fn itof_u32_f32_cases() -> Vec<TestCase<op::itof_u32_f32::Routine>> {
     cases![]
 }
@@ -692,6 +783,58 @@ fn trailing_zeros_u128_cases() -> Vec<TestCase<op::trailing_zeros_u128::Routine>
     cases![]
 }
 
+fn ffs_u32_cases() -> Vec<TestCase<op::ffs_u32::Routine>> {
+    cases![]
+}
+
+fn ffs_u64_cases() -> Vec<TestCase<op::ffs_u64::Routine>> {
+    cases![]
+}
+
+fn ffs_u128_cases() -> Vec<TestCase<op::ffs_u128::Routine>> {
+    cases![]
+}
+
+fn popcount_u32_cases() -> Vec<TestCase<op::popcount_u32::Routine>> {
+    cases![]
+}
+
+fn popcount_u64_cases() -> Vec<TestCase<op::popcount_u64::Routine>> {
+    cases![]
+}
+
+fn popcount_u128_cases() -> Vec<TestCase<op::popcount_u128::Routine>> {
+    cases![]
+}
+
+fn parity_u32_cases() -> Vec<TestCase<op::parity_u32::Routine>> {
+    cases![]
+}
+
+fn parity_u64_cases() -> Vec<TestCase<op::parity_u64::Routine>> {
+    cases![]
+}
+
+fn parity_u128_cases() -> Vec<TestCase<op::parity_u128::Routine>> {
+    cases![]
+}
+
+fn icmp_i64_cases() -> Vec<TestCase<op::icmp_i64::Routine>> {
+    cases![]
+}
+
+fn icmp_u64_cases() -> Vec<TestCase<op::icmp_u64::Routine>> {
+    cases![]
+}
+
+fn icmp_i128_cases() -> Vec<TestCase<op::icmp_i128::Routine>> {
+    cases![]
+}
+
+fn icmp_u128_cases() -> Vec<TestCase<op::icmp_u128::Routine>> {
+    cases![]
+}
+
 /*******************
  * libm test cases *
  *******************/
@@ -1,6 +1,6 @@
 //! A generator that checks a handful of cases near infinities, zeros, asymptotes, and NaNs.
 
-use libm::support::{CastInto, Float, Int, MinInt};
+use libm::support::{CastInto, Float, Int, MinInt, bf16};
 
 use crate::domain::get_domain;
 use crate::generate::{KnownSize, product2, product3};
@@ -296,6 +296,7 @@ macro_rules! impl_edge_case_input {
     };
 }
 
+impl_edge_case_input!(bf16);
 #[cfg(f16_enabled)]
 impl_edge_case_input!(f16);
 impl_edge_case_input!(f32);
@@ -2,7 +2,7 @@ use std::env;
 use std::ops::RangeInclusive;
 use std::sync::LazyLock;
 
-use libm::support::{Float, Int};
+use libm::support::{Float, Int, bf16};
 use rand::distr::uniform::SampleUniform;
 use rand::distr::{Alphanumeric, StandardUniform};
 use rand::prelude::Distribution;
@@ -117,6 +117,7 @@ macro_rules! impl_random_input {
     };
 }
 
+impl_random_input!(bf16);
 #[cfg(f16_enabled)]
 impl_random_input!(f16);
 impl_random_input!(f32);
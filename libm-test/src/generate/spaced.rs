@@ -1,7 +1,7 @@
 use std::fmt;
 use std::ops::RangeInclusive;
 
-use libm::support::{Float, Int, MinInt};
+use libm::support::{Float, Int, MinInt, bf16};
 
 use crate::domain::get_domain;
 use crate::generate::{product2, product3};
@@ -260,6 +260,7 @@ macro_rules! impl_spaced_input {
     };
 }
 
+impl_spaced_input!(bf16);
 #[cfg(f16_enabled)]
 impl_spaced_input!(f16);
 impl_spaced_input!(f32);
@@ -23,7 +23,7 @@ use std::sync::LazyLock;
 use std::time::SystemTime;
 
 pub use f8_impl::{f8, hf8};
-pub use libm::support::{Float, Int, IntTy, MinInt};
+pub use libm::support::{Float, Int, IntTy, MinInt, bf16};
 pub use num::{FloatExt, linear_ints, logspace};
 pub use op::{
     Arg0, Arg1, Arg2, BaseName, Group, Identifier, MathOp, OpCFn, OpCRet, OpRustArgs, OpRustFn,
@@ -9,11 +9,12 @@ use rug::Assign;
 use rug::az::{self, Az, CheckedCast, OverflowingCast, WrappingAs};
 use rug::float::Round::Nearest;
 use rug::ops::{
-    AddAssignRound, DivAssignRound, MulAssignRound, PowAssignRound, RemAssignRound, SubAssignRound,
+    AddAssignRound, AssignRound, DivAssignRound, MulAssignRound, PowAssignRound, RemAssignRound,
+    SubAssignRound,
 };
 pub use rug::{Float as MpFloat, Integer as MpInt};
 
-use crate::{Arg0, Arg1, Arg2, Float, MathOp, Ret0, Ret1};
+use crate::{Arg0, Arg1, Arg2, Float, MathOp, Ret0, Ret1, bf16};
 
 /// Create a multiple-precision float with the correct number of bits for a concrete float type.
 fn new_mpfloat<F: Float>() -> MpFloat {
@@ -154,12 +155,14 @@ libm_macros::for_each_function! {
         copysignf128,
         copysignf16,
         divf128,
+        divf16,
         divf32,
         divf64,
         eqf128,
         eqf16,
         eqf32,
         eqf64,
+        extend_bf16_f32,
         extend_f16_f128,
         extend_f16_f32,
         extend_f16_f64,
@@ -169,7 +172,11 @@ libm_macros::for_each_function! {
         fabs,
         fabsf,
         fabsf128,
-        fabsf16,floor,
+        fabsf16,
+        ffs_u128,
+        ffs_u32,
+        ffs_u64,
+        floor,
         floorf,
         floorf128,
         floorf16,
@@ -195,6 +202,12 @@ libm_macros::for_each_function! {
         ftoi_f128_u128,
         ftoi_f128_u32,
         ftoi_f128_u64,
+        ftoi_f16_i128,
+        ftoi_f16_i32,
+        ftoi_f16_i64,
+        ftoi_f16_u128,
+        ftoi_f16_u32,
+        ftoi_f16_u64,
         ftoi_f32_i128,
         ftoi_f32_i32,
         ftoi_f32_i64,
@@ -219,6 +232,10 @@ libm_macros::for_each_function! {
         iadd_u128,
         iaddo_i128,
         iaddo_u128,
+        icmp_i128,
+        icmp_i64,
+        icmp_u128,
+        icmp_u64,
         idiv_i128,
         idiv_i32,
         idiv_i64,
@@ -252,21 +269,27 @@ libm_macros::for_each_function! {
         isubo_i128,
         isubo_u128,
         itof_i128_f128,
+        itof_i128_f16,
         itof_i128_f32,
         itof_i128_f64,
         itof_i32_f128,
+        itof_i32_f16,
         itof_i32_f32,
         itof_i32_f64,
         itof_i64_f128,
+        itof_i64_f16,
         itof_i64_f32,
         itof_i64_f64,
         itof_u128_f128,
+        itof_u128_f16,
         itof_u128_f32,
         itof_u128_f64,
         itof_u32_f128,
+        itof_u32_f16,
         itof_u32_f32,
         itof_u32_f64,
         itof_u64_f128,
+        itof_u64_f16,
         itof_u64_f32,
         itof_u64_f64,
         jn,
@@ -299,10 +322,13 @@ libm_macros::for_each_function! {
         mulf16,
         mulf32,
         mulf64,
+        narrow_f128_bf16,
         narrow_f128_f16,
         narrow_f128_f32,
         narrow_f128_f64,
+        narrow_f32_bf16,
         narrow_f32_f16,
+        narrow_f64_bf16,
         narrow_f64_f16,
         narrow_f64_f32,
         nef128,
@@ -311,9 +337,16 @@ libm_macros::for_each_function! {
         nef64,
         nextafter,
         nextafterf,
+        parity_u128,
+        parity_u32,
+        parity_u64,
+        popcount_u128,
+        popcount_u32,
+        popcount_u64,
         pow,
         powf,remquo,
         powif128,
+        powif16,
         powif32,
         powif64,
         remquof,
@@ -589,6 +622,35 @@ macro_rules! impl_op_for_ty_all {
                 }
             }
 
+            impl MpOp for crate::op::[<div $fty>]::Routine {
+                type MpTy = (MpFloat, MpFloat);
+
+                fn new_mp() -> Self::MpTy {
+                    (new_mpfloat::<Arg0<Self>>(), new_mpfloat::<Arg1<Self>>())
+                }
+
+                fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
+                    this.0.assign(input.0);
+                    this.1.assign(input.1);
+                    let ord = this.0.div_assign_round(&this.1, Nearest);
+                    prep_retval::<Self::RustRet>(&mut this.0, ord)
+                }
+            }
+
+            impl MpOp for crate::op::[<powi $fty>]::Routine {
+                type MpTy = MpFloat;
+
+                fn new_mp() -> Self::MpTy {
+                    new_mpfloat::<Arg0<Self>>()
+                }
+
+                fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
+                    this.assign(input.0);
+                    let ord = this.pow_assign_round(input.1, Nearest);
+                    prep_retval::<Self::RustRet>(this, ord)
+                }
+            }
+
             impl MpOp for crate::op::[<eq $fty>]::Routine {
                 type MpTy = (MpFloat, MpFloat);
 
@@ -826,25 +888,23 @@ macro_rules! impl_op_for_ty_all {
     };
 }
 
-macro_rules! impl_op_for_ty_no_f16 {
-    ($fty:ty, $suffix:literal) => {
+macro_rules! impl_extend_trunc {
+    ($narrow:ty, $wide:ty) => {
         paste::paste! {
-            impl MpOp for crate::op::[<div $fty>]::Routine {
-                type MpTy = (MpFloat, MpFloat);
+            impl MpOp for crate::op::[<extend_ $narrow _ $wide>]::Routine {
+                type MpTy = MpFloat;
 
                 fn new_mp() -> Self::MpTy {
-                    (new_mpfloat::<Arg0<Self>>(), new_mpfloat::<Arg1<Self>>())
+                    new_mpfloat::<Arg0<Self>>()
                 }
 
                 fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
-                    this.0.assign(input.0);
-                    this.1.assign(input.1);
-                    let ord = this.0.div_assign_round(&this.1, Nearest);
-                    prep_retval::<Self::RustRet>(&mut this.0, ord)
+                    this.assign(input.0);
+                    prep_retval::<Self::RustRet>(this, Ordering::Equal)
                 }
             }
 
-            impl MpOp for crate::op::[<powi $fty>]::Routine {
+            impl MpOp for crate::op::[<narrow_ $wide _ $narrow>]::Routine {
                 type MpTy = MpFloat;
 
                 fn new_mp() -> Self::MpTy {
@@ -853,40 +913,48 @@ macro_rules! impl_op_for_ty_no_f16 {
 
                 fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
                     this.assign(input.0);
-                    let ord = this.pow_assign_round(input.1, Nearest);
-                    prep_retval::<Self::RustRet>(this, ord)
+                    prep_retval::<Self::RustRet>(this, Ordering::Equal)
                 }
             }
         }
     };
 }
 
-macro_rules! impl_extend_trunc {
-    ($narrow:ty, $wide:ty) => {
+/// `rug` has no `bf16` conversions so these go through `f32`, which can represent every `bf16`
+/// exactly.
+macro_rules! impl_extend_trunc_bf16 {
+    (@extend $wide:ty) => {
         paste::paste! {
-            impl MpOp for crate::op::[<extend_ $narrow _ $wide>]::Routine {
+            impl MpOp for crate::op::[<extend_bf16_ $wide>]::Routine {
                 type MpTy = MpFloat;
 
                 fn new_mp() -> Self::MpTy {
-                    new_mpfloat::<Arg0<Self>>()
+                    new_mpfloat::<bf16>()
                 }
 
                 fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
-                    this.assign(input.0);
+                    this.assign(input.0.to_f32());
                     prep_retval::<Self::RustRet>(this, Ordering::Equal)
                 }
             }
-
-            impl MpOp for crate::op::[<narrow_ $wide _ $narrow>]::Routine {
-                type MpTy = MpFloat;
+        }
+    };
+    (@narrow $wide:ty) => {
+        paste::paste! {
+            impl MpOp for crate::op::[<narrow_ $wide _bf16>]::Routine {
+                type MpTy = (MpFloat, MpFloat);
 
                 fn new_mp() -> Self::MpTy {
-                    new_mpfloat::<Arg0<Self>>()
+                    (new_mpfloat::<Arg0<Self>>(), new_mpfloat::<bf16>())
                 }
 
                 fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
-                    this.assign(input.0);
-                    prep_retval::<Self::RustRet>(this, Ordering::Equal)
+                    this.0.assign(input.0);
+                    let ord = this.1.assign_round(&this.0, Nearest);
+                    // `subnormalize_ieee` only knows the standard formats. MPFR significands are
+                    // in `[0.5, 1)` so its minimum normal exponent is one higher than ours.
+                    this.1.subnormalize_round(bf16::EXP_MIN + 1, ord, Nearest);
+                    bf16::from_f32(this.1.to_f32())
                 }
             }
         }
@@ -938,11 +1006,6 @@ macro_rules! impl_ftoi_itof {
 impl_op_for_ty!(f32, "f");
 impl_op_for_ty!(f64, "");
 
-impl_op_for_ty_no_f16!(f32, "f");
-impl_op_for_ty_no_f16!(f64, "");
-#[cfg(f128_enabled)]
-impl_op_for_ty_no_f16!(f128, "f128");
-
 #[cfg(f16_enabled)]
 impl_op_for_ty_all!(f16, "f16");
 impl_op_for_ty_all!(f32, "f");
@@ -963,6 +1026,28 @@ impl_extend_trunc!(f32, f128);
 #[cfg(f128_enabled)]
 impl_extend_trunc!(f64, f128);
 
+#[cfg(f16_enabled)]
+impl_extend_trunc_bf16!(@extend f32);
+#[cfg(f16_enabled)]
+impl_extend_trunc_bf16!(@narrow f32);
+#[cfg(f16_enabled)]
+impl_extend_trunc_bf16!(@narrow f64);
+#[cfg(f16_enabled)]
+#[cfg(f128_enabled)]
+impl_extend_trunc_bf16!(@narrow f128);
+
+#[cfg(f16_enabled)]
+impl_ftoi_itof!(f16, i32);
+#[cfg(f16_enabled)]
+impl_ftoi_itof!(f16, i64);
+#[cfg(f16_enabled)]
+impl_ftoi_itof!(f16, i128);
+#[cfg(f16_enabled)]
+impl_ftoi_itof!(f16, u32);
+#[cfg(f16_enabled)]
+impl_ftoi_itof!(f16, u64);
+#[cfg(f16_enabled)]
+impl_ftoi_itof!(f16, u128);
 impl_ftoi_itof!(f32, i32);
 impl_ftoi_itof!(f32, i64);
 impl_ftoi_itof!(f32, i128);
@@ -1198,6 +1283,45 @@ macro_rules! impl_unsigned_int_ops {
                     this.find_one(0).unwrap_or(Arg0::<Self>::BITS).try_into().unwrap()
                 }
             }
+
+            impl MpOp for crate::op::[<ffs_ $ity>]::Routine {
+                type MpTy = MpInt;
+
+                fn new_mp() -> Self::MpTy {
+                    MpInt::new()
+                }
+
+                fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
+                    this.assign(input.0);
+                    this.find_one(0).map_or(0, |i| i + 1).try_into().unwrap()
+                }
+            }
+
+            impl MpOp for crate::op::[<popcount_ $ity>]::Routine {
+                type MpTy = MpInt;
+
+                fn new_mp() -> Self::MpTy {
+                    MpInt::new()
+                }
+
+                fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
+                    this.assign(input.0);
+                    this.count_ones().unwrap().try_into().unwrap()
+                }
+            }
+
+            impl MpOp for crate::op::[<parity_ $ity>]::Routine {
+                type MpTy = MpInt;
+
+                fn new_mp() -> Self::MpTy {
+                    MpInt::new()
+                }
+
+                fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
+                    this.assign(input.0);
+                    (this.count_ones().unwrap() % 2).try_into().unwrap()
+                }
+            }
         }
     };
 }
@@ -1310,6 +1434,31 @@ macro_rules! impl_u128_i128_int_ops {
 impl_u128_i128_int_ops!(i128);
 impl_u128_i128_int_ops!(u128);
 
+macro_rules! impl_int_cmp_ops {
+    ($ity:ty) => {
+        paste::paste! {
+            impl MpOp for crate::op::[<icmp_ $ity>]::Routine {
+                type MpTy = MpInt;
+
+                fn new_mp() -> Self::MpTy {
+                    MpInt::new()
+                }
+
+                fn run(this: &mut Self::MpTy, input: Self::RustArgs) -> Self::RustRet {
+                    this.assign(input.0);
+                    // The libgcc convention is 0, 1 and 2 for less, equal and greater.
+                    (*this).partial_cmp(&input.1).unwrap() as i32 + 1
+                }
+            }
+        }
+    };
+}
+
+impl_int_cmp_ops!(i64);
+impl_int_cmp_ops!(u64);
+impl_int_cmp_ops!(i128);
+impl_int_cmp_ops!(u128);
+
 impl MpOp for crate::op::imul_u64::Routine {
     type MpTy = MpInt;
 
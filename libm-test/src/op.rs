@@ -18,7 +18,7 @@ use std::panic::{RefUnwindSafe, UnwindSafe};
 
 pub use api_list_common::{ALL_OPERATIONS, Group, MathOpInfo, Ty};
 
-use crate::{CheckOutput, Tuple, TupleCall};
+use crate::{CheckOutput, Tuple, TupleCall, bf16};
 
 /// An enum representing each possible symbol name (`sin`, `sinf`, `sinl`, etc).
 #[libm_macros::function_enum(BaseName)]
@@ -6,7 +6,7 @@ use CheckBasis::{Mpfr, Musl};
 use Identifier as Id;
 use libm::support::CastFrom;
 
-use crate::{BaseName, CheckBasis, CheckCtx, Float, Identifier, Int, TestResult};
+use crate::{BaseName, CheckBasis, CheckCtx, Float, Identifier, Int, TestResult, bf16};
 
 /// Type implementing [`IgnoreCase`].
 pub struct SpecialCase;
@@ -18,10 +18,8 @@ pub fn default_ulp(ctx: &CheckCtx) -> Option<u32> {
     let mut ulp = match ctx.base_name {
         // Basic arithmetic needs to always be precise.
         Bn::Add | Bn::Sub | Bn::Mul | Bn::Div => 0,
-        // FIXME(correctness): we need a better powi implementation (though this is no worse
-        // than C).
-        Bn::Powi if ctx.fn_ident == Id::Powif64 => 500_000,
-        Bn::Powi => 1000,
+        // Computed in extended precision and rounded once, except for subnormal results.
+        Bn::Powi => 1,
 
         // Operations that only return non-float results
         Bn::Eq | Bn::Ne | Bn::Gt | Bn::Ge | Bn::Lt | Bn::Le | Bn::Unord | Bn::Ilogb => return None,
@@ -32,6 +30,10 @@ pub fn default_ulp(ctx: &CheckCtx) -> Option<u32> {
         | Bn::Lshr
         | Bn::LeadingZeros
         | Bn::TrailingZeros
+        | Bn::Ffs
+        | Bn::Popcount
+        | Bn::Parity
+        | Bn::Icmp
         | Bn::Iadd
         | Bn::Iaddo
         | Bn::Isub
@@ -249,6 +251,8 @@ pub trait MaybeOverride<Input> {
     }
 }
 
+impl MaybeOverride<(bf16,)> for SpecialCase {}
+
 #[cfg(f16_enabled)]
 impl MaybeOverride<(f16,)> for SpecialCase {}
 
@@ -148,8 +148,8 @@ pub enum GeneratorKind {
 
 /// A list of all functions that should get extensive tests, as configured by environment variable.
 ///
-/// This also supports the special test name `all` to run all tests, as well as `all_f16`,
-/// `all_f32`, `all_f64`, and `all_f128` to run all tests for a specific float type.
+/// This also supports the special test name `all` to run all tests, as well as `all_bf16`,
+/// `all_f16`, `all_f32`, `all_f64`, and `all_f128` to run all tests for a specific float type.
 static EXTENSIVE: LazyLock<Vec<Identifier>> = LazyLock::new(|| {
     let var = env::var(EXTENSIVE_ENV).unwrap_or_default();
     let list = var.split(",").filter(|s| !s.is_empty()).collect::<Vec<_>>();
@@ -166,6 +166,7 @@ static EXTENSIVE: LazyLock<Vec<Identifier>> = LazyLock::new(|| {
     for item in list {
         match item {
             "all" => ret = Identifier::ALL.to_owned(),
+            "all_bf16" => append_ty_ops(&mut ret, Group::BF16),
             "all_f16" => append_ty_ops(&mut ret, Group::F16),
             "all_f32" => append_ty_ops(&mut ret, Group::F32),
             "all_f64" => append_ty_ops(&mut ret, Group::F64),
@@ -465,7 +466,7 @@ pub fn skip_extensive_test(ctx: &CheckCtx) -> bool {
     !t_env.should_run_extensive
 }
 
-/// The number of iterations to run for `u256` fuzz tests.
+/// The number of iterations to run for `u256` and double-double fuzz tests.
 pub fn bigint_fuzz_iteration_count() -> u64 {
     if !cfg!(optimizations_enabled) {
         return 1000;
@@ -15,7 +15,7 @@ use libm::support::{DisplayHex, Hex};
 
 use crate::precision::CheckAction;
 use crate::{
-    CheckBasis, CheckCtx, Float, GeneratorKind, Int, MaybeOverride, SpecialCase, TestResult,
+    CheckBasis, CheckCtx, Float, GeneratorKind, Int, MaybeOverride, SpecialCase, TestResult, bf16,
 };
 
 /// Trait for calling a function with a tuple as arguments.
@@ -359,7 +359,7 @@ where
     res.with_context(|| make_error_message(input, expected, actual, "", ""))
 }
 
-impl_float!(f32, f64);
+impl_float!(bf16, f32, f64);
 
 #[cfg(f16_enabled)]
 impl_float!(f16);
@@ -0,0 +1,244 @@
+//! Test the `_BitInt` division intrinsics against GMP, for widths beyond the 128-bit routines.
+
+#![cfg(feature = "build-mpfr")]
+
+use compiler_builtins::int::sdiv::{__divei4, __modei4};
+use compiler_builtins::int::udiv::{__udivei4, __umodei4};
+use libm_test::bigint_fuzz_iteration_count;
+use libm_test::generate::random::SEED;
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rug::Integer;
+use rug::integer::Order;
+
+type Intrinsic = unsafe extern "C" fn(*mut u32, *mut u32, *mut u32, u32);
+
+/// Pick a width, favoring multiples of 32 and one more than a multiple of 32 since those have a
+/// full or nearly empty top word.
+fn random_bits(rng: &mut ChaCha8Rng) -> u32 {
+    match rng.random_range(0..4) {
+        0 => rng.random_range(5..=128) * 32,
+        1 => rng.random_range(5..=127) * 32 + 1,
+        _ => rng.random_range(129..=4096),
+    }
+}
+
+/// A random value of `bits` bits, least significant word first. The magnitude varies so that
+/// there are leading zero words and small values, and the bits above `bits` are set randomly to
+/// check they are ignored.
+fn random_words(rng: &mut ChaCha8Rng, bits: u32) -> Vec<u32> {
+    let words = bits.div_ceil(32) as usize;
+    let len = match rng.random_range(0..4) {
+        0 => 1,
+        1 => words,
+        _ => rng.random_range(1..=words),
+    };
+
+    let mut x = vec![0u32; words];
+    for w in &mut x[..len] {
+        *w = rng.random();
+    }
+    if rng.random() {
+        // Long runs of ones or zeros exercise the quotient estimate corrections.
+        let fill = if rng.random() { u32::MAX } else { 0 };
+        let start = rng.random_range(0..len);
+        x[start..len - 1].fill(fill);
+    }
+    if !bits.is_multiple_of(32) && len == words {
+        x[words - 1] |= rng.random::<u32>() << (bits % 32);
+    }
+    x
+}
+
+/// Call `f` with `a` and `b` in the target's word order, returning the result least significant
+/// word first.
+fn call(f: Intrinsic, a: &[u32], b: &[u32], bits: u32) -> Vec<u32> {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    let mut res = vec![0u32; a.len()];
+    if cfg!(target_endian = "big") {
+        a.reverse();
+        b.reverse();
+    }
+
+    unsafe { f(res.as_mut_ptr(), a.as_mut_ptr(), b.as_mut_ptr(), bits) };
+
+    if cfg!(target_endian = "big") {
+        res.reverse();
+    }
+    res
+}
+
+/// The words of `x` in two's complement.
+fn to_words(x: &Integer, words: usize) -> Vec<u32> {
+    let mut res = vec![0u32; words];
+    x.clone()
+        .keep_bits(words as u32 * 32)
+        .write_digits(&mut res, Order::Lsf);
+    res
+}
+
+fn check(name: &str, f: Intrinsic, a: &[u32], b: &[u32], bits: u32, expected: &Integer) {
+    let actual = call(f, a, b, bits);
+    let expected = to_words(expected, actual.len());
+    assert!(
+        actual == expected,
+        "{name} failed for _BitInt({bits})\n\
+        a:        {a:x?}\n\
+        b:        {b:x?}\n\
+        actual:   {actual:x?}\n\
+        expected: {expected:x?}",
+    );
+}
+
+#[test]
+fn bitint_div_unsigned() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+
+    for _ in 0..bigint_fuzz_iteration_count() / 50 {
+        let bits = random_bits(&mut rng);
+        let mut a = random_words(&mut rng, bits);
+        let mut b = random_words(&mut rng, bits);
+        if rng.random_range(0..8) == 0 {
+            // Also check dividends smaller than the divisor.
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        let ba = Integer::from_digits(&a, Order::Lsf).keep_bits(bits);
+        let bb = Integer::from_digits(&b, Order::Lsf).keep_bits(bits);
+        if bb == 0 {
+            continue;
+        }
+
+        check(
+            "__udivei4",
+            __udivei4,
+            &a,
+            &b,
+            bits,
+            &Integer::from(&ba / &bb),
+        );
+        check(
+            "__umodei4",
+            __umodei4,
+            &a,
+            &b,
+            bits,
+            &Integer::from(&ba % &bb),
+        );
+    }
+}
+
+#[test]
+fn bitint_div_signed() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+
+    for _ in 0..bigint_fuzz_iteration_count() / 50 {
+        let bits = random_bits(&mut rng);
+        let mut a = random_words(&mut rng, bits);
+        let mut b = random_words(&mut rng, bits);
+        if rng.random_range(0..8) == 0 {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        // Random signs, which otherwise only come from the top bit of the full width values.
+        for x in [&mut a, &mut b] {
+            if rng.random() {
+                let words = x.len();
+                let neg = -Integer::from_digits(x, Order::Lsf);
+                *x = to_words(&neg, words);
+            }
+        }
+
+        let ba = Integer::from_digits(&a, Order::Lsf).keep_signed_bits(bits);
+        let bb = Integer::from_digits(&b, Order::Lsf).keep_signed_bits(bits);
+        let min = -(Integer::from(1) << (bits - 1));
+        if bb == 0 || (ba == min && bb == -1) {
+            continue;
+        }
+
+        // Rust and GMP both truncate toward zero.
+        check(
+            "__divei4",
+            __divei4,
+            &a,
+            &b,
+            bits,
+            &Integer::from(&ba / &bb),
+        );
+        check(
+            "__modei4",
+            __modei4,
+            &a,
+            &b,
+            bits,
+            &Integer::from(&ba % &bb),
+        );
+    }
+}
+
+#[test]
+fn bitint_div_edge_cases() {
+    for bits in [129u32, 160, 255, 256, 257, 1000, 4096] {
+        let words = bits.div_ceil(32) as usize;
+        let one = Integer::from(1);
+        let umax = (Integer::from(1) << bits) - 1u32;
+        let smax = (Integer::from(1) << (bits - 1)) - 1u32;
+        let smin = -(Integer::from(1) << (bits - 1));
+        let vals = [
+            Integer::ZERO,
+            one.clone(),
+            Integer::from(u32::MAX),
+            Integer::from(1) << 32,
+            Integer::from(u64::MAX),
+            Integer::from(1) << (bits - 2),
+            smax.clone(),
+            umax,
+        ];
+
+        for ba in &vals {
+            for bb in vals.iter().skip(1) {
+                let a = to_words(ba, words);
+                let b = to_words(bb, words);
+                check(
+                    "__udivei4",
+                    __udivei4,
+                    &a,
+                    &b,
+                    bits,
+                    &Integer::from(ba / bb),
+                );
+                check(
+                    "__umodei4",
+                    __umodei4,
+                    &a,
+                    &b,
+                    bits,
+                    &Integer::from(ba % bb),
+                );
+            }
+        }
+
+        let svals = [
+            Integer::ZERO,
+            one.clone(),
+            -one.clone(),
+            Integer::from(i64::MIN),
+            smax,
+            smin.clone() + 1u32,
+            smin.clone(),
+        ];
+
+        for ba in &svals {
+            for bb in svals.iter().skip(1) {
+                if *ba == smin && *bb == -1 {
+                    continue;
+                }
+                let a = to_words(ba, words);
+                let b = to_words(bb, words);
+                check("__divei4", __divei4, &a, &b, bits, &Integer::from(ba / bb));
+                check("__modei4", __modei4, &a, &b, bits, &Integer::from(ba % bb));
+            }
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Test complex multiplication and division against MPFR, and the C Annex G special cases.
+
+#![cfg(feature = "build-mpfr")]
+#![cfg_attr(f16_enabled, feature(f16))]
+#![cfg_attr(f128_enabled, feature(f128))]
+
+use std::ops::Range;
+
+use compiler_builtins::float::complex::*;
+use libm::support::CastFrom;
+use libm_test::generate::random::SEED;
+use libm_test::{Float, bigint_fuzz_iteration_count};
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rug::Assign;
+use rug::float::Special;
+
+type BigFloat = rug::Float;
+type Op<F> = fn(F, F, F, F) -> Complex<F>;
+
+/// More than enough for rounding errors in the reference to not matter.
+const PREC: u32 = 512;
+
+/// Wrap a builtin as an `Op`, undoing the i386 return convention.
+macro_rules! op {
+    ($f:ident: $ty:ty) => {{
+        let op: Op<$ty> = |a, b, c, d| {
+            let z = $f(a, b, c, d);
+            #[cfg(target_arch = "x86")]
+            let z = <$ty as X86ComplexRet>::from_ret(z);
+            z
+        };
+        op
+    }};
+}
+
+trait TestFloat: Float {
+    /// Exponents that keep products and quotients clear of overflow and subnormals.
+    const EXP_RANGE: Range<i32>;
+
+    fn to_big(self) -> BigFloat;
+}
+
+#[cfg(f16_enabled)]
+impl TestFloat for f16 {
+    const EXP_RANGE: Range<i32> = -6..6;
+
+    fn to_big(self) -> BigFloat {
+        BigFloat::with_val(PREC, f64::from(self))
+    }
+}
+
+impl TestFloat for f32 {
+    const EXP_RANGE: Range<i32> = -60..60;
+
+    fn to_big(self) -> BigFloat {
+        BigFloat::with_val(PREC, self)
+    }
+}
+
+impl TestFloat for f64 {
+    const EXP_RANGE: Range<i32> = -500..500;
+
+    fn to_big(self) -> BigFloat {
+        BigFloat::with_val(PREC, self)
+    }
+}
+
+#[cfg(f128_enabled)]
+impl TestFloat for f128 {
+    const EXP_RANGE: Range<i32> = -8000..8000;
+
+    /// Built from the parts since going through `f64` would lose precision and range.
+    fn to_big(self) -> BigFloat {
+        if self.is_nan() {
+            return BigFloat::with_val(PREC, Special::Nan);
+        }
+
+        let abs = if self.is_infinite() {
+            BigFloat::with_val(PREC, Special::Infinity)
+        } else {
+            let mut sig = self.to_bits() & Self::SIG_MASK;
+            let mut exp = self.ex() as i32;
+            if exp == 0 {
+                // Subnormals have the same scale as the smallest normal.
+                exp = 1;
+            } else {
+                sig |= Self::IMPLICIT_BIT;
+            }
+            BigFloat::with_val(PREC, sig) << (exp - Self::EXP_BIAS as i32 - Self::SIG_BITS as i32)
+        };
+        if self.is_sign_negative() { -abs } else { abs }
+    }
+}
+
+fn random_float<F: TestFloat>(rng: &mut ChaCha8Rng) -> F
+where
+    F::Int: CastFrom<u128>,
+{
+    let sig = F::Int::cast_from_lossy(rng.random::<u128>()) & F::SIG_MASK;
+    let exp = rng.random_range(F::EXP_RANGE);
+    let biased = u32::try_from(exp + F::EXP_BIAS as i32).unwrap();
+    F::from_parts(rng.random(), biased, sig)
+}
+
+/// `re * re + im * im`.
+fn norm_sqr(re: &BigFloat, im: &BigFloat) -> BigFloat {
+    let mut res = BigFloat::with_val(PREC, re.square_ref());
+    res += BigFloat::with_val(PREC, im.square_ref());
+    res
+}
+
+/// Check that `|res - expected| <= ulp * eps * |expected|`. The bound is normwise since a
+/// component can be far from exact if it cancelled.
+#[track_caller]
+fn assert_close<F: TestFloat>(
+    res: Complex<F>,
+    expected: (&BigFloat, &BigFloat),
+    ulp: u32,
+    msg: impl Fn() -> String,
+) {
+    let err_re = res.re.to_big() - expected.0;
+    let err_im = res.im.to_big() - expected.1;
+    let err = norm_sqr(&err_re, &err_im).sqrt();
+    let norm = norm_sqr(expected.0, expected.1).sqrt();
+    let bound = (norm * ulp) >> F::SIG_BITS;
+    assert!(
+        err <= bound,
+        "{}\nresult:   {res:?}\nexpected: ({}, {})\nerror:    {err:e}",
+        msg(),
+        expected.0.to_f64(),
+        expected.1.to_f64(),
+    );
+}
+
+fn check_mul<F: TestFloat>(op: Op<F>)
+where
+    F::Int: CastFrom<u128>,
+{
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    let mut re = BigFloat::new(PREC);
+    let mut im = BigFloat::new(PREC);
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let [a, b, c, d] = [(); 4].map(|_| random_float::<F>(&mut rng));
+        let msg = || format!("({a:?}, {b:?}) * ({c:?}, {d:?})");
+
+        re.assign(a.to_big() * c.to_big() - b.to_big() * d.to_big());
+        im.assign(a.to_big() * d.to_big() + b.to_big() * c.to_big());
+        assert_close(op(a, b, c, d), (&re, &im), 4, msg);
+    }
+}
+
+fn check_div<F: TestFloat>(op: Op<F>)
+where
+    F::Int: CastFrom<u128>,
+{
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    let mut re = BigFloat::new(PREC);
+    let mut im = BigFloat::new(PREC);
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let [a, b, c, d] = [(); 4].map(|_| random_float::<F>(&mut rng));
+        let msg = || format!("({a:?}, {b:?}) / ({c:?}, {d:?})");
+
+        let denom = norm_sqr(&c.to_big(), &d.to_big());
+        re.assign(a.to_big() * c.to_big() + b.to_big() * d.to_big());
+        re /= &denom;
+        im.assign(b.to_big() * c.to_big() - a.to_big() * d.to_big());
+        im /= &denom;
+        assert_close(op(a, b, c, d), (&re, &im), 8, msg);
+    }
+}
+
+#[test]
+#[cfg(f16_enabled)]
+fn mp_mulhc3() {
+    check_mul(op!(__mulhc3: f16));
+}
+
+#[test]
+fn mp_mulsc3() {
+    check_mul(op!(__mulsc3: f32));
+}
+
+#[test]
+fn mp_muldc3() {
+    check_mul(op!(__muldc3: f64));
+}
+
+#[test]
+#[cfg(f128_enabled)]
+fn mp_multc3() {
+    check_mul(op!(__multc3: f128));
+}
+
+#[test]
+#[cfg(f16_enabled)]
+fn mp_divhc3() {
+    check_div(op!(__divhc3: f16));
+}
+
+#[test]
+fn mp_divsc3() {
+    check_div(op!(__divsc3: f32));
+}
+
+#[test]
+fn mp_divdc3() {
+    check_div(op!(__divdc3: f64));
+}
+
+#[test]
+#[cfg(f128_enabled)]
+fn mp_divtc3() {
+    check_div(op!(__divtc3: f128));
+}
+
+fn is_inf<F: Float>(z: Complex<F>) -> bool {
+    z.re.is_infinite() || z.im.is_infinite()
+}
+
+fn check_special<F: Float>(mul: Op<F>, div: Op<F>) {
+    let inf = F::INFINITY;
+    let nan = F::NAN;
+    let one = F::ONE;
+    let zero = F::ZERO;
+
+    // An infinity times a nonzero number is an infinity, even if the naive result is NaN.
+    assert!(is_inf(mul(inf, nan, one, zero)));
+    assert!(is_inf(mul(one, one, nan, inf)));
+    assert!(is_inf(mul(inf, inf, one, one)));
+
+    // Products that overflow and cancel are still infinite.
+    assert!(is_inf(mul(F::MAX, F::MAX, F::MAX, -F::MAX)));
+
+    // A nonzero number divided by zero is an infinity.
+    assert!(is_inf(div(one, zero, zero, zero)));
+    assert!(is_inf(div(one, one, zero, zero)));
+
+    // An infinity divided by a finite number is an infinity.
+    assert!(is_inf(div(inf, nan, one, one)));
+    assert!(is_inf(div(nan, -inf, F::MAX, zero)));
+
+    // A finite number divided by an infinity is zero.
+    let z = div(one, one, inf, nan);
+    assert_eq!((z.re, z.im), (zero, zero));
+    let z = div(one, -one, inf, inf);
+    assert_eq!((z.re, z.im), (zero, zero));
+
+    // NaN in, NaN out if there is nothing to recover.
+    let z = mul(nan, nan, one, one);
+    assert!(z.re.is_nan() && z.im.is_nan());
+    let z = div(zero, zero, zero, zero);
+    assert!(z.re.is_nan() && z.im.is_nan());
+}
+
+#[test]
+#[cfg(f16_enabled)]
+fn special_values_f16() {
+    check_special(op!(__mulhc3: f16), op!(__divhc3: f16));
+}
+
+#[test]
+fn special_values_f32() {
+    check_special(op!(__mulsc3: f32), op!(__divsc3: f32));
+}
+
+#[test]
+fn special_values_f64() {
+    check_special(op!(__muldc3: f64), op!(__divdc3: f64));
+}
+
+#[test]
+#[cfg(f128_enabled)]
+fn special_values_f128() {
+    check_special(op!(__multc3: f128), op!(__divtc3: f128));
+}
+
+#[test]
+fn div_scaling() {
+    // `c * c + d * d` would overflow or underflow without scaling.
+    let big = 2.0f64.powi(998);
+    let small = 2.0f64.powi(-998);
+
+    let z = __divdc3(big, big, big, big);
+    assert_eq!((z.re, z.im), (1.0, 0.0));
+    let z = __divdc3(small, small, small, small);
+    assert_eq!((z.re, z.im), (1.0, 0.0));
+    let z = __divdc3(1.0, 0.0, big, big);
+    assert_eq!((z.re, z.im), (small / 2.0, -small / 2.0));
+    let z = __divdc3(1.0, 0.0, small, small);
+    assert_eq!((z.re, z.im), (big / 2.0, -big / 2.0));
+}
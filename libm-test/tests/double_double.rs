@@ -0,0 +1,333 @@
+//! Test the IBM double-double (`ppc_fp128`) builtins against MPFR. These are only exported on
+//! PowerPC, but the algorithms are the same on any host.
+
+#![cfg(feature = "build-mpfr")]
+
+use std::ops::Range;
+
+use compiler_builtins::float::double_double::*;
+use libm_test::generate::random::SEED;
+use libm_test::{Float, bigint_fuzz_iteration_count};
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rug::float::Round;
+use rug::{Assign, Integer};
+
+type BigFloat = rug::Float;
+
+/// Enough precision to hold the sum of any two `f64`s exactly, and more than enough for rounding
+/// errors in the reference to not matter.
+const PREC: u32 = 2200;
+
+/// Only values that stay clear of overflow and subnormals get the error bounds.
+const EXP_RANGE: Range<i32> = -400..400;
+
+/// An `f64` with a random significand and sign.
+fn random_f64(rng: &mut ChaCha8Rng, exp: i32) -> f64 {
+    let sig = rng.random::<u64>() & f64::SIG_MASK;
+    let biased = u32::try_from(exp + f64::EXP_BIAS as i32).unwrap();
+    f64::from_parts(rng.random(), biased, sig)
+}
+
+/// An `f64` below the rounding precision of `hi`, so it can be used as the low part. Zero if that
+/// would be subnormal.
+fn random_lo(rng: &mut ChaCha8Rng, hi: f64) -> f64 {
+    let exp = hi.exp_unbiased() - 53 - rng.random_range(0..64);
+    if exp < f64::EXP_MIN {
+        0.0
+    } else {
+        random_f64(rng, exp)
+    }
+}
+
+/// A random canonical double-double with the exponent of `hi` in `exp`. `lo` is zero or has a
+/// random exponent below `hi`.
+fn random_dd(rng: &mut ChaCha8Rng, exp: Range<i32>) -> DoubleDouble {
+    let exp = rng.random_range(exp);
+    let hi = random_f64(rng, exp);
+    let lo = match rng.random_range(0..8) {
+        0 => 0.0,
+        _ => random_lo(rng, hi),
+    };
+    normalize(hi, lo)
+}
+
+fn normalize(hi: f64, lo: f64) -> DoubleDouble {
+    let s = hi + lo;
+    DoubleDouble {
+        hi: s,
+        lo: lo - (s - hi),
+    }
+}
+
+fn big(x: DoubleDouble) -> BigFloat {
+    let mut res = BigFloat::with_val(PREC, x.hi);
+    res += x.lo;
+    res
+}
+
+/// Check that `res` is canonical and within `ulp` units of `2^-106` relative to `expected`.
+#[track_caller]
+fn assert_close(res: DoubleDouble, expected: &BigFloat, ulp: u32, msg: impl Fn() -> String) {
+    assert_eq!(res.hi + res.lo, res.hi, "not canonical: {res:?}, {}", msg());
+
+    let mut err = big(res);
+    err -= expected;
+    err.abs_mut();
+    let mut bound = BigFloat::with_val(PREC, expected.abs_ref());
+    bound *= ulp;
+    bound >>= 106;
+    assert!(
+        err <= bound,
+        "{}\nresult:   {res:?}\nexpected: {expected}\nerror:    {err:e}",
+        msg()
+    );
+}
+
+/// Pairs of operands, with some of them close to cancelling.
+fn random_pair(rng: &mut ChaCha8Rng) -> (DoubleDouble, DoubleDouble) {
+    let a = random_dd(rng, EXP_RANGE);
+    let b = match rng.random_range(0..4) {
+        0 => {
+            // Nearly `-a`, so the high parts cancel.
+            let hi = f64::from_bits(a.hi.to_bits() + rng.random_range(0..3));
+            let lo = random_lo(rng, hi);
+            normalize(-hi, lo)
+        }
+        _ => random_dd(rng, EXP_RANGE),
+    };
+    (a, b)
+}
+
+#[test]
+fn mp_qadd_qsub() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    let mut expected = BigFloat::new(PREC);
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let (a, b) = random_pair(&mut rng);
+        let msg = || format!("{a:?} + {b:?}");
+
+        expected.assign(big(a) + big(b));
+        let res = __gcc_qadd(a.hi, a.lo, b.hi, b.lo);
+        if expected.is_zero() {
+            assert_eq!((res.hi, res.lo), (0.0, 0.0), "{}", msg());
+        } else {
+            assert_close(res, &expected, 3, msg);
+        }
+
+        expected.assign(big(a) - big(b));
+        let res = __gcc_qsub(a.hi, a.lo, b.hi, b.lo);
+        if !expected.is_zero() {
+            assert_close(res, &expected, 3, || format!("{a:?} - {b:?}"));
+        }
+    }
+}
+
+#[test]
+fn mp_qmul() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    let mut expected = BigFloat::new(PREC);
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let a = random_dd(&mut rng, EXP_RANGE);
+        let b = random_dd(&mut rng, EXP_RANGE);
+
+        expected.assign(big(a) * big(b));
+        let res = __gcc_qmul(a.hi, a.lo, b.hi, b.lo);
+        assert_close(res, &expected, 5, || format!("{a:?} * {b:?}"));
+    }
+}
+
+#[test]
+fn mp_qdiv() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    let mut expected = BigFloat::new(PREC);
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let a = random_dd(&mut rng, EXP_RANGE);
+        let b = random_dd(&mut rng, EXP_RANGE);
+
+        expected.assign(big(a) / big(b));
+        let res = __gcc_qdiv(a.hi, a.lo, b.hi, b.lo);
+        assert_close(res, &expected, 15, || format!("{a:?} / {b:?}"));
+    }
+
+    // Small dividends get scaled to keep the remainder exact.
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let a = random_dd(&mut rng, -1000..-960);
+        let b = random_dd(&mut rng, -100..-60);
+
+        expected.assign(big(a) / big(b));
+        let res = __gcc_qdiv(a.hi, a.lo, b.hi, b.lo);
+        assert_close(res, &expected, 15, || format!("{a:?} / {b:?}"));
+    }
+}
+
+#[test]
+fn special_values() {
+    let inf = f64::INFINITY;
+    let nan = f64::NAN;
+    let big = f64::MAX;
+
+    let cases = [
+        (__gcc_qadd(inf, 0.0, 1.0, 0.0), inf),
+        (__gcc_qadd(-0.0, 0.0, -0.0, 0.0), -0.0),
+        (__gcc_qsub(-0.0, 0.0, 0.0, 0.0), -0.0),
+        (__gcc_qsub(1.0, 1e-20, 1.0, 1e-20), 0.0),
+        (__gcc_qadd(big, 0.0, big, 0.0), inf),
+        (__gcc_qmul(inf, 0.0, -2.0, 0.0), -inf),
+        (__gcc_qmul(-0.0, 0.0, 2.0, 1e-20), -0.0),
+        (__gcc_qmul(big, 0.0, 2.0, 0.0), inf),
+        (__gcc_qdiv(1.0, 0.0, 0.0, 0.0), inf),
+        (__gcc_qdiv(-1.0, 0.0, inf, 0.0), -0.0),
+    ];
+
+    for (i, (res, expected)) in cases.into_iter().enumerate() {
+        assert!(
+            res.hi.biteq(expected) && res.lo.biteq(0.0),
+            "case {i}: {res:?}"
+        );
+    }
+
+    for res in [
+        __gcc_qadd(inf, 0.0, -inf, 0.0),
+        __gcc_qadd(nan, 0.0, 1.0, 0.0),
+        __gcc_qmul(inf, 0.0, 0.0, 0.0),
+        __gcc_qdiv(0.0, 0.0, 0.0, 0.0),
+        __gcc_qdiv(inf, 0.0, inf, 0.0),
+    ] {
+        assert!(res.hi.is_nan() && res.lo == 0.0, "{res:?}");
+    }
+}
+
+#[test]
+fn mp_int_to_dd() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+
+    /// The result must be the nearest `f64` plus the nearest `f64` to what remains.
+    #[track_caller]
+    fn check(res: DoubleDouble, i: Integer) {
+        let hi = BigFloat::with_val(53, &i);
+        let mut rem = i.clone();
+        rem -= hi.to_integer().unwrap();
+        let lo = BigFloat::with_val(53, &rem);
+        assert_eq!((res.hi, res.lo), (hi.to_f64(), lo.to_f64()), "{i}");
+    }
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        // Bias towards narrower values.
+        let shift = rng.random_range(0..128);
+        let u = rng.random::<u128>() >> shift;
+        let i = rng.random::<i128>() >> shift;
+
+        check(__floatuntitf(u), u.into());
+        check(__floattitf(i), i.into());
+        check(__floatunditf(u as u64), (u as u64).into());
+        check(__floatditf(i as i64), (i as i64).into());
+        check(__floatunsitf(u as u32), (u as u32).into());
+        check(__floatsitf(i as i32), (i as i32).into());
+    }
+
+    for u in [u128::MAX, u128::MAX - (1 << 74), 1 << 127, (1 << 54) + 1] {
+        check(__floatuntitf(u), u.into());
+    }
+    for i in [i128::MAX, i128::MIN, i128::MIN + 1, -(1 << 54) - 1] {
+        check(__floattitf(i), i.into());
+    }
+    for i in [i64::MAX, i64::MIN, i64::MIN + 1] {
+        check(__floatditf(i), i.into());
+    }
+}
+
+#[test]
+fn mp_dd_to_int() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    let mut int = Integer::new();
+
+    let check = |x: DoubleDouble, int: &mut Integer| {
+        let msg = format!("{x:?}");
+        if x.hi.is_nan() {
+            int.assign(0);
+        } else if x.hi.is_infinite() {
+            int.assign(if x.hi < 0.0 { i32::MIN } else { i32::MAX });
+            *int <<= 1000;
+        } else {
+            int.assign(big(x).to_integer_round(Round::Zero).unwrap().0);
+        }
+
+        let (a, aa) = (x.hi, x.lo);
+        assert_eq!(
+            __fixunstfsi(a, aa),
+            int.clone().clamp(&0, &u32::MAX).to_u32().unwrap(),
+            "{msg}"
+        );
+        assert_eq!(
+            __fixunstfdi(a, aa),
+            int.clone().clamp(&0, &u64::MAX).to_u64().unwrap(),
+            "{msg}"
+        );
+        assert_eq!(
+            __fixunstfti(a, aa),
+            int.clone().clamp(&0, &u128::MAX).to_u128().unwrap(),
+            "{msg}"
+        );
+        assert_eq!(
+            __fixtfsi(a, aa),
+            int.clone().clamp(&i32::MIN, &i32::MAX).to_i32().unwrap(),
+            "{msg}"
+        );
+        assert_eq!(
+            __fixtfdi(a, aa),
+            int.clone().clamp(&i64::MIN, &i64::MAX).to_i64().unwrap(),
+            "{msg}"
+        );
+        assert_eq!(
+            __fixtfti(a, aa),
+            int.clone().clamp(&i128::MIN, &i128::MAX).to_i128().unwrap(),
+            "{msg}"
+        );
+    };
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        // Cover values with fractional parts up to those that saturate 128-bit integers.
+        let x = random_dd(&mut rng, -4..132);
+        check(x, &mut int);
+    }
+
+    let edges = [
+        (1.0, -f64::EPSILON / 4.0),
+        (-1.0, f64::EPSILON / 4.0),
+        (0.5, -f64::EPSILON / 8.0),
+        (-0.0, 0.0),
+        (2f64.powi(63), -0.5),
+        (2f64.powi(63), -2f64.powi(-80)),
+        (-2f64.powi(63), -0.5),
+        (2f64.powi(64), -2f64.powi(-80)),
+        (2f64.powi(127), -1.0),
+        (-2f64.powi(127), -1.0),
+        (2f64.powi(128), -1.0),
+        (5.0, -2f64.powi(-80)),
+        (f64::INFINITY, 0.0),
+        (f64::NEG_INFINITY, 0.0),
+        (f64::NAN, 0.0),
+    ];
+    for (hi, lo) in edges {
+        check(DoubleDouble { hi, lo }, &mut int);
+    }
+}
+
+#[test]
+fn random_bits_do_not_panic() {
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let [a, aa, c, cc] = [(); 4].map(|_| f64::from_bits(rng.random()));
+        __gcc_qadd(a, aa, c, cc);
+        __gcc_qsub(a, aa, c, cc);
+        __gcc_qmul(a, aa, c, cc);
+        __gcc_qdiv(a, aa, c, cc);
+        __fixtfti(a, aa);
+        __fixunstfti(a, aa);
+    }
+}
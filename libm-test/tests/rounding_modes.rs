@@ -0,0 +1,443 @@
+//! Test the `*_round` variants of the soft float routines against MPFR, in all rounding modes and
+//! including the exception flags.
+
+#![cfg(feature = "build-mpfr")]
+#![cfg_attr(f16_enabled, feature(f16))]
+#![cfg_attr(f128_enabled, feature(f128))]
+
+use std::any::type_name;
+use std::cmp::Ordering;
+use std::fmt;
+
+use compiler_builtins::float::add::add_round;
+use compiler_builtins::float::conv::int_to_float_round;
+use compiler_builtins::float::div::div_round;
+use compiler_builtins::float::mul::mul_round;
+use compiler_builtins::float::sub::sub_round;
+use compiler_builtins::float::trunc::trunc_round;
+use compiler_builtins::float::{FpResult, Round, Status};
+use libm::support::CastFrom;
+use libm_test::generate::random::SEED;
+use libm_test::{Float, MinInt, bigint_fuzz_iteration_count};
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rug::Assign;
+use rug::az::{self, Az};
+use rug::float::Round as MpRound;
+use rug::ops::{AddAssignRound, AssignRound, DivAssignRound, MulAssignRound, SubAssignRound};
+
+type MpFloat = rug::Float;
+type BinOp<F> = fn(F, F, Round) -> FpResult<F>;
+
+const ROUNDS: [Round; 4] = [
+    Round::Nearest,
+    Round::Negative,
+    Round::Positive,
+    Round::Zero,
+];
+
+/// The exception flags, which compare more readably than `Status`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Flags {
+    invalid: bool,
+    divide_by_zero: bool,
+    overflow: bool,
+    underflow: bool,
+    inexact: bool,
+}
+
+impl From<Status> for Flags {
+    fn from(status: Status) -> Self {
+        Self {
+            invalid: status.invalid(),
+            divide_by_zero: status.divide_by_zero(),
+            overflow: status.overflow(),
+            underflow: status.underflow(),
+            inexact: status.inexact(),
+        }
+    }
+}
+
+fn mp_round(round: Round) -> MpRound {
+    match round {
+        Round::Nearest => MpRound::Nearest,
+        Round::Negative => MpRound::Down,
+        Round::Positive => MpRound::Up,
+        Round::Zero => MpRound::Zero,
+    }
+}
+
+fn prec<F: Float>() -> u32 {
+    F::SIG_BITS + 1
+}
+
+fn mp<F: Float>(x: F) -> MpFloat
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    let mut res = MpFloat::new(prec::<F>());
+    res.assign(x);
+    res
+}
+
+/// Turn an MPFR result `r` that was rounded to the precision of `F` with ternary value `ord` into
+/// the expected value and flags. This accounts for the limited exponent range of `F`, with
+/// tininess detected after rounding.
+fn finish<F: Float>(mut r: MpFloat, ord: Ordering, round: Round) -> (F, Flags)
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    let mut flags = Flags::default();
+
+    if r.is_nan() {
+        flags.invalid = true;
+        return (F::NAN, flags);
+    }
+
+    if !r.is_finite() || r.is_zero() {
+        assert_eq!(ord, Ordering::Equal);
+        return ((&r).az::<F>(), flags);
+    }
+
+    let negative = r.is_sign_negative();
+    if r.clone().abs() > mp(F::MAX) {
+        let to_inf = match round {
+            Round::Nearest => true,
+            Round::Negative => negative,
+            Round::Positive => !negative,
+            Round::Zero => false,
+        };
+        let abs = if to_inf { F::INFINITY } else { F::MAX };
+        flags.overflow = true;
+        flags.inexact = true;
+        return (if negative { -abs } else { abs }, flags);
+    }
+
+    let tiny = r.clone().abs() < mp(F::MIN_POSITIVE_NORMAL);
+    let ord = r.subnormalize_ieee_round(ord, mp_round(round));
+    flags.inexact = ord != Ordering::Equal;
+    flags.underflow = tiny && flags.inexact;
+    ((&r).az::<F>(), flags)
+}
+
+#[track_caller]
+fn assert_res<F: Float>(res: FpResult<F>, expected: (F, Flags), msg: impl Fn() -> String) {
+    let (val, flags) = expected;
+    let same = if val.is_nan() {
+        res.val.is_nan()
+    } else {
+        res.val.to_bits() == val.to_bits()
+    };
+    assert!(
+        same && Flags::from(res.status) == flags,
+        "{}\nresult:   {:?} ({:#x}) {:?}\nexpected: {val:?} ({:#x}) {flags:?}",
+        msg(),
+        res.val,
+        res.val.to_bits(),
+        Flags::from(res.status),
+        val.to_bits(),
+    );
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+        };
+        f.write_str(s)
+    }
+}
+
+fn is_snan<F: Float>(x: F) -> bool {
+    x.is_nan() && x.to_bits() & (F::IMPLICIT_BIT >> 1) == F::Int::ZERO
+}
+
+/// The expected result of `a op b`.
+fn expected<F: Float>(op: Op, a: F, b: F, round: Round) -> (F, Flags)
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    if a.is_nan() || b.is_nan() {
+        let flags = Flags {
+            invalid: is_snan(a) || is_snan(b),
+            ..Default::default()
+        };
+        return (F::NAN, flags);
+    }
+
+    let mode = mp_round(round);
+    let mut r = mp(a);
+    let b_mp = mp(b);
+    let ord = match op {
+        Op::Add => r.add_assign_round(&b_mp, mode),
+        Op::Sub => r.sub_assign_round(&b_mp, mode),
+        Op::Mul => r.mul_assign_round(&b_mp, mode),
+        Op::Div => r.div_assign_round(&b_mp, mode),
+    };
+
+    let mut res = finish(r, ord, round);
+    if let Op::Div = op {
+        res.1.divide_by_zero = b == F::ZERO && a != F::ZERO && !a.is_infinite();
+    }
+    res
+}
+
+fn check_op<F: Float>(op: Op, f: BinOp<F>, a: F, b: F, round: Round)
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    let msg = || format!("{a:?} {op} {b:?}, {round:?}");
+    assert_res(f(a, b, round), expected(op, a, b, round), msg);
+}
+
+fn random_bits<F: Float>(rng: &mut ChaCha8Rng) -> F
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    F::from_bits(F::Int::cast_from_lossy(rng.random::<u128>()))
+}
+
+/// A random value with an exponent near that of `a`, so that addition can cancel and rounding
+/// is not always trivial.
+fn random_near<F: Float>(rng: &mut ChaCha8Rng, a: F) -> F
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    let delta = rng.random_range(-(F::SIG_BITS as i32 + 4)..=F::SIG_BITS as i32 + 4);
+    let exp = (a.ex() as i32 + delta).clamp(0, F::EXP_SAT as i32 - 1);
+    let sig = random_bits::<F>(rng).to_bits();
+    F::from_parts(rng.random(), exp as u32, sig)
+}
+
+fn special_values<F: Float>() -> Vec<F> {
+    let one = F::ONE;
+    let min_subnormal = F::from_bits(F::Int::ONE);
+    let snan = F::from_bits(F::EXP_MASK | F::Int::ONE);
+    let mut values = vec![
+        F::ZERO,
+        one,
+        one + F::EPSILON,
+        F::MAX,
+        F::MIN_POSITIVE_NORMAL,
+        min_subnormal,
+        F::from_bits(F::IMPLICIT_BIT - F::Int::ONE),
+        F::INFINITY,
+        F::NAN,
+        snan,
+    ];
+    values.extend(values.clone().into_iter().map(|x| -x));
+    values
+}
+
+fn check_binops<F: Float>(add: BinOp<F>, sub: BinOp<F>, mul: BinOp<F>, div: BinOp<F>)
+where
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F>,
+    F::Int: CastFrom<u128>,
+{
+    let ops = [
+        (Op::Add, add),
+        (Op::Sub, sub),
+        (Op::Mul, mul),
+        (Op::Div, div),
+    ];
+    let specials = special_values::<F>();
+
+    for round in ROUNDS {
+        for &a in &specials {
+            for &b in &specials {
+                for (op, f) in ops {
+                    check_op(op, f, a, b, round);
+                }
+            }
+        }
+    }
+
+    let mut rng = ChaCha8Rng::from_seed(*SEED);
+    for _ in 0..bigint_fuzz_iteration_count() {
+        let round = ROUNDS[rng.random_range(0..ROUNDS.len())];
+        let a = random_bits::<F>(&mut rng);
+        let b = if rng.random() {
+            random_bits::<F>(&mut rng)
+        } else {
+            random_near(&mut rng, a)
+        };
+
+        for (op, f) in ops {
+            check_op(op, f, a, b, round);
+        }
+    }
+}
+
+#[test]
+#[cfg(f16_enabled)]
+fn binops_f16() {
+    check_binops::<f16>(add_round, sub_round, mul_round, div_round);
+}
+
+#[test]
+fn binops_f32() {
+    check_binops::<f32>(add_round, sub_round, mul_round, div_round);
+}
+
+#[test]
+fn binops_f64() {
+    check_binops::<f64>(add_round, sub_round, mul_round, div_round);
+}
+
+#[test]
+#[cfg(f128_enabled)]
+fn binops_f128() {
+    check_binops::<f128>(add_round, sub_round, mul_round, div_round);
+}
+
+fn check_int_to_float<I, F: Float>(f: fn(I, Round) -> FpResult<F>, i: I, round: Round)
+where
+    I: Copy + fmt::Debug,
+    for<'a> &'a MpFloat: az::Cast<F>,
+    MpFloat: Assign<F> + AssignRound<I, Round = MpRound, Ordering = Ordering>,
+    F::Int: CastFrom<u128>,
+{
+    let mut r = MpFloat::new(prec::<F>());
+    let ord = r.assign_round(i, mp_round(round));
+    let msg = || format!("{i:?} to {}, {round:?}", type_name::<F>());
+    assert_res(f(i, round), finish(r, ord, round), msg);
+}
+
+macro_rules! int_to_float_tests {
+    ($($(#[$attr:meta])* $name:ident: $I:ty => $F:ty;)*) => {$(
+        #[test]
+        $(#[$attr])*
+        fn $name() {
+            let f: fn($I, Round) -> FpResult<$F> = int_to_float_round;
+            for round in ROUNDS {
+                for i in [<$I>::MIN, <$I>::MAX, <$I>::MAX - 1, <$I>::MAX >> 1, 0, 1] {
+                    check_int_to_float(f, i, round);
+                }
+            }
+
+            let mut rng = ChaCha8Rng::from_seed(*SEED);
+            for _ in 0..bigint_fuzz_iteration_count() {
+                let round = ROUNDS[rng.random_range(0..ROUNDS.len())];
+                // Vary the number of significant bits.
+                let i = rng.random::<$I>() >> rng.random_range(0..<$I>::BITS);
+                check_int_to_float(f, i, round);
+            }
+        }
+    )*};
+}
+
+int_to_float_tests! {
+    #[cfg(f16_enabled)]
+    u32_to_f16: u32 => f16;
+    #[cfg(f16_enabled)]
+    i128_to_f16: i128 => f16;
+    i32_to_f32: i32 => f32;
+    u32_to_f32: u32 => f32;
+    i64_to_f32: i64 => f32;
+    u64_to_f32: u64 => f32;
+    i128_to_f32: i128 => f32;
+    u128_to_f32: u128 => f32;
+    i32_to_f64: i32 => f64;
+    u32_to_f64: u32 => f64;
+    i64_to_f64: i64 => f64;
+    u64_to_f64: u64 => f64;
+    i128_to_f64: i128 => f64;
+    u128_to_f64: u128 => f64;
+    #[cfg(f128_enabled)]
+    u64_to_f128: u64 => f128;
+    #[cfg(f128_enabled)]
+    i128_to_f128: i128 => f128;
+    #[cfg(f128_enabled)]
+    u128_to_f128: u128 => f128;
+}
+
+fn check_trunc<F, R>(f: fn(F, Round) -> FpResult<R>, a: F, round: Round)
+where
+    F: Float,
+    R: Float,
+    for<'a> &'a MpFloat: az::Cast<F> + az::Cast<R>,
+    MpFloat: Assign<F> + Assign<R>,
+    F::Int: CastFrom<u128>,
+    R::Int: CastFrom<u128>,
+{
+    let msg = || format!("{a:?} to {}, {round:?}", type_name::<R>());
+    if a.is_nan() {
+        let flags = Flags {
+            invalid: is_snan(a),
+            ..Default::default()
+        };
+        assert_res(f(a, round), (R::NAN, flags), msg);
+        return;
+    }
+
+    let mut r = MpFloat::new(prec::<R>());
+    let ord = r.assign_round(&mp(a), mp_round(round));
+    assert_res(f(a, round), finish(r, ord, round), msg);
+}
+
+macro_rules! trunc_tests {
+    ($($(#[$attr:meta])* $name:ident: $F:ty => $R:ty;)*) => {$(
+        #[test]
+        $(#[$attr])*
+        fn $name() {
+            let f: fn($F, Round) -> FpResult<$R> = trunc_round;
+            for round in ROUNDS {
+                for a in special_values::<$F>() {
+                    check_trunc(f, a, round);
+                }
+            }
+
+            // Exponents that are in range for the narrower type, or just outside it.
+            let exp_range = <$R>::EXP_MIN - <$R>::SIG_BITS as i32 - 2..=<$R>::EXP_MAX + 1;
+
+            let mut rng = ChaCha8Rng::from_seed(*SEED);
+            for _ in 0..bigint_fuzz_iteration_count() {
+                let round = ROUNDS[rng.random_range(0..ROUNDS.len())];
+                let mut a = random_bits::<$F>(&mut rng);
+                if rng.random() {
+                    let exp = rng.random_range(exp_range.clone()) + <$F>::EXP_BIAS as i32;
+                    a = <$F>::from_parts(rng.random(), exp as u32, a.to_bits());
+                }
+                check_trunc(f, a, round);
+            }
+        }
+    )*};
+}
+
+trunc_tests! {
+    #[cfg(f16_enabled)]
+    f32_to_f16: f32 => f16;
+    #[cfg(f16_enabled)]
+    f64_to_f16: f64 => f16;
+    f64_to_f32: f64 => f32;
+    #[cfg(all(f16_enabled, f128_enabled))]
+    f128_to_f16: f128 => f16;
+    #[cfg(f128_enabled)]
+    f128_to_f32: f128 => f32;
+    #[cfg(f128_enabled)]
+    f128_to_f64: f128 => f64;
+}
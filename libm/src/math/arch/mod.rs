@@ -17,7 +17,8 @@ cfg_select_nofmt! {
         };
     }
     target_feature = "sse2" => {
-        mod x86;
+        // `compiler-builtins` also uses the feature detection for `memcpy`.
+        pub(crate) mod x86;
         pub use x86::{sqrt, sqrtf, fma, fmaf};
     }
     all(
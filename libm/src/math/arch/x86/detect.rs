@@ -19,6 +19,8 @@ pub mod cpu_flags {
         SSE,
         SSE2,
         ERMSB,
+        FSRM,
+        AVX2,
         MOVRS,
         FMA,
         FMA4,
@@ -57,14 +59,15 @@ fn load_x86_features() -> Flags {
     //   value for subsequent calls of `cpuinfo` in range [0, 0x8000_0000].
     // - The vendor ID is stored in 12 u8 ascii chars, returned in EBX, EDX, and ECX
     //   (in that order)
-    let mut vendor_id = [0u8; 12];
+    //
+    // The vendor ID is kept as the raw words rather than copied into a byte array, since this
+    // also runs the first time `compiler-builtins`' `memcpy` is called.
+    let vendor_id;
     let max_basic_leaf;
     unsafe {
         let CpuidResult { eax, ebx, ecx, edx } = __cpuid(0);
         max_basic_leaf = eax;
-        vendor_id[0..4].copy_from_slice(&ebx.to_ne_bytes());
-        vendor_id[4..8].copy_from_slice(&edx.to_ne_bytes());
-        vendor_id[8..12].copy_from_slice(&ecx.to_ne_bytes());
+        vendor_id = (ebx, edx, ecx);
     }
 
     if max_basic_leaf < 1 {
@@ -115,6 +118,7 @@ fn load_x86_features() -> Flags {
     enable(proc_info_edx, 25, cpu_flags::SSE);
     enable(proc_info_edx, 26, cpu_flags::SSE2);
     enable(extended_features_ebx, 9, cpu_flags::ERMSB);
+    enable(extended_features_edx, 4, cpu_flags::FSRM);
     enable(extended_features_eax_leaf_1, 31, cpu_flags::MOVRS);
 
     // `XSAVE` and `AVX` support:
@@ -166,6 +170,7 @@ fn load_x86_features() -> Flags {
 
                 // FMA (uses 256-bit wide registers):
                 enable(proc_info_ecx, 12, cpu_flags::FMA);
+                enable(extended_features_ebx, 5, cpu_flags::AVX2);
 
                 // For AVX-512 the OS also needs to support saving/restoring
                 // the extended state, only then we enable AVX-512 support:
@@ -188,7 +193,7 @@ fn load_x86_features() -> Flags {
     // (AMD64 Architecture Programmer's Manual, Appendix E).
     // Related Hygon kernel patch can be found on
     // http://lkml.kernel.org/r/5ce86123a7b9dad925ac583d88d2f921040e859b.1538583282.git.puwen@hygon.cn
-    if vendor_id == *b"AuthenticAMD" || vendor_id == *b"HygonGenuine" {
+    if vendor_id == vendor_words(b"AuthenticAMD") || vendor_id == vendor_words(b"HygonGenuine") {
         // These features are available on AMD arch CPUs:
         enable(extended_proc_info_ecx, 16, cpu_flags::FMA4);
     }
@@ -196,6 +201,12 @@ fn load_x86_features() -> Flags {
     value
 }
 
+/// Split a vendor ID into the EBX, EDX and ECX words that `cpuid` returns it in.
+fn vendor_words(id: &[u8; 12]) -> (u32, u32, u32) {
+    let word = |i: usize| u32::from_le_bytes([id[i], id[i + 1], id[i + 2], id[i + 3]]);
+    (word(0), word(4), word(8))
+}
+
 #[cfg(test)]
 mod tests {
     extern crate std;
@@ -216,6 +227,8 @@ mod tests {
                 cpu_flags::SSE => is_x86_feature_detected!("sse"),
                 cpu_flags::SSE2 => is_x86_feature_detected!("sse2"),
                 cpu_flags::ERMSB => is_x86_feature_detected!("ermsb"),
+                cpu_flags::FSRM => continue, // not yet supported in std
+                cpu_flags::AVX2 => is_x86_feature_detected!("avx2"),
                 cpu_flags::MOVRS => continue, // only very recent support in std
                 cpu_flags::FMA => is_x86_feature_detected!("fma"),
                 cpu_flags::FMA4 => continue, // not yet supported in std
@@ -1,6 +1,6 @@
 //! Architecture-specific support for x86-32 with SSE2 (i686) and x86-64.
 
-mod detect;
+pub(crate) mod detect;
 mod fma;
 mod sqrt;
 
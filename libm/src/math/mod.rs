@@ -72,14 +72,16 @@ cfg_select_nofmt! {
         pub mod generic;
     }
     _ => {
-        mod generic;
+        pub(crate) mod generic;
     }
 }
 
 pub mod relaxed;
 
+// Also used by `compiler-builtins` for feature detection
+pub(crate) mod arch;
+
 // Private modules
-mod arch;
 mod expo2;
 mod k_cos;
 mod k_cosf;
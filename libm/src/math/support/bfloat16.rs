@@ -0,0 +1,285 @@
+//! The "brain" floating point format, which has the exponent range of `f32` but only 8 bits of
+//! precision.
+
+use core::{fmt, ops};
+
+use super::float_traits::{f32_to_bits, round_to_odd};
+use super::{Float, f32_from_bits};
+
+/// A 16-bit float with a 1 bit sign, 8 bit exponent, and 7 bit significand.
+///
+/// This is the top half of an `f32`. Rust has no primitive for it, so this wraps the bits and
+/// does arithmetic in `f32`. With more than twice the precision, rounding the `f32` result of a
+/// basic operation again gives the correctly rounded `bf16` result.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+#[allow(non_camel_case_types)]
+pub struct bf16(u16);
+
+#[allow(dead_code)] // only used by compiler-builtins and tests
+impl bf16 {
+    /// Raw transmutation from `u16`.
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    /// Raw transmutation to `u16`.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Widen to `f32`, which is always exact.
+    pub const fn to_f32(self) -> f32 {
+        f32_from_bits((self.0 as u32) << 16)
+    }
+
+    /// Round an `f32` to the nearest `bf16`, ties to even. NaNs keep their sign and the upper
+    /// part of their payload but are always made quiet.
+    pub const fn from_f32(x: f32) -> Self {
+        let bits = f32_to_bits(x);
+        if bits & !f32::SIGN_MASK > f32::EXP_MASK {
+            return Self((bits >> 16) as u16 | Self::SIG_TOP_BIT);
+        }
+
+        let round = 0x7fff + ((bits >> 16) & 1);
+        Self(((bits + round) >> 16) as u16)
+    }
+}
+
+impl Float for bf16 {
+    type Int = u16;
+    type SignedInt = i16;
+
+    const ZERO: Self = Self(0);
+    const NEG_ZERO: Self = Self(Self::SIGN_MASK);
+    const ONE: Self = Self::from_f32(1.0);
+    const NEG_ONE: Self = Self::from_f32(-1.0);
+    const INFINITY: Self = Self(Self::EXP_MASK);
+    const NEG_INFINITY: Self = Self(Self::SIGN_MASK | Self::EXP_MASK);
+    const MAX: Self = Self(Self::EXP_MASK - 1);
+    const MIN: Self = Self(Self::SIGN_MASK | (Self::EXP_MASK - 1));
+
+    // Truncating the `f32` NaNs keeps the significand's top bits, including the platform's
+    // choice of quiet bit.
+    const NAN: Self = Self((f32_to_bits(<f32 as Float>::NAN) >> 16) as u16);
+    const SNAN: Self = Self((f32_to_bits(<f32 as Float>::SNAN) >> 16) as u16);
+    const NEG_NAN: Self = Self(Self::NAN.0 | Self::SIGN_MASK);
+    const NEG_SNAN: Self = Self(Self::SNAN.0 | Self::SIGN_MASK);
+
+    const EPSILON: Self = Self::from_f32(1.0 / 128.0);
+
+    const MIN_POSITIVE_NORMAL: Self = Self(Self::IMPLICIT_BIT);
+    const MIN_POSITIVE_SUBNORMAL: Self = Self(1);
+
+    const PI: Self = Self::from_f32(core::f32::consts::PI);
+    const NEG_PI: Self = Self::from_f32(-core::f32::consts::PI);
+    const FRAC_PI_2: Self = Self::from_f32(core::f32::consts::FRAC_PI_2);
+
+    const BITS: u32 = 16;
+    const SIG_BITS: u32 = 7;
+
+    const SIGN_MASK: Self::Int = 1 << (Self::BITS - 1);
+    const SIG_MASK: Self::Int = (1 << Self::SIG_BITS) - 1;
+    const EXP_MASK: Self::Int = !(Self::SIGN_MASK | Self::SIG_MASK);
+    const IMPLICIT_BIT: Self::Int = 1 << Self::SIG_BITS;
+    const SIG_TOP_BIT: Self::Int = Self::IMPLICIT_BIT >> 1;
+
+    fn to_bits(self) -> Self::Int {
+        self.0
+    }
+
+    fn eq_repr(self, rhs: Self) -> bool {
+        if self.is_nan() && rhs.is_nan() {
+            true
+        } else {
+            self.0 == rhs.0
+        }
+    }
+
+    fn is_nan(self) -> bool {
+        self.0 & !Self::SIGN_MASK > Self::EXP_MASK
+    }
+
+    fn is_infinite(self) -> bool {
+        self.0 & !Self::SIGN_MASK == Self::EXP_MASK
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.0 & Self::SIGN_MASK != 0
+    }
+
+    fn from_bits(a: Self::Int) -> Self {
+        Self(a)
+    }
+
+    fn abs(self) -> Self {
+        Self(self.0 & !Self::SIGN_MASK)
+    }
+
+    fn copysign(self, other: Self) -> Self {
+        Self((self.0 & !Self::SIGN_MASK) | (other.0 & Self::SIGN_MASK))
+    }
+
+    fn fma(self, y: Self, z: Self) -> Self {
+        // The product is exact in `f64`. Round the sum to odd, first in `f64` and then in `f32`,
+        // which keeps enough information that the final rounding to nearest is correct.
+        let p = f64::from(self.to_f32()) * f64::from(y.to_f32());
+        let z = f64::from(z.to_f32());
+        let s = p + z;
+        if !s.is_finite() {
+            return Self::from_f32(s as f32);
+        }
+
+        let b = s - p;
+        let err = (p - (s - b)) + (z - b);
+        let s = round_to_odd(s, err);
+
+        let n = s as f32;
+        if n.is_infinite() {
+            return Self::from_f32(n);
+        }
+
+        // Only the sign of the error matters, and the difference may not be representable.
+        let wide = f64::from(n);
+        let err = if s > wide {
+            1.0
+        } else if s < wide {
+            -1.0
+        } else {
+            0.0
+        };
+        Self::from_f32(round_to_odd(n, err))
+    }
+
+    fn normalize(significand: Self::Int) -> (i32, Self::Int) {
+        let shift = significand.leading_zeros().wrapping_sub(Self::EXP_BITS);
+        (1i32.wrapping_sub(shift as i32), significand << shift)
+    }
+}
+
+macro_rules! impl_binop {
+    ($($Op:ident, $OpAssign:ident, $op:ident, $op_assign:ident, $tok:tt;)*) => {
+        $(
+            impl ops::$Op for bf16 {
+                type Output = Self;
+                fn $op(self, rhs: Self) -> Self::Output {
+                    Self::from_f32(self.to_f32() $tok rhs.to_f32())
+                }
+            }
+
+            impl ops::$OpAssign for bf16 {
+                fn $op_assign(&mut self, rhs: Self) {
+                    *self = ops::$Op::$op(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+
+impl_binop!(
+    Add, AddAssign, add, add_assign, +;
+    Sub, SubAssign, sub, sub_assign, -;
+    Mul, MulAssign, mul, mul_assign, *;
+    Div, DivAssign, div, div_assign, /;
+    Rem, RemAssign, rem, rem_assign, %;
+);
+
+impl ops::Neg for bf16 {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self(self.0 ^ Self::SIGN_MASK)
+    }
+}
+
+impl PartialEq for bf16 {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_f32() == other.to_f32()
+    }
+}
+
+impl PartialOrd for bf16 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
+    }
+}
+
+impl fmt::Debug for bf16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_f32(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_f32_rounding() {
+        // Exact
+        assert_eq!(bf16::from_f32(1.0).to_bits(), 0x3f80);
+        assert_eq!(bf16::from_f32(-0.0).to_bits(), 0x8000);
+        // Below, above, and at halfway with an even and odd truncated value
+        assert_eq!(bf16::from_f32(f32::from_bits(0x3f807fff)).to_bits(), 0x3f80);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x3f808001)).to_bits(), 0x3f81);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x3f808000)).to_bits(), 0x3f80);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x3f818000)).to_bits(), 0x3f82);
+        // Overflow and subnormals
+        assert_eq!(bf16::from_f32(f32::MAX).to_bits(), 0x7f80);
+        assert_eq!(bf16::from_f32(-f32::MAX).to_bits(), 0xff80);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x7f7f7fff)).to_bits(), 0x7f7f);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x00008000)).to_bits(), 0x0000);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x00008001)).to_bits(), 0x0001);
+        assert_eq!(bf16::from_f32(f32::from_bits(0x007fffff)).to_bits(), 0x0080);
+        // NaNs stay NaNs, even if the payload is only in the low bits
+        assert!(bf16::from_f32(f32::from_bits(0x7f800001)).is_nan());
+        assert!(bf16::from_f32(f32::from_bits(0xff800001)).is_sign_negative());
+        assert!(bf16::from_f32(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(bf16::ONE.to_f32(), 1.0);
+        assert_eq!(bf16::MAX.to_f32(), f32::from_bits(0x7f7f0000));
+        assert_eq!(
+            bf16::EPSILON.to_f32(),
+            bf16::from_bits(0x3f81).to_f32() - 1.0
+        );
+        assert_eq!(bf16::PI.to_bits(), 0x4049);
+        assert!(bf16::NAN.is_qnan());
+        assert!(bf16::SNAN.is_snan());
+        assert!(bf16::NEG_NAN.is_nan() && bf16::NEG_NAN.is_sign_negative());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let x = bf16::from_f32(3.0);
+        let y = bf16::from_f32(0.5);
+        assert_eq!((x + y).to_f32(), 3.5);
+        assert_eq!((x - y).to_f32(), 2.5);
+        assert_eq!((x * y).to_f32(), 1.5);
+        assert_eq!((x / y).to_f32(), 6.0);
+        assert_eq!((x % y).to_f32(), 0.0);
+        assert_eq!((-x).to_f32(), -3.0);
+        assert!(x > y);
+        assert!(bf16::ZERO == bf16::NEG_ZERO);
+        assert!(bf16::NAN != bf16::NAN);
+    }
+
+    #[test]
+    fn fma_rounds_once() {
+        // (1 + 2^-4)^2 = 1 + 2^-3 + 2^-8 is halfway between two `bf16` values. A tiny addend
+        // breaks the tie, which would be lost if the sum were rounded to `f32` first.
+        let a = bf16::from_f32(1.0 + 1.0 / 16.0);
+        let b = bf16::from_f32(1.0 + 1.0 / 16.0);
+        let tiny = bf16::from_f32(-f32::from_bits(0x0080_0000));
+        assert_eq!(a.fma(b, bf16::ZERO).to_bits(), 0x3f90);
+        assert_eq!(a.fma(b, tiny).to_bits(), 0x3f90);
+        assert_eq!(a.fma(b, -tiny).to_bits(), 0x3f91);
+        assert!(a.fma(b, bf16::NAN).is_nan());
+        assert!(bf16::INFINITY.fma(bf16::ZERO, bf16::ONE).is_nan());
+        assert_eq!(
+            bf16::MAX.fma(bf16::MAX, bf16::NEG_INFINITY),
+            bf16::NEG_INFINITY
+        );
+    }
+}
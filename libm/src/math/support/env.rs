@@ -95,6 +95,18 @@ impl Status {
     /// is not represented by one of the other flags.
     pub const INEXACT: Self = Self(1 << 4);
 
+    /// True if `INVALID` is set.
+    #[cfg_attr(not(feature = "unstable-public-internals"), allow(dead_code))]
+    pub const fn invalid(self) -> bool {
+        self.0 & Self::INVALID.0 != 0
+    }
+
+    /// True if `DIVIDE_BY_ZERO` is set.
+    #[cfg_attr(not(feature = "unstable-public-internals"), allow(dead_code))]
+    pub const fn divide_by_zero(self) -> bool {
+        self.0 & Self::DIVIDE_BY_ZERO.0 != 0
+    }
+
     /// True if `UNDERFLOW` is set.
     #[cfg_attr(not(feature = "unstable-public-internals"), allow(dead_code))]
     pub const fn underflow(self) -> bool {
@@ -0,0 +1,393 @@
+//! The x87 80-bit extended precision format, which is `long double` for most C compilers
+//! targeting x86.
+
+use core::{fmt, ops};
+
+use super::float_traits::round_to_odd;
+use super::{Float, Hex};
+
+/// An 80-bit float with a 1 bit sign, 15 bit exponent, and 64 bit significand.
+///
+/// Unlike the IEEE interchange formats, the x87 format stores the integer bit of the significand
+/// explicitly. For canonical values that bit is redundant (it is set exactly when the exponent is
+/// nonzero), so this type drops it and stores a 79-bit representation with an implicit bit
+/// instead. That is what `Float::to_bits` returns and is what lets the generic conversion routines
+/// work with this type unchanged; use [`f80::from_x87_bits`] and [`f80::to_x87_bits`] for the
+/// layout in memory.
+///
+/// The 79-bit layout is the same as the top of an `f128`, which has the same exponent range and
+/// a significand more than 48 bits wider. Arithmetic is done in `f128` and rounded to odd before
+/// narrowing so that the result is only rounded once.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+#[allow(non_camel_case_types)]
+pub struct f80(u128);
+
+#[allow(dead_code)] // only used by compiler-builtins and tests
+impl f80 {
+    /// Distance between the 79-bit representation and `f128`.
+    const F128_SHIFT: u32 = f128::SIG_BITS - Self::SIG_BITS;
+
+    /// The explicit integer bit in the x87 layout.
+    const X87_INT_BIT: u128 = 1 << 63;
+
+    /// Create a value from the 80 bits stored in memory, which are the low bits of `bits`.
+    ///
+    /// Encodings that a 387 or later FPU treats as invalid operands (unnormals, pseudo-infinities,
+    /// and pseudo-NaNs; i.e. a nonzero exponent without the integer bit) become the default NaN,
+    /// which is what the hardware produces for them. Pseudo-denormals are representable with the
+    /// minimum normal exponent so they keep their value.
+    pub const fn from_x87_bits(bits: u128) -> Self {
+        let sign = (bits >> 79) & 1;
+        let exp = (bits >> 64) & 0x7fff;
+        let int_bit = bits & Self::X87_INT_BIT != 0;
+        let frac = bits & Self::SIG_MASK;
+
+        let exp = match (exp, int_bit) {
+            (0, false) => 0,
+            (0, true) => 1,
+            (_, true) => exp,
+            (_, false) => return Self::NEG_NAN,
+        };
+
+        Self((sign << 78) | (exp << Self::SIG_BITS) | frac)
+    }
+
+    /// Return the 80 bits that are stored in memory, with the integer bit set for normal numbers,
+    /// infinities, and NaNs.
+    pub const fn to_x87_bits(self) -> u128 {
+        let int_bit = if self.0 & Self::EXP_MASK != 0 {
+            Self::X87_INT_BIT
+        } else {
+            0
+        };
+
+        ((self.0 >> Self::SIG_BITS) << 64) | int_bit | (self.0 & Self::SIG_MASK)
+    }
+
+    /// Widen to `f128`, which is always exact.
+    pub const fn to_f128(self) -> f128 {
+        f128::from_bits(self.0 << Self::F128_SHIFT)
+    }
+
+    /// Round an `f128` to the nearest `f80`, ties to even. NaNs keep their sign and the upper
+    /// part of their payload but are always made quiet.
+    pub const fn from_f128(x: f128) -> Self {
+        let bits = x.to_bits();
+        if bits & !f128::SIGN_MASK > f128::EXP_MASK {
+            return Self((bits >> Self::F128_SHIFT) | Self::SIG_TOP_BIT);
+        }
+
+        let round = (1 << (Self::F128_SHIFT - 1)) - 1 + ((bits >> Self::F128_SHIFT) & 1);
+        Self((bits + round) >> Self::F128_SHIFT)
+    }
+}
+
+impl Float for f80 {
+    type Int = u128;
+    type SignedInt = i128;
+
+    const ZERO: Self = Self(0);
+    const NEG_ZERO: Self = Self(Self::SIGN_MASK);
+    const ONE: Self = Self::from_f128(1.0);
+    const NEG_ONE: Self = Self::from_f128(-1.0);
+    const INFINITY: Self = Self(Self::EXP_MASK);
+    const NEG_INFINITY: Self = Self(Self::SIGN_MASK | Self::EXP_MASK);
+    const MAX: Self = Self(Self::EXP_MASK - 1);
+    const MIN: Self = Self(Self::SIGN_MASK | (Self::EXP_MASK - 1));
+
+    // x87 has no MIPS-style NaNs. The default NaN (the "real indefinite") is negative.
+    const NAN: Self = Self(Self::EXP_MASK | Self::SIG_TOP_BIT);
+    const SNAN: Self = Self(Self::EXP_MASK | (Self::SIG_TOP_BIT >> 1));
+    const NEG_NAN: Self = Self(Self::NAN.0 | Self::SIGN_MASK);
+    const NEG_SNAN: Self = Self(Self::SNAN.0 | Self::SIGN_MASK);
+
+    const EPSILON: Self = Self::from_f128(1.0 / (1u128 << 63) as f128);
+
+    const MIN_POSITIVE_NORMAL: Self = Self(Self::IMPLICIT_BIT);
+    const MIN_POSITIVE_SUBNORMAL: Self = Self(1);
+
+    const PI: Self = Self::from_f128(core::f128::consts::PI);
+    const NEG_PI: Self = Self::from_f128(-core::f128::consts::PI);
+    const FRAC_PI_2: Self = Self::from_f128(core::f128::consts::FRAC_PI_2);
+
+    // The integer bit is not included, see the type's documentation.
+    const BITS: u32 = 79;
+    const SIG_BITS: u32 = 63;
+
+    const SIGN_MASK: Self::Int = 1 << (Self::BITS - 1);
+    const SIG_MASK: Self::Int = (1 << Self::SIG_BITS) - 1;
+    const EXP_MASK: Self::Int = (Self::SIGN_MASK - 1) & !Self::SIG_MASK;
+    const IMPLICIT_BIT: Self::Int = 1 << Self::SIG_BITS;
+    const SIG_TOP_BIT: Self::Int = Self::IMPLICIT_BIT >> 1;
+
+    fn to_bits(self) -> Self::Int {
+        self.0
+    }
+
+    fn eq_repr(self, rhs: Self) -> bool {
+        if self.is_nan() && rhs.is_nan() {
+            true
+        } else {
+            self.0 == rhs.0
+        }
+    }
+
+    fn is_nan(self) -> bool {
+        self.0 & !Self::SIGN_MASK > Self::EXP_MASK
+    }
+
+    fn is_infinite(self) -> bool {
+        self.0 & !Self::SIGN_MASK == Self::EXP_MASK
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.0 & Self::SIGN_MASK != 0
+    }
+
+    fn from_bits(a: Self::Int) -> Self {
+        Self(a & (Self::SIGN_MASK | (Self::SIGN_MASK - 1)))
+    }
+
+    fn abs(self) -> Self {
+        Self(self.0 & !Self::SIGN_MASK)
+    }
+
+    fn copysign(self, other: Self) -> Self {
+        Self((self.0 & !Self::SIGN_MASK) | (other.0 & Self::SIGN_MASK))
+    }
+
+    /// Note that unlike the basic operations, this rounds twice (to `f128` then to `f80`) so the
+    /// result may be off by one ulp when the `f128` result lands exactly halfway.
+    fn fma(self, y: Self, z: Self) -> Self {
+        Self::from_f128(super::super::fmaf128(
+            self.to_f128(),
+            y.to_f128(),
+            z.to_f128(),
+        ))
+    }
+
+    fn normalize(significand: Self::Int) -> (i32, Self::Int) {
+        // `Int` is wider than `BITS` so the unused top bits need to be skipped.
+        let shift = significand
+            .leading_zeros()
+            .wrapping_sub(u128::BITS - Self::SIG_BITS - 1);
+        (1i32.wrapping_sub(shift as i32), significand << shift)
+    }
+}
+
+impl ops::Add for f80 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        let (a, b) = (self.to_f128(), rhs.to_f128());
+        let s = a + b;
+        if !s.is_finite() {
+            return Self::from_f128(s);
+        }
+
+        let bb = s - a;
+        let err = (a - (s - bb)) + (b - bb);
+        Self::from_f128(round_to_odd(s, err))
+    }
+}
+
+impl ops::Sub for f80 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl ops::Mul for f80 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (a, b) = (self.to_f128(), rhs.to_f128());
+        let p = a * b;
+        if !p.is_finite() {
+            return Self::from_f128(p);
+        }
+
+        let err = super::super::fmaf128(a, b, -p);
+        Self::from_f128(round_to_odd(p, err))
+    }
+}
+
+impl ops::Div for f80 {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        let (a, b) = (self.to_f128(), rhs.to_f128());
+        let q = a / b;
+        if !q.is_finite() || !b.is_finite() || q == 0.0 {
+            return Self::from_f128(q);
+        }
+
+        // `a - q * b` has the sign of the error scaled by `b`.
+        let r = super::super::fmaf128(-q, b, a);
+        let err = if b.is_sign_negative() { -r } else { r };
+        Self::from_f128(round_to_odd(q, err))
+    }
+}
+
+impl ops::Rem for f80 {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self::Output {
+        // The remainder is always exact.
+        Self::from_f128(super::super::fmodf128(self.to_f128(), rhs.to_f128()))
+    }
+}
+
+macro_rules! impl_op_assign {
+    ($($Op:ident, $OpAssign:ident, $op:ident, $op_assign:ident;)*) => {
+        $(
+            impl ops::$OpAssign for f80 {
+                fn $op_assign(&mut self, rhs: Self) {
+                    *self = ops::$Op::$op(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+
+impl_op_assign!(
+    Add, AddAssign, add, add_assign;
+    Sub, SubAssign, sub, sub_assign;
+    Mul, MulAssign, mul, mul_assign;
+    Div, DivAssign, div, div_assign;
+    Rem, RemAssign, rem, rem_assign;
+);
+
+impl ops::Neg for f80 {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self(self.0 ^ Self::SIGN_MASK)
+    }
+}
+
+impl PartialEq for f80 {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_f128() == other.to_f128()
+    }
+}
+
+impl PartialOrd for f80 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.to_f128().partial_cmp(&other.to_f128())
+    }
+}
+
+impl fmt::Debug for f80 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&Hex(*self), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn x87_layout() {
+        let one = 0x3fff_8000_0000_0000_0000;
+        assert_eq!(f80::ONE.to_x87_bits(), one);
+        assert_eq!(f80::from_x87_bits(one).to_bits(), f80::ONE.to_bits());
+        assert_eq!(f80::NEG_INFINITY.to_x87_bits(), 0xffff_8000_0000_0000_0000);
+        assert_eq!(f80::NAN.to_x87_bits(), 0x7fff_c000_0000_0000_0000);
+        assert_eq!(f80::MAX.to_x87_bits(), 0x7ffe_ffff_ffff_ffff_ffff);
+        // Subnormals have no integer bit
+        assert_eq!(f80::MIN_POSITIVE_SUBNORMAL.to_x87_bits(), 1);
+        assert_eq!(
+            f80::from_x87_bits(0x8000_0000_0000_0000_0001).to_bits(),
+            f80::SIGN_MASK | 1
+        );
+        // Pseudo-denormals have the value of the smallest exponent
+        assert_eq!(
+            f80::from_x87_bits(0x0000_8000_0000_0000_0001).to_x87_bits(),
+            0x0001_8000_0000_0000_0001
+        );
+        // Unnormals, pseudo-infinities, and pseudo-NaNs are invalid
+        assert!(f80::from_x87_bits(0x3fff_0000_0000_0000_0000).is_nan());
+        assert!(f80::from_x87_bits(0x7fff_0000_0000_0000_0000).is_nan());
+        assert!(f80::from_x87_bits(0x7fff_4000_0000_0000_0000).is_nan());
+        // Bits above the first 80 are ignored
+        assert_eq!(
+            f80::from_x87_bits((u128::MAX << 80) | one).to_bits(),
+            f80::ONE.to_bits()
+        );
+    }
+
+    #[test]
+    fn from_f128_rounding() {
+        let one = f128::from_bits(0x3fff_0000_0000_0000_0000_0000_0000_0000);
+        let ulp = 1 << 49;
+        let near = |x: u128| f80::from_f128(f128::from_bits(one.to_bits() + x)).to_bits();
+        assert_eq!(near(0), f80::ONE.to_bits());
+        // Below, above, and at halfway with an even and odd truncated value
+        assert_eq!(near(ulp / 2 - 1), f80::ONE.to_bits());
+        assert_eq!(near(ulp / 2 + 1), f80::ONE.to_bits() + 1);
+        assert_eq!(near(ulp / 2), f80::ONE.to_bits());
+        assert_eq!(near(ulp + ulp / 2), f80::ONE.to_bits() + 2);
+        // Overflow and subnormals
+        assert!(f80::from_f128(f128::MAX).is_infinite());
+        assert_eq!(f80::from_f128(f128::from_bits(ulp / 2)).to_bits(), 0);
+        assert_eq!(f80::from_f128(f128::from_bits(ulp / 2 + 1)).to_bits(), 1);
+        // NaNs stay NaNs, even if the payload is only in the low bits
+        assert!(f80::from_f128(f128::from_bits(f128::EXP_MASK | 1)).is_nan());
+        assert!(f80::from_f128(-f128::NAN).is_sign_negative());
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(f80::EPSILON.to_x87_bits(), 0x3fc0_8000_0000_0000_0000);
+        assert_eq!(f80::PI.to_x87_bits(), 0x4000_c90f_daa2_2168_c235);
+        assert_eq!(f80::EXP_BITS, 15);
+        assert_eq!(f80::EXP_BIAS, 16383);
+        assert!(f80::NAN.is_qnan());
+        assert!(f80::SNAN.is_snan());
+        assert!(f80::NEG_NAN.is_nan() && f80::NEG_NAN.is_sign_negative());
+        assert_eq!(f80::normalize(1), (-62, f80::IMPLICIT_BIT));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let x = f80::from_f128(3.0);
+        let y = f80::from_f128(0.5);
+        assert_eq!((x + y).to_f128(), 3.5);
+        assert_eq!((x - y).to_f128(), 2.5);
+        assert_eq!((x * y).to_f128(), 1.5);
+        assert_eq!((x / y).to_f128(), 6.0);
+        assert_eq!((x % y).to_f128(), 0.0);
+        assert_eq!((-x).to_f128(), -3.0);
+        assert!(x > y);
+        assert!(f80::ZERO == f80::NEG_ZERO);
+        assert!(f80::NAN != f80::NAN);
+    }
+
+    #[test]
+    fn basic_ops_round_once() {
+        // 1 + 2^-63 + 2^-113 needs more than `f128` precision. It is above the halfway point
+        // between `1` and `1 + 2^-63`, which rounding to `f128` first would lose.
+        let one = f80::ONE;
+        let half_ulp = f80::from_f128(f128::from_bits(0x3fbf_0000_0000_0000_0000_0000_0000_0000));
+        let tiny = f80::from_f128(f128::from_bits(0x3f8e_0000_0000_0000_0000_0000_0000_0000));
+        assert_eq!((one + half_ulp).to_bits(), one.to_bits());
+        assert_eq!(((one + tiny) + half_ulp).to_bits(), one.to_bits());
+        assert_eq!((one + (half_ulp + tiny)).to_bits(), one.to_bits() + 1);
+        assert_eq!((one - (half_ulp + tiny)).to_bits(), one.to_bits() - 1);
+
+        // (1 + 2^-32)^2 = 1 + 2^-31 + 2^-64 is exactly halfway, ties to even.
+        let a =
+            f80::ONE + f80::from_f128(f128::from_bits(0x3fdf_0000_0000_0000_0000_0000_0000_0000));
+        assert_eq!((a * a).to_x87_bits(), 0x3fff_8000_0001_0000_0000);
+        // (1 + 2^-32 + 2^-63)^2 is just above halfway so it rounds up.
+        let b = a + f80::EPSILON;
+        assert_eq!((b * b).to_x87_bits(), 0x3fff_8000_0001_0000_0003);
+
+        assert_eq!(
+            (f80::ONE / f80::from_f128(3.0)).to_x87_bits(),
+            0x3ffd_aaaa_aaaa_aaaa_aaab
+        );
+        assert!((f80::ZERO / f80::ZERO).is_nan());
+        assert!((f80::ONE / f80::ZERO).is_infinite());
+        assert_eq!((f80::ONE / f80::INFINITY).to_bits(), 0);
+    }
+}
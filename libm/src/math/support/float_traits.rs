@@ -456,6 +456,22 @@ pub const fn f64_to_bits(x: f64) -> u64 {
     unsafe { mem::transmute::<f64, u64>(x) }
 }
 
+/// Given a value `x` that was rounded to nearest and the rounding error `err` (of which only the
+/// sign is used), return the value rounded to odd instead.
+pub fn round_to_odd<F: Float>(x: F, err: F) -> F {
+    if err == F::ZERO || x.to_bits() & F::Int::ONE != F::Int::ZERO {
+        return x;
+    }
+
+    if x == F::ZERO {
+        F::MIN_POSITIVE_SUBNORMAL.copysign(err)
+    } else if x.is_sign_negative() == err.is_sign_negative() {
+        F::from_bits(x.to_bits() + F::Int::ONE)
+    } else {
+        F::from_bits(x.to_bits() - F::Int::ONE)
+    }
+}
+
 /// Trait for floats that are wider than a `NFloat` type.
 pub trait WideFloat: Float {
     /// Float that is half the bit width of the floatthis trait is implemented for.
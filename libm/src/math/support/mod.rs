@@ -1,7 +1,10 @@
 #[macro_use]
 pub mod macros;
+mod bfloat16;
 mod big;
 mod env;
+#[cfg(f128_enabled)]
+mod float80;
 // Runtime feature detection requires atomics.
 #[cfg(target_has_atomic = "ptr")]
 pub(crate) mod feature_detect;
@@ -10,6 +13,8 @@ pub mod hex_float;
 mod int_traits;
 mod modular;
 
+#[allow(unused_imports)]
+pub use bfloat16::bf16;
 #[allow(unused_imports)]
 pub use big::{i256, u256};
 // Clippy seems to have a false positive
@@ -19,6 +24,9 @@ pub use env::{FpResult, Round, Status};
 #[allow(unused_imports)]
 pub use float_traits::{Float, HalfRep, IntTy, NarrowFloat, WideFloat};
 pub(crate) use float_traits::{f32_from_bits, f64_from_bits};
+#[cfg(f128_enabled)]
+#[allow(unused_imports)]
+pub use float80::f80;
 #[cfg(f16_enabled)]
 #[allow(unused_imports)]
 pub use hex_float::hf16;